@@ -1,14 +1,28 @@
 #![allow(dead_code)]
 
+pub mod backend;
+pub mod button_learning;
 pub mod calibrate;
+pub mod curve;
 pub mod datas;
+pub mod hotplug;
+pub mod led;
 pub mod logic;
+pub mod power;
 pub mod xbox;
 pub mod ps4;
+pub mod recorder;
+pub mod rumble;
+pub mod scripted;
+pub mod slots;
+pub mod switch_pro;
+#[cfg(target_os = "linux")]
+pub mod evdev_input;
 
 // ---------------------- 外部依赖 ----------------------
 use crate::adaptive_sampler::AdaptiveSampler;
 use crate::controller::datas::{CompactPressureDatas, ControllerButtons, ControllerDatas};
+use crate::controller::slots::{self, PerSlot, MAX_SLOTS, PRIMARY_SLOT};
 use crate::{controller, mapping, preset, xeno_utils};
 use gilrs::{Axis, Event, EventType, Gamepad, Gilrs, GilrsBuilder};
 use hidapi::HidApi;
@@ -26,11 +40,13 @@ use uuid::Uuid;
 
 use std::collections::HashMap;
 
-// --- 副预设切换状态 ---
+// --- 副预设切换状态（每个槽位独立） ---
 /// 用于追踪 Toggle 模式下的副预设激活状态
-static IS_SUB_PRESET_ACTIVE: Lazy<RwLock<bool>> = Lazy::new(|| RwLock::new(false));
+static IS_SUB_PRESET_ACTIVE: Lazy<PerSlot<RwLock<bool>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(false)));
 /// 用于检测按键单击事件 (按下后立即释放)
-static TOGGLE_BUTTON_LAST_STATE: Lazy<RwLock<bool>> = Lazy::new(|| RwLock::new(false));
+static TOGGLE_BUTTON_LAST_STATE: Lazy<PerSlot<RwLock<bool>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(false)));
 
 // ---------------------- 结构体定义 ----------------------
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -98,8 +114,8 @@ impl DeviceInfo {
 
 /// 全局应用句柄容器
 pub struct Handles {
-    /// Tauri 应用句柄
-    pub app_handle: AppHandle,
+    /// Tauri 应用句柄，无界面运行时为 None
+    pub app_handle: Option<AppHandle>,
     /// Windows XInput 句柄
     #[cfg(target_os = "windows")]
     pub xinput_handle: XInputHandle,
@@ -134,39 +150,52 @@ pub enum ControllerType {
 #[allow(dead_code)]
 static HANDLES: Lazy<Mutex<Option<Handles>>> = Lazy::new(|| Mutex::new(None));
 
-/// 当前选中的控制器设备（多线程读多，写少）
+/// 各槽位选中的控制器设备（多线程读多，写少），默认解引用到当前活动槽位
 #[allow(dead_code)]
-pub static CURRENT_DEVICE: Lazy<RwLock<DeviceInfo>> = Lazy::new(|| {
-    RwLock::new(DeviceInfo {
-        name: "".into(),
-        vendor_id: "".into(),
-        product_id: None,
-        sub_product_id: None,
-        uuid_is_invalid: true,
-        device_path: None,
-        controller_type: ControllerType::Other,
+pub static CURRENT_DEVICE: Lazy<PerSlot<RwLock<DeviceInfo>>> = Lazy::new(|| {
+    PerSlot::new(|| {
+        RwLock::new(DeviceInfo {
+            name: "".into(),
+            vendor_id: "".into(),
+            product_id: None,
+            sub_product_id: None,
+            uuid_is_invalid: true,
+            device_path: None,
+            controller_type: ControllerType::Other,
+        })
     })
 });
 
-/// 当前控制器采样数据（高频读取，偶尔写入）
-pub static CONTROLLER_DATA: Lazy<RwLock<ControllerDatas>> =
-    Lazy::new(|| RwLock::new(ControllerDatas::new()));
+/// 当前控制器采样数据（高频读取，偶尔写入），已应用死区与响应曲线
+pub static CONTROLLER_DATA: Lazy<PerSlot<RwLock<ControllerDatas>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(ControllerDatas::new())));
+
+/// 后端最近一次轮询（或回放）得到的输入，未经死区处理
+///
+/// 轮询失败时保留上一帧，每个周期都从这里复制一份再做死区塑形，
+/// 避免在 `CONTROLLER_DATA` 上重复叠加反死区、外死区等处理
+pub static POLLED_CONTROLLER_DATA: Lazy<PerSlot<RwLock<ControllerDatas>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(ControllerDatas::new())));
 
 /// 原始控制器采样数据，专用于校准
-pub static RAW_CONTROLLER_DATA: Lazy<RwLock<ControllerDatas>> =
-    Lazy::new(|| RwLock::new(ControllerDatas::new()));
+pub static RAW_CONTROLLER_DATA: Lazy<PerSlot<RwLock<ControllerDatas>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(ControllerDatas::new())));
+
+pub static PREV_CONTROLLER_DATA: Lazy<PerSlot<RwLock<ControllerDatas>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(ControllerDatas::new())));
+pub static PREV_BTN_DATA: Lazy<PerSlot<RwLock<u32>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(0)));
 
-pub static PREV_CONTROLLER_DATA: Lazy<RwLock<ControllerDatas>> =
-    Lazy::new(|| RwLock::new(ControllerDatas::new()));
-pub static PREV_BTN_DATA: Lazy<RwLock<u32>> =
-    Lazy::new(|| RwLock::new(0));
+pub static PREV_PRESSURE_DATA: Lazy<PerSlot<RwLock<CompactPressureDatas>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(CompactPressureDatas::new())));
 
-pub static PREV_PRESSURE_DATA: Lazy<RwLock<CompactPressureDatas>> =
-    Lazy::new(|| RwLock::new(CompactPressureDatas::new()));
+/// 摇杆旋转物理状态 (由 controller 模块独占)
+pub static JOYSTICK_ROTATION_STATES: Lazy<PerSlot<RwLock<HashMap<JoystickSource, JoystickRotationState>>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(HashMap::new())));
 
-/// 全局摇杆旋转物理状态 (由 controller 模块独占)
-pub static JOYSTICK_ROTATION_STATES: Lazy<RwLock<HashMap<JoystickSource, JoystickRotationState>>> =
-    Lazy::new(|| RwLock::new(HashMap::new()));
+/// 各槽位绑定的 gilrs 手柄 ID，用于区分多个同型号手柄
+pub static GILRS_GAMEPAD_ID: Lazy<PerSlot<RwLock<Option<usize>>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(None)));
 
 /// 自适应采样器实例（结构复杂，保持 Mutex）
 #[allow(dead_code)]
@@ -296,6 +325,10 @@ fn list_controllers_from_gilrs() -> Vec<DeviceInfo> {
 
     let mut devices = Vec::new();
     for (_id, gamepad) in gilrs.gamepads() {
+        // 跳过本程序创建的虚拟手柄
+        if gamepad.name() == mapping::virtual_pad::VIRTUAL_PAD_NAME {
+            continue;
+        }
         let vid_opt = gamepad.vendor_id();
         let pid_opt = gamepad.product_id();
 
@@ -328,32 +361,73 @@ fn list_controllers_from_gilrs() -> Vec<DeviceInfo> {
     devices
 }
 
+/// hidapi 枚举到的一个 HID 接口
+struct HidEntry {
+    vendor_id: String,
+    product_id: String,
+    path: String,
+    serial: Option<String>,
+}
+
+impl HidEntry {
+    /// 是否与已分配的接口属于同一个手柄（同一路径，或同型号且序列号相同）
+    fn same_device(&self, other: &HidEntry) -> bool {
+        self.path == other.path
+            || (self.serial.is_some()
+                && self.serial == other.serial
+                && self.vendor_id == other.vendor_id
+                && self.product_id == other.product_id)
+    }
+}
+
+/// 为设备补全 HID 路径与子 PID，每个 HID 手柄只分配给一个设备
+///
+/// 先按 VID+PID 精确匹配；仍未分配路径的设备再按 VID 匹配（HID 报告的 PID 与手柄 PID 不同，记为子 PID）。
+/// 两个同型号手柄因此会分别得到各自的路径，而不是都指向最后枚举到的那个
+fn assign_hid_paths(devices: &mut [DeviceInfo], hid_entries: &[HidEntry]) {
+    let mut assigned: Vec<&HidEntry> = Vec::new();
+    for exact in [true, false] {
+        for entry in hid_entries {
+            if assigned.iter().any(|a| a.same_device(entry)) {
+                continue;
+            }
+            // 已有路径的设备（如脚本设备）不参与匹配
+            let Some(d) = devices.iter_mut().filter(|d| d.device_path.is_none()).find(|d| {
+                d.vendor_id.eq_ignore_ascii_case(&entry.vendor_id)
+                    && (!exact
+                        || d.product_id.as_deref().is_some_and(|pid| pid.eq_ignore_ascii_case(&entry.product_id)))
+            }) else {
+                continue;
+            };
+            d.sub_product_id = Some(entry.product_id.clone());
+            d.device_path = Some(entry.path.clone());
+            assigned.push(entry);
+        }
+    }
+}
+
 fn list_controllers_should_manage() -> Vec<DeviceInfo> {
-    let mut devices = list_controllers_from_gilrs();
-    let hidapi = match HidApi::new() {
-        Ok(api) => api,
+    let mut devices = backend::enumerate();
+
+    // hidapi 不可用时仍返回脚本设备等不依赖 HID 路径的设备
+    let hid_entries = match HidApi::new() {
+        Ok(api) => api
+            .device_list()
+            .map(|device| HidEntry {
+                vendor_id: format!("{:04x}", device.vendor_id()),
+                product_id: format!("{:04x}", device.product_id()),
+                path: device.path().to_string_lossy().to_string(),
+                serial: device.serial_number().filter(|s| !s.is_empty()).map(str::to_string),
+            })
+            .collect::<Vec<_>>(),
         Err(e) => {
             log::error!("初始化 hidapi 失败: {e}");
-            return Vec::new();
+            Vec::new()
         }
     };
 
     // 遍历所有检测到的HID设备，并将路径信息和子PID补全
-    for device in hidapi.device_list() {
-        let vid = format!("{:04x}", device.vendor_id());
-        let pid = format!("{:04x}", device.product_id());
-
-        for d in devices.iter_mut() {
-            if d.vendor_id.eq_ignore_ascii_case(&vid) {
-                log::error!("({}/{}) - ({vid},{pid})", d.vendor_id, d.product_id.as_deref().unwrap_or("Unknown"));
-                d.sub_product_id = Some(pid.clone());
-                d.device_path = Some(device.path().to_string_lossy().to_string());
-                if d.device_path.is_none() {
-                    log::warn!("手柄路径缺失：{d:#?}");
-                }
-            }
-        }
-    }
+    assign_hid_paths(&mut devices, &hid_entries);
 
     devices.into_iter().filter(|d| {
         !((d.vendor_id.eq("0000") || d.vendor_id.is_empty()) && d.product_id.is_none())
@@ -364,15 +438,31 @@ fn list_controllers_should_manage() -> Vec<DeviceInfo> {
 /// 获取全局 Tauri 应用句柄
 ///
 /// # Panics
-/// 如果全局句柄未初始化会 panic
+/// 如果全局句柄未初始化或以无界面模式运行会 panic
 pub fn get_app_handle() -> AppHandle {
-    HANDLES
-        .lock()
-        .unwrap()
-        .as_ref()
-        .expect("HANDLES not initialized")
-        .app_handle
-        .clone()
+    try_get_app_handle().expect("HANDLES not initialized")
+}
+
+/// 获取全局 Tauri 应用句柄，未初始化或无界面运行时返回 None
+pub fn try_get_app_handle() -> Option<AppHandle> {
+    HANDLES.lock().unwrap().as_ref().and_then(|h| h.app_handle.clone())
+}
+
+/// 向前端与本地控制接口的订阅者发送事件，无界面运行时只推送给订阅者
+///
+/// 返回 false 表示发送失败
+pub fn emit_event<S: Serialize + Clone>(event: &str, payload: S) -> bool {
+    crate::ipc::broadcast(event, &payload);
+    let Some(app_handle) = try_get_app_handle() else {
+        return true;
+    };
+    match app_handle.emit(event, payload) {
+        Ok(()) => true,
+        Err(e) => {
+            log::error!("发送 {event} 事件失败: {e}");
+            false
+        }
+    }
 }
 
 /// 获取 XInput 句柄 (Windows only)
@@ -401,44 +491,73 @@ fn _find_device_by_name(name: &str) -> Option<DeviceInfo> {
 // ---------------------- Tauri 命令接口 ----------------------
 
 #[tauri::command]
-pub fn get_controller_data() -> ControllerDatas {
-    *CONTROLLER_DATA.read().unwrap()
+pub fn get_controller_data(slot: Option<usize>) -> ControllerDatas {
+    let slot = slot.unwrap_or(PRIMARY_SLOT).min(MAX_SLOTS - 1);
+    *CONTROLLER_DATA.slot(slot).read().unwrap()
+}
+
+/// 槽位信息（前端展示用）
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceSlotInfo {
+    pub slot: usize,
+    pub device: Option<DeviceInfo>,
+    pub preset: String,
+}
+
+/// 列出所有槽位当前使用的设备与预设
+#[tauri::command]
+pub fn list_device_slots() -> Vec<DeviceSlotInfo> {
+    (0..MAX_SLOTS)
+        .map(|slot| {
+            let device = CURRENT_DEVICE.slot(slot).read().unwrap().clone();
+            DeviceSlotInfo {
+                slot,
+                device: device.device_path.is_some().then_some(device),
+                preset: preset::CURRENT_PRESET.slot(slot).read().unwrap().name.clone(),
+            }
+        })
+        .collect()
 }
 
 /// 查询可用设备命令 (Tauri 前端调用)
 ///
 /// 触发 "update_devices" 事件通知前端
 #[tauri::command]
-pub fn query_devices(app: AppHandle) -> Vec<DeviceInfo> {
+pub fn query_devices() -> Vec<DeviceInfo> {
     let devices = _query_devices();
-    if let Err(e) = app.emit("update_devices", devices.clone()) {
-        log::error!("发送 update_devices 事件失败: {e}");
-    }
+    emit_event("update_devices", devices.clone());
     log::debug!("执行了 query_devices 命令");
     devices
 }
 
 /// 更新设置中上次连接的设备信息
 fn update_last_connected_device_setting(device_info: Option<DeviceInfo>) {
-    let mut settings = get_setting();
-    settings.last_connected_device = device_info.map(|d| LastConnectedDevice {
+    let last_connected_device = device_info.map(|d| LastConnectedDevice {
         vid: u16::from_str_radix(&d.vendor_id, 16).unwrap_or(0),
         pid: u16::from_str_radix(&d.product_id.unwrap_or_default(), 16).unwrap_or(0),
         sub_pid: u16::from_str_radix(&d.sub_product_id.unwrap_or_default(), 16).unwrap_or(0),
     });
-    let app_handle = get_app_handle();
-    tauri::async_runtime::spawn(async move {
-        if let Err(e) = setting::update_settings(app_handle, settings).await {
-            log::error!("保存上次连接设备信息失败: {e:?}");
-        }
-    });
+    setting::update_last_connected_device(last_connected_device);
 }
 
 /// 选择使用指定设备命令 (Tauri 前端调用)
+///
+/// `slot` 为空时使用主槽位
 #[tauri::command]
-pub fn use_device(device_name: String) -> bool {
-    log::debug!("尝试使用设备: {device_name}");
-    let device = _find_device_by_name(&device_name);
+pub fn use_device(device_name: String, slot: Option<usize>) -> bool {
+    match slots::resolve_slot(slot) {
+        Ok(slot) => slots::with_slot(slot, || use_device_in_slot(&device_name)),
+        Err(e) => {
+            log::error!("{e}");
+            false
+        }
+    }
+}
+
+fn use_device_in_slot(device_name: &str) -> bool {
+    let slot = slots::active_slot();
+    log::debug!("尝试在槽位 {slot} 使用设备: {device_name}");
+    let device = _find_device_by_name(device_name);
     match device {
         Some(mut device_info) => {
                 // 如果 hidapi 未检测到此设备（device_path 为 None），使用 WGI 标识符作为 fallback。
@@ -453,13 +572,33 @@ pub fn use_device(device_name: String) -> bool {
                 }
                 let mut current_device = CURRENT_DEVICE.write().unwrap();
             *current_device = device_info.clone();
-            log::info!("✅ 使用设备: {}", current_device.name);
+            *GILRS_GAMEPAD_ID.write().unwrap() = None;
+            log::info!("✅ [槽位 {slot}] 使用设备: {}", current_device.name);
 
             // 加载与此设备关联的校准数据
             crate::controller::calibrate::load_calibration(&device_info);
 
+            // 按设置创建虚拟手柄
+            if get_setting().virtual_pad_enabled {
+                mapping::virtual_pad::create();
+            }
+
             drop(current_device); // 显式释放锁
-            update_last_connected_device_setting(Some(device_info));
+
+            match backend::open(&device_info) {
+                Some(name) => log::info!("[槽位 {slot}] 输入后端: {name}"),
+                None => log::warn!("[槽位 {slot}] 暂无可用的输入后端，将在轮询时重试"),
+            }
+
+            if slot == PRIMARY_SLOT {
+                update_last_connected_device_setting(Some(device_info));
+            } else {
+                // 附加槽位首次使用时加载其预设的映射
+                let preset_name = preset::get_current_preset().name;
+                if let Err(e) = preset::switch_to_preset(&preset_name) {
+                    log::error!("槽位 {slot} 加载预设 {preset_name} 失败: {e}");
+                }
+            }
             true
         }
         None => {
@@ -469,36 +608,71 @@ pub fn use_device(device_name: String) -> bool {
     }
 }
 
+/// 断开设备连接命令 (Tauri 前端调用)
+///
+/// `slot` 为空时断开当前活动槽位（前端调用时即主槽位）
 #[tauri::command]
-pub fn disconnect_device() -> bool {
-    log::debug!("尝试断开设备连接");
+pub fn disconnect_device(slot: Option<usize>) -> bool {
+    match slots::resolve_slot(slot) {
+        Ok(slot) => slots::with_slot(slot, || disconnect_device_in_slot(true)),
+        Err(e) => {
+            log::error!("{e}");
+            false
+        }
+    }
+}
+
+/// 断开当前活动槽位的设备
+///
+/// `forget` 为 true 时同时清除主槽位记住的上次连接设备（用户主动断开）；
+/// 物理拔出时保留，以便设备重新插入后自动重连
+fn disconnect_device_in_slot(forget: bool) -> bool {
+    let slot = slots::active_slot();
+    log::debug!("尝试断开槽位 {slot} 的设备连接");
+    rumble::stop();
+    power::clear();
+    recorder::stop_slot();
     let mut current_device = CURRENT_DEVICE.write().unwrap();
     *current_device = default_devices()[0].clone();
-    log::info!("✅ 已断开当前设备");
+    *GILRS_GAMEPAD_ID.write().unwrap() = None;
+    log::info!("✅ 已断开槽位 {slot} 的设备");
+
+    backend::close_all();
+
+    // 销毁虚拟手柄，避免游戏读到残留的按键状态
+    mapping::virtual_pad::destroy();
 
     // 重置全局校准数据
     crate::controller::calibrate::reset_calibration();
 
     drop(current_device); // 显式释放锁
-    update_last_connected_device_setting(None);
+    if forget && slot == PRIMARY_SLOT {
+        update_last_connected_device_setting(None);
+    }
     true
 }
 
 /// 断开当前设备命令 (Tauri 前端调用)
 #[tauri::command]
-pub fn physical_disconnect_device() -> bool {
+pub fn physical_disconnect_device(slot: Option<usize>) -> bool {
     log::debug!("------ physical_disconnect_device");
-    let app_handle = get_app_handle();
-    let dname = {
-        let device = CURRENT_DEVICE.read().unwrap();
-        device.name.clone()
+    let slot = match slots::resolve_slot(slot) {
+        Ok(slot) => slot,
+        Err(e) => {
+            log::error!("{e}");
+            return false;
+        }
     };
-    log::info!("尝试断开设备: {dname}");
-    if let Err(e) = app_handle.emit("physical_connect_status", dname) {
-        log::error!("发送 physical_connect_status 事件失败: {e}");
-        return false;
+    let dname = CURRENT_DEVICE.slot(slot).read().unwrap().name.clone();
+    log::info!("尝试断开槽位 {slot} 的设备: {dname}");
+
+    // 前端只显示主槽位
+    if slot == PRIMARY_SLOT {
+        if !emit_event("physical_connect_status", dname) {
+            return false;
+        }
     }
-    disconnect_device()
+    slots::with_slot(slot, || disconnect_device_in_slot(false))
 }
 
 
@@ -528,37 +702,42 @@ pub fn set_frequency(freq: u32) {
     );
 }
 
+/// 手柄震动命令 (Tauri 前端调用)
+///
+/// `strength` 为 0.0-1.0，`slot` 为空时使用主槽位
 #[tauri::command]
-pub fn need_rumble() {
-
+pub fn need_rumble(strength: f32, duration_ms: u32, slot: Option<usize>) -> Result<(), String> {
+    let slot = slots::resolve_slot(slot)?;
+    slots::with_slot(slot, || rumble::rumble(strength, strength, duration_ms));
+    Ok(())
 }
 
 // ---------------------- 设备轮询 ----------------------
 
 pub fn pack_and_send_data(controller_data: &ControllerDatas) {
+    // 前端只显示主槽位的数据
+    if slots::active_slot() != PRIMARY_SLOT {
+        return;
+    }
+
     let mut prev_controller_data = PREV_CONTROLLER_DATA.write().unwrap();
-    if controller_data.eq(&prev_controller_data) {
+    // 只比较前端关心的紧凑数据，体感噪声等不触发发送
+    if controller_data.as_compact().eq(&prev_controller_data.as_compact()) {
         // 无变化，不发送数据
         return;
     }
 
     // 数据有变化则进一步比较具体值
     // 按键数据变化
-    let app_handle = get_app_handle();
-    let compact_data = controller_data.as_compact();
-    if let Err(e) = app_handle.emit("update_controller_compact_datas", compact_data) {
-        log::error!("按键数据发送失败: {e}");
-    }
-    drop(app_handle);
+    emit_event("update_controller_compact_datas", controller_data.as_compact());
+    crate::overlay::publish_controller(controller_data.as_compact());
 
     *prev_controller_data = *controller_data;
 }
 
 
-fn _poll_other_controllers(gamepad: Gamepad) {
+fn _poll_other_controllers(gamepad: Gamepad, controller_data: &mut ControllerDatas) {
     // 检测按键状态
-    let mut controller_data = CONTROLLER_DATA.write().unwrap();
-
     let buttons = [
         (gamepad.is_pressed(gilrs::Button::South), ControllerButtons::South),
         (gamepad.is_pressed(gilrs::Button::East), ControllerButtons::East),
@@ -575,6 +754,9 @@ fn _poll_other_controllers(gamepad: Gamepad) {
         (gamepad.is_pressed(gilrs::Button::Select), ControllerButtons::Back),
         (gamepad.is_pressed(gilrs::Button::Start), ControllerButtons::Start),
         (gamepad.is_pressed(gilrs::Button::Mode), ControllerButtons::Guide),
+        // gilrs 默认映射把 evdev 的 BTN_Z 报告为 Z，与 evdev 后端一致视为截图/分享键；
+        // gilrs 没有背键按钮，P1-P4 无法经此后端读取（见 backend 模块说明）
+        (gamepad.is_pressed(gilrs::Button::Z), ControllerButtons::Capture),
     ];
 
     let pressed_count: usize = buttons.iter().filter(|(p, _)| *p).count();
@@ -584,7 +766,8 @@ fn _poll_other_controllers(gamepad: Gamepad) {
     let cnt = POLL_CNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
     if cnt == 0 || cnt % 125 == 0 {
         log::debug!(
-            "gilrs 轮询 #{cnt}: 按键按下 {pressed_count}/15, 左摇杆=({:.3},{:.3}) 右摇杆=({:.3},{:.3})",
+            "gilrs 轮询 #{cnt}: 按键按下 {pressed_count}/{}, 左摇杆=({:.3},{:.3}) 右摇杆=({:.3},{:.3})",
+            buttons.len(),
             gamepad.axis_data(Axis::LeftStickX).map_or(0.0, |d| d.value()),
             gamepad.axis_data(Axis::LeftStickY).map_or(0.0, |d| d.value()),
             gamepad.axis_data(Axis::RightStickX).map_or(0.0, |d| d.value()),
@@ -616,10 +799,11 @@ fn _poll_other_controllers(gamepad: Gamepad) {
         controller_data.set_button(button, pressed);
     }
 
-    let raw_lx = gamepad.axis_data(Axis::LeftStickX).map_or(0.0, |d| d.value());
-    let raw_ly = gamepad.axis_data(Axis::LeftStickY).map_or(0.0, |d| d.value());
-    let raw_rx = gamepad.axis_data(Axis::RightStickX).map_or(0.0, |d| d.value());
-    let raw_ry = gamepad.axis_data(Axis::RightStickY).map_or(0.0, |d| d.value());
+    // 经 button_learning 读取，应用已学习的轴反向
+    let raw_lx = button_learning::axis_value(&gamepad, Axis::LeftStickX).unwrap_or(0.0);
+    let raw_ly = button_learning::axis_value(&gamepad, Axis::LeftStickY).unwrap_or(0.0);
+    let raw_rx = button_learning::axis_value(&gamepad, Axis::RightStickX).unwrap_or(0.0);
+    let raw_ry = button_learning::axis_value(&gamepad, Axis::RightStickY).unwrap_or(0.0);
 
     // // 将原始数据写入 RAW_CONTROLLER_DATA 供校准线程使用
     // {
@@ -640,26 +824,42 @@ fn _poll_other_controllers(gamepad: Gamepad) {
     controller_data.right_stick.is_pressed = gamepad.is_pressed(gilrs::Button::RightThumb);
     controller_data.left_stick.is_pressed = gamepad.is_pressed(gilrs::Button::LeftThumb);
 
+    // 学习为轴的扳机（LeftZ/RightZ，[-1, 1]）在没有模拟按键数据时换算到 [0, 1]
     controller_data.left_trigger.value = gamepad.button_data(gilrs::Button::LeftTrigger2)
                                                 .map(|data| data.value())
+                                                .or_else(|| button_learning::axis_value(&gamepad, Axis::LeftZ).map(|v| (v + 1.0) / 2.0))
                                                 .unwrap_or(0.0);
     controller_data.right_trigger.value = gamepad.button_data(gilrs::Button::RightTrigger2)
                                                  .map(|data| data.value())
+                                                 .or_else(|| button_learning::axis_value(&gamepad, Axis::RightZ).map(|v| (v + 1.0) / 2.0))
                                                  .unwrap_or(0.0);
 }
 
-/// 轮询非Xbox控制器状态
-fn poll_other_controllers(device: &DeviceInfo) {
+/// 通过 gilrs 轮询控制器状态，输入写入 `controller_data`
+/// 返回 false 表示 gilrs 中没有匹配的手柄
+fn poll_other_controllers(device: &DeviceInfo, controller_data: &mut ControllerDatas) -> bool {
     let gilrs_guard = GLOBAL_GILRS.lock().unwrap();
     let gilrs = gilrs_guard.as_ref().unwrap();
 
     let mut found = false;
 
+    // 同型号手柄可能有多个：优先使用本槽位已绑定的手柄，跳过其他槽位占用的手柄
+    let active_slot = slots::active_slot();
+    let bound_id = *GILRS_GAMEPAD_ID.read().unwrap();
+    let claimed_ids: Vec<usize> = (0..MAX_SLOTS)
+        .filter(|slot| *slot != active_slot)
+        .filter_map(|slot| *GILRS_GAMEPAD_ID.slot(slot).read().unwrap())
+        .collect();
+
     static POLL_OTHER_CNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
     let cnt = POLL_OTHER_CNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
 
     // 遍历所有已连接的游戏手柄
     for (_id, gamepad) in gilrs.gamepads() {
+        let gamepad_id: usize = _id.into();
+        if claimed_ids.contains(&gamepad_id) || bound_id.is_some_and(|id| id != gamepad_id) {
+            continue;
+        }
         let vid = format!("{:04x}", gamepad.vendor_id().unwrap());
         let pid = format!("{:04x}", gamepad.product_id().unwrap());
 
@@ -673,15 +873,24 @@ fn poll_other_controllers(device: &DeviceInfo) {
             );
         }
 
-        // 匹配当前设备
-        if vid.eq_ignore_ascii_case(&device.vendor_id)
+        // 匹配当前设备（排除本程序创建的虚拟手柄）
+        if gamepad.name() != mapping::virtual_pad::VIRTUAL_PAD_NAME
+            && vid.eq_ignore_ascii_case(&device.vendor_id)
             && device.product_id.as_deref().is_some_and(|d_pid| pid.eq_ignore_ascii_case(d_pid))
         {
             found = true;
-            _poll_other_controllers(gamepad);
+            *GILRS_GAMEPAD_ID.write().unwrap() = Some(gamepad_id);
+            power::update_from_gilrs(&gamepad);
+            _poll_other_controllers(gamepad, controller_data);
+            break;
         }
     }
 
+    if !found && bound_id.is_some() {
+        // 绑定的手柄已不在，下次重新匹配
+        *GILRS_GAMEPAD_ID.write().unwrap() = None;
+    }
+
     if !found && (cnt == 0 || cnt % 125 == 0) {
         log::warn!(
             "gilrs gamepads 中未找到匹配设备 (target: vid={}, pid={})",
@@ -689,57 +898,25 @@ fn poll_other_controllers(device: &DeviceInfo) {
             device.product_id.as_deref().unwrap_or("?")
         );
     }
+    found
 }
 
-/// 根据控制器类型分发轮询任务
+/// 按优先级依次尝试支持该设备的输入后端（见 [`backend`]），第一个成功的后端写入本周期数据
+///
+/// 后端只写入本地副本，成功后再一次性写回 `POLLED_CONTROLLER_DATA`，避免持有写锁时再去获取后端内部的锁
 fn poll_controller(device: &DeviceInfo) {
-    match device.controller_type {
-        // Xbox 控制器在 Windows 下优先走 XInput API（性能更好，不依赖 gilrs 事件队列）
-        // 若 XInput 未找到匹配设备（如第三方手柄仅 WGI 可见），回退 gilrs
-        ControllerType::Xbox => {
-            #[cfg(target_os = "windows")]
-            {
-                if !xbox::poll_xbox_controller(device) {
-                    static FALLBACK_CNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
-                    let fc = FALLBACK_CNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                    if fc % 125 == 0 {
-                        log::debug!("XInput 未匹配，回退 gilrs 轮询");
-                    }
-                    poll_other_controllers(device);
-                }
-            }
-            #[cfg(not(target_os = "windows"))]
-            {
-                poll_other_controllers(device)
-            }
-        }
-        ControllerType::PlayStation => {
-            // ps4::poll_ps4_controller(device);
-            poll_other_controllers(device);
-        }
-        _ => {
-            // 非标准控制器统一走 gilrs（依赖 SDL DB 映射，gilrs-core UUID 修复后生效）
-            poll_other_controllers(device)
-        }
+    let mut controller_data = *POLLED_CONTROLLER_DATA.read().unwrap();
+    if backend::poll(device, &mut controller_data) {
+        *POLLED_CONTROLLER_DATA.write().unwrap() = controller_data;
     }
 }
 
 // ---------------------- 后台任务 ----------------------
 /// 启动设备发现后台任务
 ///
-/// 每500ms扫描一次设备并发送更新事件
+/// 设备列表改为由热插拔事件驱动刷新，不再定时扫描（见 [`hotplug`]）
 pub fn polling_devices() {
-    // tauri::async_runtime::spawn(async move {
-    //     log::info!("🛠️ 控制器监听已启动...");
-    //     let app_handle = get_app_handle();
-    //     loop {
-    //         let devices = _query_devices();
-    //         if let Err(e) = app_handle.emit("update_devices", devices.clone()) {
-    //             log::error!("发送 update_devices 事件失败: {e}");
-    //         }
-    //         tokio::time::sleep(Duration::from_millis(500)).await;
-    //     }
-    // });
+    hotplug::initialize();
 }
 
 /// 处理预设切换决策, 返回是否应该使用副预设
@@ -793,66 +970,102 @@ fn handle_preset_switching_decision() -> bool {
 }
 
 
-/// 主设备状态监听循环
-pub fn listen() {
-    thread::spawn(|| {
-        log::info!("🎧 启动设备监听任务");
-        let mut last_device: Option<DeviceInfo> = None;
-
-        loop {
-            let time_start = Instant::now();
-
-            let current_device = CURRENT_DEVICE.read().unwrap().clone();
+/// 单个槽位的一次监听：跟踪设备连接状态并执行轮询与映射
+///
+/// 在 `slots::with_slot` 中调用，所有全局状态均作用于当前槽位
+fn listen_slot(last_device: &mut Option<DeviceInfo>, tick: Instant) {
+    let slot = slots::active_slot();
+    let current_device = CURRENT_DEVICE.read().unwrap().clone();
+
+    // 设备连接状态跟踪
+    let last_has_device = last_device.is_some();
+    let current_has_device = current_device.device_path.is_some();
+
+    match (last_has_device, current_has_device) {
+        (false, true) => {
+            log::info!("🔌 [槽位 {slot}] 连接新设备: {}", current_device.name);
+            *last_device = Some(current_device.clone());
+        }
+        (true, true)
+        if last_device.as_ref().unwrap().device_path != current_device.device_path =>
+            {
+                log::info!(
+                "🔄 [槽位 {slot}] 设备切换: {} → {}",
+                last_device.as_ref().unwrap().name,
+                current_device.name
+            );
+                *last_device = Some(current_device.clone());
+            }
+        (true, false) => {
+            if let Some(device) = &last_device {
+                log::info!("❌ [槽位 {slot}] 设备断开: {} ({}/{}/{})",
+                device.name, device.vendor_id,
+                device.product_id.as_deref().unwrap_or("Unknown"),
+                device.sub_product_id.as_deref().unwrap_or("Unknown"));
+            }
+            *last_device = None;
+        }
+        _ => (), // 无状态变化
+    }
 
-            // 设备连接状态跟踪
-            let last_has_device = last_device.is_some();
-            let current_has_device = current_device.device_path.is_some();
+    // 执行设备状态轮询
+    if let Some(device) = &last_device {
+        // 回放时用录制数据代替设备轮询
+        if recorder::is_replaying() {
+            recorder::replay_frame();
+        } else {
+            poll_controller(device);
+            recorder::record_frame(tick, &POLLED_CONTROLLER_DATA.read().unwrap());
+        }
 
-            match (last_has_device, current_has_device) {
-                (false, true) => {
-                    log::info!("🔌 连接新设备: {}", current_device.name);
-                    last_device = Some(current_device.clone());
-                }
-                (true, true)
-                if last_device.as_ref().unwrap().device_path != current_device.device_path =>
-                    {
-                        log::info!(
-                        "🔄 设备切换: {} → {}",
-                        last_device.as_ref().unwrap().name,
-                        current_device.name
-                    );
-                        last_device = Some(current_device.clone());
-                    }
-                (true, false) => {
-                    if let Some(device) = &last_device {
-                        log::info!("❌ 设备断开: {} ({}/{}/{})",
-                        device.name, device.vendor_id,
-                        device.product_id.as_deref().unwrap_or("Unknown"),
-                        device.sub_product_id.as_deref().unwrap_or("Unknown"));
-                    }
-                    last_device = None;
-                }
-                _ => (), // 无状态变化
-            }
+        // 一次性获取锁，减少锁操作；死区总是作用在本周期复制的原始输入上
+        let mut controller_data = CONTROLLER_DATA.write().unwrap();
+        controller_data.copy_input_from(&POLLED_CONTROLLER_DATA.read().unwrap());
+        logic::apply_deadzone(&mut controller_data);
+        logic::check_sticks_rotation(&mut controller_data);
+
+        let data_snapshot = *controller_data;
+        drop(controller_data); // 释放写锁
+
+        let use_sub_preset = handle_preset_switching_decision();
+        // 扳机调节的鼠标速度在本帧鼠标移动之前算出
+        mapping::analog::update_mouse_speed_scale(&data_snapshot, use_sub_preset);
+
+        pack_and_send_data(&data_snapshot);
+        mapping::handle_mouse_movement(&data_snapshot);
+        mapping::stick_scroll::handle_stick_scroll(&data_snapshot);
+        mapping::handle_gyro_mouse(&data_snapshot);
+        if get_setting().virtual_pad_passthrough_analog {
+            mapping::virtual_pad::sync_analog(&data_snapshot);
+        }
 
-            // 执行设备状态轮询
-            if let Some(device) = &last_device {
-                poll_controller(device);
+        led::sync(use_sub_preset);
+        if slot == PRIMARY_SLOT {
+            crate::overlay::sync_preset_state(use_sub_preset);
+        }
+        mapping::map(&mut CONTROLLER_DATA.write().unwrap(), use_sub_preset);
+    }
+}
 
-                // 一次性获取锁，减少锁操作
-                let mut controller_data = CONTROLLER_DATA.write().unwrap();
-                logic::apply_deadzone(&mut controller_data);
-                logic::check_sticks_rotation(&mut controller_data);
+/// 执行一个监听周期，依次处理每个槽位
+///
+/// `last_devices` 由调用方在周期之间保存；无界面运行或脚本后端驱动时可直接逐周期调用
+pub fn listen_tick(last_devices: &mut [Option<DeviceInfo>; MAX_SLOTS], tick: Instant) {
+    for (slot, last_device) in last_devices.iter_mut().enumerate() {
+        slots::with_slot(slot, || listen_slot(last_device, tick));
+    }
+}
 
-                let data_snapshot = *controller_data;
-                drop(controller_data); // 释放写锁
+/// 主设备状态监听循环，依次处理每个槽位
+pub fn listen() {
+    thread::spawn(|| {
+        log::info!("🎧 启动设备监听任务");
+        let mut last_devices: [Option<DeviceInfo>; MAX_SLOTS] = Default::default();
 
-                pack_and_send_data(&data_snapshot);
-                mapping::handle_mouse_movement(&data_snapshot);
+        loop {
+            let time_start = Instant::now();
 
-                let use_sub_preset = handle_preset_switching_decision();
-                mapping::map(&mut CONTROLLER_DATA.write().unwrap(), use_sub_preset);
-            }
+            listen_tick(&mut last_devices, time_start);
 
             let elapsed = time_start.elapsed();
             // log::info!("elapsed time: {:#?}", elapsed);
@@ -862,13 +1075,27 @@ pub fn listen() {
     });
 }
 
+/// 等待 gilrs 初始化完成（由 `gilrs_listen` 线程创建），超时返回 false
+pub fn wait_gilrs_ready(timeout: Duration) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        if GLOBAL_GILRS.lock().unwrap_or_else(|e| e.into_inner()).is_some() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    false
+}
+
 /// 初始化 Gilrs 事件监听线程
 pub fn gilrs_listen() {
     thread::spawn(move || {
-        let gilrs = GilrsBuilder::new()
+        let mut gilrs = GilrsBuilder::new()
         .add_mappings(include_str!("gamecontrollerdb_ext.txt"))
         .build()
         .expect("Failed to init Gilrs");
+        // 已连接手柄不一定会再收到 Connected 事件，启动时先应用一次本地按键映射
+        button_learning::apply_saved_mappings(&mut gilrs);
         {
             *GLOBAL_GILRS.lock().unwrap() = Some(gilrs);
         }
@@ -885,9 +1112,21 @@ pub fn gilrs_listen() {
                     if n == 0 || n % 125 == 0 {
                         log::debug!("gilrs_listen 事件 #{n}: id={id} event={event:?}");
                     }
-                    let _ = id;
+                    button_learning::capture_event(id, &event);
+                    if event == EventType::Connected {
+                        button_learning::apply_saved_mapping(gilrs, id);
+                        hotplug::notify(hotplug::HotplugEvent::Added);
+                    }
                     if event == EventType::Disconnected {
-                        let device = CURRENT_DEVICE.read().unwrap().clone();
+                        hotplug::notify(hotplug::HotplugEvent::Removed);
+
+                        // 找到绑定该手柄的槽位，未绑定时按主槽位处理
+                        let gamepad_id: usize = id.into();
+                        let slot = (0..MAX_SLOTS)
+                            .find(|slot| *GILRS_GAMEPAD_ID.slot(*slot).read().unwrap() == Some(gamepad_id))
+                            .unwrap_or(PRIMARY_SLOT);
+
+                        let device = CURRENT_DEVICE.slot(slot).read().unwrap().clone();
                         let controller_type = device.controller_type;
                         if device.eq(&default_devices()[0].clone()) {
                             log::warn!("设备已断开，跳过处理");
@@ -898,11 +1137,11 @@ pub fn gilrs_listen() {
                         #[cfg(target_os = "windows")]
                         if controller_type != ControllerType::Xbox {
                             log::warn!("---- 检测到设备断开，尝试物理断开设备");
-                            physical_disconnect_device();
+                            physical_disconnect_device(Some(slot));
                         }
 
                         #[cfg(not(target_os = "windows"))]
-                        physical_disconnect_device();
+                        physical_disconnect_device(Some(slot));
                     }
                     // if let EventType::AxisChanged(axis, value, code) = event {
                     //     log::info!("Axis {:?} changed: {}", axis, value);
@@ -923,9 +1162,9 @@ pub fn gilrs_listen() {
 
 // ---------------------- 初始化函数 ----------------------
 /// 初始化全局句柄
-fn query_needed_handle(app_handle: AppHandle) {
+fn query_needed_handle(app_handle: Option<AppHandle>) {
     *HANDLES.lock().unwrap() = Some(Handles {
-        app_handle: app_handle.clone(),
+        app_handle,
         #[cfg(target_os = "windows")]
         xinput_handle: XInputHandle::load_default().unwrap(),
     });
@@ -937,7 +1176,9 @@ fn query_needed_handle(app_handle: AppHandle) {
 /// 1. Gilrs 事件监听
 /// 2. 设备发现轮询
 /// 3. 主设备状态监听
-pub fn initialize(app_handle: AppHandle) {
+///
+/// `app_handle` 为 None 时以无界面模式运行，所有前端事件被忽略
+pub fn initialize(app_handle: Option<AppHandle>) {
     log::debug!("初始化控制器模块");
 
     let setting = get_setting();
@@ -951,45 +1192,17 @@ pub fn initialize(app_handle: AppHandle) {
 
 /// 尝试自动连接上次连接的设备
 #[tauri::command]
-pub fn try_auto_connect_last_device(app_handle: AppHandle) {
+pub fn try_auto_connect_last_device() {
     let settings = get_setting();
     if settings.remember_last_connection {
         if let Some(last_device) = settings.last_connected_device {
             log::info!("尝试连接上次连接的设备: {last_device:?}");
-            let devices = query_devices(app_handle.clone()); // query_devices 现在是同步的
-            if let Some(device_info) = devices.into_iter().find(|d| {
-                let last_vid_str = format!("{:04x}", last_device.vid);
-                let last_pid_str = format!("{:04x}", last_device.pid);
-                let last_sub_pid_str = format!("{:04x}", last_device.sub_pid);
-
-                let vid_matches = d.vendor_id == last_vid_str;
-
-                let pid_matches = if last_device.pid == 0 {
-                    true
-                } else {
-                    d.product_id.as_deref().is_some_and(|pid| pid == last_pid_str)
-                };
-
-                let sub_pid_matches = if last_device.sub_pid == 0 {
-                    true
-                } else {
-                    d.sub_product_id.as_deref().is_some_and(|sub_pid| sub_pid == last_sub_pid_str)
-                };
-
-                // log::debug!("匹配检查: DeviceInfo {:?} vs LastConnectedDevice {:?}", d, last_device);
-                // log::debug!("  VID: {} == {} -> {}", d.vendor_id, last_vid_str, vid_matches);
-                // log::debug!("  PID: {:?} == {} -> {}", d.product_id, last_pid_str, pid_matches);
-                // log::debug!("  SubPID: {:?} == {} -> {}", d.sub_product_id, last_sub_pid_str, sub_pid_matches);
-                // log::debug!("  总匹配: {}", vid_matches && (pid_matches || sub_pid_matches));
-
-                vid_matches && (pid_matches || sub_pid_matches)
-            }) {
+            let devices = query_devices(); // query_devices 现在是同步的
+            if let Some(device_info) = find_last_connected_device(devices, &last_device) {
                 log::info!("找到匹配的设备，尝试连接: {device_info:?}");
-                if use_device(device_info.name.clone()) { // use_device 现在是同步的
+                if use_device(device_info.name.clone(), None) { // use_device 现在是同步的
                     log::info!("成功自动连接上次设备");
-                    if let Err(e) = app_handle.emit("auto_connect_success", device_info) {
-                        log::error!("发送 auto_connect_success 事件失败: {e}");
-                    }
+                    emit_event("auto_connect_success", device_info);
                 } else {
                     log::error!("自动连接上次设备失败");
                 }
@@ -1001,3 +1214,100 @@ pub fn try_auto_connect_last_device(app_handle: AppHandle) {
         }
     }
 }
+
+/// 在设备列表中查找上次连接的设备
+///
+/// VID 必须一致，PID 或子 PID 任一匹配即可（为 0 表示不限制）
+pub fn find_last_connected_device(
+    devices: Vec<DeviceInfo>,
+    last_device: &LastConnectedDevice,
+) -> Option<DeviceInfo> {
+    devices.into_iter().find(|d| {
+        let last_vid_str = format!("{:04x}", last_device.vid);
+        let last_pid_str = format!("{:04x}", last_device.pid);
+        let last_sub_pid_str = format!("{:04x}", last_device.sub_pid);
+
+        let vid_matches = d.vendor_id == last_vid_str;
+
+        let pid_matches = if last_device.pid == 0 {
+            true
+        } else {
+            d.product_id.as_deref().is_some_and(|pid| pid == last_pid_str)
+        };
+
+        let sub_pid_matches = if last_device.sub_pid == 0 {
+            true
+        } else {
+            d.sub_product_id.as_deref().is_some_and(|sub_pid| sub_pid == last_sub_pid_str)
+        };
+
+        // log::debug!("匹配检查: DeviceInfo {:?} vs LastConnectedDevice {:?}", d, last_device);
+        // log::debug!("  VID: {} == {} -> {}", d.vendor_id, last_vid_str, vid_matches);
+        // log::debug!("  PID: {:?} == {} -> {}", d.product_id, last_pid_str, pid_matches);
+        // log::debug!("  SubPID: {:?} == {} -> {}", d.sub_product_id, last_sub_pid_str, sub_pid_matches);
+        // log::debug!("  总匹配: {}", vid_matches && (pid_matches || sub_pid_matches));
+
+        vid_matches && (pid_matches || sub_pid_matches)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(vendor_id: &str, product_id: &str) -> DeviceInfo {
+        let mut device = DeviceInfo::new("手柄".to_string(), vendor_id.to_string(), ControllerType::Other);
+        device.product_id = Some(product_id.to_string());
+        device
+    }
+
+    fn hid(vendor_id: &str, product_id: &str, path: &str, serial: Option<&str>) -> HidEntry {
+        HidEntry {
+            vendor_id: vendor_id.to_string(),
+            product_id: product_id.to_string(),
+            path: path.to_string(),
+            serial: serial.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn identical_pads_get_distinct_hid_paths() {
+        let mut devices = vec![device("054c", "09cc"), device("054c", "09cc")];
+        assign_hid_paths(
+            &mut devices,
+            &[
+                hid("054c", "09cc", "/dev/hidraw1", Some("aa:bb")),
+                // 同一手柄的另一个接口
+                hid("054c", "09cc", "/dev/hidraw2", Some("aa:bb")),
+                hid("054c", "09cc", "/dev/hidraw3", Some("cc:dd")),
+            ],
+        );
+        assert_eq!(devices[0].device_path.as_deref(), Some("/dev/hidraw1"));
+        assert_eq!(devices[1].device_path.as_deref(), Some("/dev/hidraw3"));
+    }
+
+    #[test]
+    fn exact_pid_match_wins_over_vendor_match() {
+        // 同厂商的两个不同手柄，HID 枚举顺序与设备顺序相反
+        let mut devices = vec![device("054c", "05c4"), device("054c", "0ce6")];
+        assign_hid_paths(
+            &mut devices,
+            &[
+                hid("054c", "0ce6", "/dev/hidraw1", None),
+                hid("054c", "05c4", "/dev/hidraw2", None),
+            ],
+        );
+        assert_eq!(devices[0].device_path.as_deref(), Some("/dev/hidraw2"));
+        assert_eq!(devices[1].device_path.as_deref(), Some("/dev/hidraw1"));
+        assert_eq!(devices[1].sub_product_id.as_deref(), Some("0ce6"));
+    }
+
+    #[test]
+    fn falls_back_to_vendor_match_for_sub_pid() {
+        let mut devices = vec![device("045e", "02ea"), device("045e", "02ea")];
+        assign_hid_paths(&mut devices, &[hid("045e", "0b12", "/dev/hidraw4", None)]);
+        assert_eq!(devices[0].device_path.as_deref(), Some("/dev/hidraw4"));
+        assert_eq!(devices[0].sub_product_id.as_deref(), Some("0b12"));
+        assert_eq!(devices[1].device_path, None);
+    }
+}
@@ -0,0 +1,207 @@
+//! 手柄输入后端
+//!
+//! 每种输入来源（XInput、hidapi 原生驱动、evdev、gilrs、脚本）实现 [`InputBackend`]，
+//! 按优先级注册在 [`BACKENDS`] 中：
+//!
+//! 1. 脚本后端：只负责 `script:` 设备，用于无手柄环境下驱动完整 listen 流程
+//! 2. XInput（Windows，Xbox 手柄）
+//! 3. PlayStation / Switch Pro hidapi 原生驱动
+//! 4. evdev（Linux）
+//! 5. gilrs：兜底，支持所有手柄
+//!
+//! 轮询时依次尝试支持该设备的后端，某个后端打不开或读取失败时回退到下一个。
+//!
+//! 按键学习（见 [`button_learning`]）通过注入 gilrs 映射生效，只有 gilrs 后端会应用学习结果；
+//! 其余后端直接解析原始报告。已注入本地映射的手柄因此跳过原生后端，只由 gilrs 驱动。
+//!
+//! 扩展按键（背键 P1-P4、分享/截图键）的支持情况：
+//!
+//! | 后端 | P1-P4 | 分享 / 截图 |
+//! |------|-------|-------------|
+//! | XInput | 不支持（XInput 不报告） | 不支持 |
+//! | PlayStation hidapi | 不适用 | 不适用（触摸板按下与静音键单独上报） |
+//! | Switch Pro hidapi | 不适用 | 支持 |
+//! | evdev | 支持（xpad TRIGGER_HAPPY5-8） | 支持（KEY_RECORD / BTN_Z） |
+//! | gilrs | 不支持（gilrs 没有背键按钮） | 仅默认映射下的 `Button::Z` |
+//!
+//! Windows 上 Xbox 手柄优先走 XInput，WGI（gilrs）只在 XInput 匹配失败时才会使用，
+//! 因此 Elite 背键与 Series 分享键在 Windows 上目前无法映射。
+//! 后端状态（已打开的设备等）按槽位保存，所有方法都作用于当前活动槽位。
+
+use crate::controller::controller::{self, DeviceInfo, GLOBAL_GILRS};
+use crate::controller::datas::ControllerDatas;
+use crate::controller::{button_learning, ps4, scripted, switch_pro};
+#[cfg(target_os = "linux")]
+use crate::controller::evdev_input;
+#[cfg(target_os = "windows")]
+use crate::controller::xbox;
+use crate::mapping;
+use once_cell::sync::Lazy;
+
+/// 手柄输入来源
+pub trait InputBackend: Send + Sync {
+    /// 后端名称（日志用）
+    fn name(&self) -> &'static str;
+
+    /// 是否尝试用该后端驱动此设备
+    fn supports(&self, device: &DeviceInfo) -> bool;
+
+    /// 枚举该后端能发现的设备，默认不参与设备发现
+    fn enumerate(&self) -> Vec<DeviceInfo> {
+        Vec::new()
+    }
+
+    /// 打开设备，返回是否可用。默认无需显式打开（`poll` 时按需打开）
+    fn open(&self, _device: &DeviceInfo) -> bool {
+        true
+    }
+
+    /// 读取一帧输入写入 `datas`，返回 false 表示该后端当前不可用，调用方应回退下一个后端
+    fn poll(&self, device: &DeviceInfo, datas: &mut ControllerDatas) -> bool;
+
+    /// 关闭当前槽位已打开的设备
+    fn close(&self) {}
+
+    /// 是否应用按键学习的结果，默认不应用
+    fn applies_learned_mapping(&self) -> bool {
+        false
+    }
+}
+
+/// gilrs 输入后端：依赖 SDL 映射，支持所有 gilrs 能识别的手柄
+pub struct GilrsBackend;
+
+impl InputBackend for GilrsBackend {
+    fn name(&self) -> &'static str {
+        "gilrs"
+    }
+
+    fn supports(&self, device: &DeviceInfo) -> bool {
+        !scripted::is_scripted(device)
+    }
+
+    fn enumerate(&self) -> Vec<DeviceInfo> {
+        controller::list_controllers_from_gilrs()
+    }
+
+    fn open(&self, device: &DeviceInfo) -> bool {
+        let gilrs_guard = GLOBAL_GILRS.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(gilrs) = gilrs_guard.as_ref() else {
+            return false;
+        };
+        gilrs.gamepads().any(|(_, gamepad)| {
+            gamepad.name() != mapping::virtual_pad::VIRTUAL_PAD_NAME
+                && gamepad.vendor_id().is_some_and(|vid| format!("{vid:04x}").eq_ignore_ascii_case(&device.vendor_id))
+                && gamepad.product_id().zip(device.product_id.as_deref())
+                    .is_some_and(|(pid, d_pid)| format!("{pid:04x}").eq_ignore_ascii_case(d_pid))
+        })
+    }
+
+    fn poll(&self, device: &DeviceInfo, datas: &mut ControllerDatas) -> bool {
+        controller::poll_other_controllers(device, datas)
+    }
+
+    fn close(&self) {
+        *controller::GILRS_GAMEPAD_ID.write().unwrap() = None;
+    }
+
+    fn applies_learned_mapping(&self) -> bool {
+        true
+    }
+}
+
+/// 已注册的输入后端，按轮询优先级排列
+static BACKENDS: Lazy<Vec<Box<dyn InputBackend>>> = Lazy::new(|| {
+    let mut backends: Vec<Box<dyn InputBackend>> = vec![Box::new(scripted::ScriptedBackend)];
+    #[cfg(target_os = "windows")]
+    backends.push(Box::new(xbox::XInputBackend));
+    backends.push(Box::new(ps4::PsHidBackend));
+    backends.push(Box::new(switch_pro::SwitchProBackend));
+    #[cfg(target_os = "linux")]
+    backends.push(Box::new(evdev_input::EvdevBackend));
+    backends.push(Box::new(GilrsBackend));
+    backends
+});
+
+/// 全部已注册的后端
+pub fn backends() -> &'static [Box<dyn InputBackend>] {
+    &BACKENDS
+}
+
+/// 按优先级列出可驱动该设备的后端；已注入本地按键映射的设备只使用会应用学习结果的后端
+fn candidates(device: &DeviceInfo) -> impl Iterator<Item = &'static dyn InputBackend> + '_ {
+    let learned = button_learning::has_learned_mapping(device);
+    backends()
+        .iter()
+        .map(|backend| backend.as_ref())
+        .filter(move |backend| backend.supports(device) && (!learned || backend.applies_learned_mapping()))
+}
+
+/// 依次尝试支持该设备的后端，返回是否有后端成功读取
+pub fn poll(device: &DeviceInfo, datas: &mut ControllerDatas) -> bool {
+    candidates(device).any(|backend| backend.poll(device, datas))
+}
+
+/// 打开设备，返回第一个可用后端的名称
+pub fn open(device: &DeviceInfo) -> Option<&'static str> {
+    candidates(device)
+        .find(|backend| backend.open(device))
+        .map(|backend| backend.name())
+}
+
+/// 关闭当前槽位在所有后端中打开的设备
+pub fn close_all() {
+    for backend in backends() {
+        backend.close();
+    }
+}
+
+/// 汇总所有后端发现的设备
+///
+/// 按优先级逆序枚举：通用后端（gilrs）先列出，其余后端只补充前面未发现的设备（按 VID/PID 去重）
+pub fn enumerate() -> Vec<DeviceInfo> {
+    let mut devices: Vec<DeviceInfo> = Vec::new();
+    for backend in backends().iter().rev() {
+        let found = backend.enumerate();
+        let new_devices: Vec<DeviceInfo> = found
+            .into_iter()
+            .filter(|d| {
+                !devices.iter().any(|known| {
+                    known.vendor_id.eq_ignore_ascii_case(&d.vendor_id) && known.product_id == d.product_id
+                })
+            })
+            .collect();
+        if !devices.is_empty() {
+            for d in &new_devices {
+                log::info!("{} 补充了前面后端未识别的手柄: {}", backend.name(), d.name);
+            }
+        }
+        devices.extend(new_devices);
+    }
+    devices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::controller::ControllerType;
+
+    #[test]
+    fn learned_devices_are_routed_to_gilrs() {
+        let mut device = DeviceInfo::new("学习测试手柄".to_string(), "2dc8".to_string(), ControllerType::PlayStation);
+        device.product_id = Some("6101".to_string());
+        let names = |device: &DeviceInfo| candidates(device).map(|backend| backend.name()).collect::<Vec<_>>();
+
+        let native = names(&device);
+        assert!(native.contains(&"PlayStation HID"));
+        #[cfg(target_os = "linux")]
+        assert!(native.contains(&"evdev"));
+        assert_eq!(native.last(), Some(&"gilrs"));
+
+        button_learning::set_learned("2dc8:6101", true);
+        assert_eq!(names(&device), vec!["gilrs"]);
+
+        button_learning::set_learned("2dc8:6101", false);
+        assert_eq!(names(&device), native);
+    }
+}
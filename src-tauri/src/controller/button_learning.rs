@@ -0,0 +1,739 @@
+//! 按键学习 + TOML 本地映射持久化（见 docs/adr/ADR-002）
+//!
+//! SDL DB 未收录的手柄在 gilrs 中退回默认映射，按键容易错位。学习流程：
+//! 1. 前端为某个设备开始学习，逐个指定要学习的逻辑按键
+//! 2. 用户按下对应的物理键，记录 gilrs 上报的原始 EvCode
+//! 3. 结束学习时写入 `button_mappings.toml`，叠加到设备原有映射上并通过 `Gilrs::set_mapping` 注入
+//!
+//! 摇杆/扳机学习：用户推动摇杆或按下扳机约 2 秒，期间采样所有原始轴，
+//! 取偏移最大的轴作为目标轴，偏移方向决定是否反向（gilrs 映射不支持反向，
+//! 由 [`axis_value`] 在读取时处理）。数字扳机会被学习为按键。
+//!
+//! 手柄连接时（包括程序启动时已连接的手柄）会自动应用已保存的映射。
+//!
+//! 学习结果只作用于 gilrs。evdev、PlayStation / Switch Pro hidapi、XInput 等原生后端直接解析
+//! 原始报告，不经过 gilrs 映射，因此已注入本地映射的手柄只交给 gilrs 后端驱动
+//! （见 [`has_learned_mapping`] 与 `backend` 模块）。
+
+use crate::controller::controller::{emit_event, DeviceInfo, GLOBAL_GILRS};
+use crate::mapping;
+use crate::xeno_utils;
+use gilrs::ev::Code;
+use gilrs::{Axis, Button, EventType, GamepadId, Gilrs};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::thread;
+use std::time::Duration;
+
+const BUTTON_MAPPINGS_FILE: &str = "button_mappings.toml";
+
+/// D-Pad 以 hat 轴上报时，超过该值视为按下
+const DPAD_AXIS_THRESHOLD: f32 = 0.5;
+
+/// 摇杆/扳机学习的采样时长
+const AXIS_SAMPLE_DURATION: Duration = Duration::from_secs(2);
+
+/// 采样期间轴的最大偏移低于该值视为静止漂移，学习失败
+const AXIS_LEARN_THRESHOLD: f32 = 0.5;
+
+/// 可学习的逻辑按键（TOML 键名 → gilrs 按键）
+pub const LEARNABLE_BUTTONS: [(&str, Button); 17] = [
+    ("south", Button::South),
+    ("east", Button::East),
+    ("west", Button::West),
+    ("north", Button::North),
+    ("left_shoulder", Button::LeftTrigger),
+    ("right_shoulder", Button::RightTrigger),
+    ("left_trigger", Button::LeftTrigger2),
+    ("right_trigger", Button::RightTrigger2),
+    ("back", Button::Select),
+    ("start", Button::Start),
+    ("guide", Button::Mode),
+    ("left_stick", Button::LeftThumb),
+    ("right_stick", Button::RightThumb),
+    ("dpad_up", Button::DPadUp),
+    ("dpad_down", Button::DPadDown),
+    ("dpad_left", Button::DPadLeft),
+    ("dpad_right", Button::DPadRight),
+];
+
+/// 可映射的逻辑轴（TOML 键名 → gilrs 轴）
+///
+/// `dpad_x` / `dpad_y` 由按键学习写入（hat 形式的 D-Pad），其余由摇杆/扳机学习写入
+pub const MAPPABLE_AXES: [(&str, Axis); 8] = [
+    ("left_x", Axis::LeftStickX),
+    ("left_y", Axis::LeftStickY),
+    ("right_x", Axis::RightStickX),
+    ("right_y", Axis::RightStickY),
+    ("left_z", Axis::LeftZ),
+    ("right_z", Axis::RightZ),
+    ("dpad_x", Axis::DPadX),
+    ("dpad_y", Axis::DPadY),
+];
+
+/// 可学习的摇杆/扳机轴：摇杆向右/向上、扳机按下为正方向
+pub const LEARNABLE_AXES: [&str; 6] = ["left_x", "left_y", "right_x", "right_y", "left_z", "right_z"];
+
+/// 扳机轴对应的按键名（扳机以数字按键上报时使用）
+fn trigger_button_name(axis: &str) -> Option<&'static str> {
+    match axis {
+        "left_z" => Some("left_trigger"),
+        "right_z" => Some("right_trigger"),
+        _ => None,
+    }
+}
+
+/// 单个手柄的映射配置，对应 `[controllers."vid:pid"]`
+///
+/// EvCode 按 gilrs 的 serde 格式保存，其结构随平台后端不同（见 ADR-002），
+/// 因此每个条目记录学习时的平台，其他平台上不会应用
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ControllerButtonMapping {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// 学习时的平台（`std::env::consts::OS`），为空视为当前平台
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    /// 逻辑按键 → 原始 EvCode
+    #[serde(default)]
+    pub buttons: BTreeMap<String, Code>,
+    /// 逻辑轴 → 原始 EvCode
+    #[serde(default)]
+    pub axes: BTreeMap<String, Code>,
+    /// 需要反向的逻辑轴
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub inverted_axes: BTreeSet<String>,
+}
+
+impl ControllerButtonMapping {
+    fn is_empty(&self) -> bool {
+        self.buttons.is_empty() && self.axes.is_empty()
+    }
+
+    /// 条目是否在当前平台上学习，其他平台的 EvCode 无法直接使用
+    fn is_current_platform(&self) -> bool {
+        self.platform.as_deref().is_none_or(|platform| platform == std::env::consts::OS)
+    }
+
+    /// 记录一个逻辑按键/轴对应的 EvCode，同一 EvCode 只能对应一个条目，返回被顶替的条目名
+    fn assign(&mut self, target: &str, code: Code, is_axis: bool) -> Option<String> {
+        let replaced = self
+            .buttons
+            .iter()
+            .chain(self.axes.iter())
+            .find(|(name, c)| **c == code && name.as_str() != target)
+            .map(|(name, _)| name.clone());
+        if let Some(name) = &replaced {
+            self.buttons.remove(name);
+            self.axes.remove(name);
+            self.inverted_axes.remove(name);
+        }
+
+        if is_axis {
+            self.axes.insert(target.to_string(), code);
+        } else {
+            self.buttons.insert(target.to_string(), code);
+        }
+        replaced
+    }
+
+    /// 在设备原有映射上叠加已学习的条目，转换为 gilrs 映射数据，未知的条目名打 warning 跳过
+    ///
+    /// 只学习了部分按键时，未学习的按键/轴沿用原有映射；原有映射中被学习条目占用的
+    /// EvCode 或已被重新学习的逻辑按键/轴会被丢弃，避免重复映射
+    fn to_gilrs_mapping(&self, base: &BaseMapping) -> gilrs::Mapping {
+        let mut learned_buttons = Vec::new();
+        for (name, code) in &self.buttons {
+            match LEARNABLE_BUTTONS.iter().find(|(n, _)| n == name) {
+                Some((_, button)) => learned_buttons.push((*button, *code)),
+                None => log::warn!("未知的按键名 '{name}'，已跳过"),
+            }
+        }
+        let mut learned_axes = Vec::new();
+        for (name, code) in &self.axes {
+            match MAPPABLE_AXES.iter().find(|(n, _)| n == name) {
+                Some((_, axis)) => learned_axes.push((*axis, *code)),
+                None => log::warn!("未知的轴名 '{name}'，已跳过"),
+            }
+        }
+
+        let code_learned =
+            |code: &Code| learned_buttons.iter().any(|(_, c)| c == code) || learned_axes.iter().any(|(_, c)| c == code);
+
+        let mut data = gilrs::Mapping::new();
+        for (button, code) in &base.buttons {
+            if !code_learned(code) && !learned_buttons.iter().any(|(b, _)| b == button) {
+                data.insert_btn(*code, *button);
+            }
+        }
+        for (axis, code) in &base.axes {
+            if !code_learned(code) && !learned_axes.iter().any(|(a, _)| a == axis) {
+                data.insert_axis(*code, *axis);
+            }
+        }
+        for (button, code) in learned_buttons {
+            data.insert_btn(code, button);
+        }
+        for (axis, code) in learned_axes {
+            data.insert_axis(code, axis);
+        }
+        data
+    }
+
+    /// 需要反向的 gilrs 轴
+    fn inverted_gilrs_axes(&self) -> Vec<Axis> {
+        self.inverted_axes
+            .iter()
+            .filter_map(|name| MAPPABLE_AXES.iter().find(|(n, _)| n == name).map(|(_, axis)| *axis))
+            .collect()
+    }
+}
+
+/// `button_mappings.toml` 文件结构
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ButtonMappingsFile {
+    #[serde(default)]
+    pub controllers: BTreeMap<String, ControllerButtonMapping>,
+}
+
+/// 单个原始轴的采样结果
+#[derive(Debug, Clone, Copy)]
+struct AxisSample {
+    /// 采样期间第一次上报的值，视为静止位置
+    first: f32,
+    /// 相对静止位置的最大偏移（带符号）
+    peak: f32,
+}
+
+/// 摇杆/扳机学习的采样状态
+struct AxisSampling {
+    /// 采样编号，计时线程结束时据此确认仍是自己启动的那次采样
+    generation: u64,
+    target: String,
+    samples: HashMap<Code, AxisSample>,
+    /// 扳机以数字按键上报时记录的按键 EvCode
+    button: Option<Code>,
+}
+
+/// 正在进行的学习会话
+struct LearningSession {
+    key: String,
+    gamepad_id: GamepadId,
+    /// 正在等待按下的逻辑按键
+    target: Option<String>,
+    /// 正在采样的逻辑轴
+    axis_sampling: Option<AxisSampling>,
+    mapping: ControllerButtonMapping,
+}
+
+static LEARNING_SESSION: Lazy<Mutex<Option<LearningSession>>> = Lazy::new(|| Mutex::new(None));
+/// 快速判断是否处于学习状态，避免每个 gilrs 事件都去抢锁
+static IS_LEARNING: AtomicBool = AtomicBool::new(false);
+/// 轴采样编号，每次开始采样时递增
+static AXIS_SAMPLING_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// 设备在注入本地映射之前的原有映射（SDL DB 或驱动默认），学习结果叠加在其上
+#[derive(Debug, Clone, Default)]
+struct BaseMapping {
+    buttons: Vec<(Button, Code)>,
+    axes: Vec<(Axis, Code)>,
+}
+
+/// 各 gilrs 手柄首次注入本地映射前记录的原有映射，重复注入时始终以此为基础
+static BASE_MAPPINGS: Lazy<RwLock<HashMap<usize, BaseMapping>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// 已注入本地映射的手柄（`vid:pid`）
+static LEARNED_DEVICES: Lazy<RwLock<HashSet<String>>> = Lazy::new(|| RwLock::new(HashSet::new()));
+
+/// 各 gilrs 手柄需要反向的轴
+static INVERTED_AXES: Lazy<RwLock<HashMap<usize, Vec<Axis>>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// 学习到一个按键时发送给前端的数据
+#[derive(Debug, Clone, Serialize)]
+pub struct ButtonLearnedPayload {
+    pub button: String,
+    pub code: u32,
+    /// 同一物理键之前被分配给的条目（已被移除）
+    pub replaced: Option<String>,
+}
+
+/// 映射文件损坏时发送给前端的数据
+#[derive(Debug, Clone, Serialize)]
+pub struct ButtonMappingsInvalidPayload {
+    /// 损坏的手柄条目，为空表示整个文件无法解析
+    pub entry: Option<String>,
+    pub error: String,
+}
+
+/// 摇杆/扳机学习结束时发送给前端的数据
+#[derive(Debug, Clone, Serialize)]
+pub struct AxisLearnedPayload {
+    pub axis: String,
+    /// 学习到的 EvCode，为空表示未检测到明显的轴移动
+    pub code: Option<u32>,
+    pub inverted: bool,
+    /// 扳机以数字按键上报，已学习为按键
+    pub as_button: bool,
+    pub replaced: Option<String>,
+}
+
+// ---------------------- 文件持久化 ----------------------
+
+fn mapping_key(vid: u16, pid: u16) -> String {
+    format!("{vid:04x}:{pid:04x}")
+}
+
+fn device_mapping_key(device: &DeviceInfo) -> Option<String> {
+    let vid = u16::from_str_radix(&device.vendor_id, 16).ok()?;
+    let pid = u16::from_str_radix(device.product_id.as_deref()?, 16).ok()?;
+    Some(mapping_key(vid, pid))
+}
+
+/// 读取映射文件，单个手柄的条目损坏时打 warning 跳过，不影响其他条目。
+/// 文件或条目损坏时发送 `button_mappings_invalid` 事件提示用户
+pub fn load_button_mappings() -> ButtonMappingsFile {
+    let path = xeno_utils::get_config_path(BUTTON_MAPPINGS_FILE);
+    let mut file = ButtonMappingsFile::default();
+    if !path.exists() {
+        return file;
+    }
+
+    let table = match std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|s| toml::from_str::<toml::Table>(&s).map_err(|e| e.to_string()))
+    {
+        Ok(table) => table,
+        Err(e) => {
+            log::warn!("读取按键映射文件失败: {e}，将忽略本地按键映射");
+            emit_event("button_mappings_invalid", ButtonMappingsInvalidPayload { entry: None, error: e });
+            return file;
+        }
+    };
+
+    let Some(controllers) = table.get("controllers").and_then(|v| v.as_table()) else {
+        return file;
+    };
+    for (key, value) in controllers {
+        match value.clone().try_into::<ControllerButtonMapping>() {
+            Ok(mapping) => {
+                file.controllers.insert(key.to_lowercase(), mapping);
+            }
+            Err(e) => {
+                log::warn!("按键映射条目 [controllers.\"{key}\"] 格式错误，已跳过: {e}");
+                emit_event(
+                    "button_mappings_invalid",
+                    ButtonMappingsInvalidPayload {
+                        entry: Some(key.clone()),
+                        error: e.to_string(),
+                    },
+                );
+            }
+        }
+    }
+    file
+}
+
+fn save_controller_mapping(key: &str, mapping: &ControllerButtonMapping) -> Result<(), String> {
+    xeno_utils::ensure_config_dir();
+    let mut file = load_button_mappings();
+    if mapping.is_empty() {
+        file.controllers.remove(key);
+    } else {
+        file.controllers.insert(key.to_string(), mapping.clone());
+    }
+    let path = xeno_utils::get_config_path(BUTTON_MAPPINGS_FILE);
+    xeno_utils::write_toml_file(&path, &file).map_err(|e| format!("写入按键映射文件失败: {e}"))
+}
+
+// ---------------------- gilrs 映射注入 ----------------------
+
+/// gilrs 支持映射的全部按键与轴，用于读取设备的原有映射
+const ALL_BUTTONS: [Button; 19] = [
+    Button::South,
+    Button::East,
+    Button::North,
+    Button::West,
+    Button::C,
+    Button::Z,
+    Button::LeftTrigger,
+    Button::LeftTrigger2,
+    Button::RightTrigger,
+    Button::RightTrigger2,
+    Button::Select,
+    Button::Start,
+    Button::Mode,
+    Button::LeftThumb,
+    Button::RightThumb,
+    Button::DPadUp,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::DPadRight,
+];
+const ALL_AXES: [Axis; 8] = [
+    Axis::LeftStickX,
+    Axis::LeftStickY,
+    Axis::LeftZ,
+    Axis::RightStickX,
+    Axis::RightStickY,
+    Axis::RightZ,
+    Axis::DPadX,
+    Axis::DPadY,
+];
+
+/// 读取设备当前的映射；同一手柄只在首次注入前读取一次，之后复用缓存，
+/// 避免把上一次注入的学习结果当作原有映射
+fn base_mapping(gilrs: &Gilrs, id: GamepadId) -> BaseMapping {
+    let mut cache = BASE_MAPPINGS.write().unwrap();
+    cache
+        .entry(id.into())
+        .or_insert_with(|| {
+            let gamepad = gilrs.gamepad(id);
+            BaseMapping {
+                buttons: ALL_BUTTONS
+                    .iter()
+                    .filter_map(|button| gamepad.button_code(*button).map(|code| (*button, code)))
+                    .collect(),
+                axes: ALL_AXES
+                    .iter()
+                    .filter_map(|axis| gamepad.axis_code(*axis).map(|code| (*axis, code)))
+                    .collect(),
+            }
+        })
+        .clone()
+}
+
+fn set_gilrs_mapping(gilrs: &mut Gilrs, id: GamepadId, mapping: &ControllerButtonMapping) -> Result<(), String> {
+    let data = mapping.to_gilrs_mapping(&base_mapping(gilrs, id));
+    INVERTED_AXES.write().unwrap().insert(id.into(), mapping.inverted_gilrs_axes());
+    gilrs
+        .set_mapping(id.into(), &data, mapping.name.as_deref())
+        .map(|_| ())
+        .map_err(|e| format!("注入按键映射失败: {e}"))
+}
+
+/// 记录手柄是否已注入本地映射
+pub(crate) fn set_learned(key: &str, learned: bool) {
+    let mut devices = LEARNED_DEVICES.write().unwrap();
+    if learned {
+        devices.insert(key.to_string());
+    } else {
+        devices.remove(key);
+    }
+}
+
+/// 设备是否已注入本地按键映射，此类设备只能由 gilrs 后端驱动才能用上学习结果
+pub fn has_learned_mapping(device: &DeviceInfo) -> bool {
+    device_mapping_key(device).is_some_and(|key| LEARNED_DEVICES.read().unwrap().contains(&key))
+}
+
+/// 读取 gilrs 轴的值，并按已学习的极性反向
+pub fn axis_value(gamepad: &gilrs::Gamepad, axis: Axis) -> Option<f32> {
+    let value = gamepad.axis_data(axis)?.value();
+    let inverted = INVERTED_AXES
+        .read()
+        .unwrap()
+        .get(&gamepad.id().into())
+        .is_some_and(|axes| axes.contains(&axis));
+    Some(if inverted { -value } else { value })
+}
+
+/// 为指定手柄应用已保存的映射（若有）
+pub fn apply_saved_mapping(gilrs: &mut Gilrs, id: GamepadId) {
+    let gamepad = gilrs.gamepad(id);
+    if gamepad.name() == mapping::virtual_pad::VIRTUAL_PAD_NAME {
+        return;
+    }
+    let (Some(vid), Some(pid)) = (gamepad.vendor_id(), gamepad.product_id()) else {
+        return;
+    };
+    let key = mapping_key(vid, pid);
+
+    let Some(mapping) = load_button_mappings().controllers.remove(&key) else {
+        return;
+    };
+    if !mapping.is_current_platform() {
+        log::info!(
+            "手柄 {key} 的本地按键映射在 {} 平台上学习，当前平台不适用，已跳过",
+            mapping.platform.as_deref().unwrap_or_default()
+        );
+        return;
+    }
+    match set_gilrs_mapping(gilrs, id, &mapping) {
+        Ok(()) => {
+            set_learned(&key, !mapping.is_empty());
+            log::info!(
+                "🎯 已为手柄 {key} 加载本地按键映射 ({} 个按键, {} 个轴)",
+                mapping.buttons.len(),
+                mapping.axes.len()
+            )
+        }
+        Err(e) => log::warn!("手柄 {key}: {e}"),
+    }
+}
+
+/// 为所有已连接的手柄应用已保存的映射
+pub fn apply_saved_mappings(gilrs: &mut Gilrs) {
+    let ids: Vec<GamepadId> = gilrs.gamepads().map(|(id, _)| id).collect();
+    for id in ids {
+        apply_saved_mapping(gilrs, id);
+    }
+}
+
+// ---------------------- 事件捕获 ----------------------
+
+/// 由 gilrs 事件线程调用：学习中时记录目标手柄按下的原始 EvCode
+pub fn capture_event(id: GamepadId, event: &EventType) {
+    if !IS_LEARNING.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let mut session_guard = LEARNING_SESSION.lock().unwrap();
+    let Some(session) = session_guard.as_mut() else {
+        return;
+    };
+    if session.gamepad_id != id {
+        return;
+    }
+
+    if let Some(sampling) = session.axis_sampling.as_mut() {
+        match *event {
+            EventType::AxisChanged(_, value, code) => {
+                let sample = sampling.samples.entry(code).or_insert(AxisSample { first: value, peak: 0.0 });
+                let offset = value - sample.first;
+                if offset.abs() > sample.peak.abs() {
+                    sample.peak = offset;
+                }
+            }
+            EventType::ButtonPressed(_, code) if trigger_button_name(&sampling.target).is_some() => {
+                sampling.button.get_or_insert(code);
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    let Some(target) = session.target.clone() else {
+        return;
+    };
+
+    let (code, slot, is_axis) = match *event {
+        EventType::ButtonPressed(_, code) => (code, target.clone(), false),
+        // 部分手柄的 D-Pad 以 hat 轴上报，学习为 dpad_x / dpad_y 轴
+        EventType::AxisChanged(_, value, code)
+            if target.starts_with("dpad_") && value.abs() >= DPAD_AXIS_THRESHOLD =>
+        {
+            let axis = if matches!(target.as_str(), "dpad_left" | "dpad_right") {
+                "dpad_x"
+            } else {
+                "dpad_y"
+            };
+            (code, axis.to_string(), true)
+        }
+        _ => return,
+    };
+
+    let replaced = session.mapping.assign(&slot, code, is_axis);
+    session.target = None;
+    log::info!("学习到按键 {target} -> {slot} (EvCode {})", code.into_u32());
+
+    let payload = ButtonLearnedPayload {
+        button: target,
+        code: code.into_u32(),
+        replaced,
+    };
+    emit_event("button_learned", payload);
+}
+
+// ---------------------- Tauri 命令接口 ----------------------
+
+/// 开始为指定设备学习按键，返回已保存映射中学习过的按键名
+#[tauri::command]
+pub fn start_button_learning(device: DeviceInfo) -> Result<Vec<String>, String> {
+    let key = device_mapping_key(&device).ok_or("设备缺少 VID/PID，无法学习按键")?;
+
+    let gilrs_guard = GLOBAL_GILRS.lock().unwrap_or_else(|e| e.into_inner());
+    let gilrs = gilrs_guard.as_ref().ok_or("gilrs 尚未初始化")?;
+    let gamepad_id = gilrs
+        .gamepads()
+        .find(|(_, gamepad)| {
+            gamepad.name() != mapping::virtual_pad::VIRTUAL_PAD_NAME
+                && gamepad.vendor_id().zip(gamepad.product_id()).map(|(v, p)| mapping_key(v, p)).as_deref()
+                    == Some(key.as_str())
+        })
+        .map(|(id, _)| id)
+        .ok_or_else(|| format!("gilrs 中未找到设备 {key}，无法学习按键"))?;
+    drop(gilrs_guard);
+
+    // 其他平台学习的条目无法复用，重新学习后会被覆盖
+    let mapping = load_button_mappings()
+        .controllers
+        .remove(&key)
+        .filter(ControllerButtonMapping::is_current_platform)
+        .unwrap_or_else(|| ControllerButtonMapping {
+            name: Some(device.name.clone()),
+            ..Default::default()
+        });
+    let learned = mapping.buttons.keys().chain(mapping.axes.keys()).cloned().collect();
+
+    *LEARNING_SESSION.lock().unwrap() = Some(LearningSession {
+        key: key.clone(),
+        gamepad_id,
+        target: None,
+        axis_sampling: None,
+        mapping,
+    });
+    IS_LEARNING.store(true, Ordering::SeqCst);
+    log::info!("开始按键学习: {} ({key})", device.name);
+    Ok(learned)
+}
+
+/// 指定下一个要学习的逻辑按键，等待用户按下物理键
+#[tauri::command]
+pub fn learn_button(button: String) -> Result<(), String> {
+    if !LEARNABLE_BUTTONS.iter().any(|(name, _)| *name == button) {
+        return Err(format!("不支持学习的按键: {button}"));
+    }
+    let mut session_guard = LEARNING_SESSION.lock().unwrap();
+    let session = session_guard.as_mut().ok_or("尚未开始按键学习")?;
+    log::debug!("等待按下物理键: {button}");
+    session.axis_sampling = None;
+    session.target = Some(button);
+    Ok(())
+}
+
+/// 开始学习一个摇杆轴或扳机：采样结束后发送 `axis_learned` 事件
+#[tauri::command]
+pub fn learn_axis(axis: String) -> Result<(), String> {
+    if !LEARNABLE_AXES.contains(&axis.as_str()) {
+        return Err(format!("不支持学习的轴: {axis}"));
+    }
+    let generation = {
+        let mut session_guard = LEARNING_SESSION.lock().unwrap();
+        let session = session_guard.as_mut().ok_or("尚未开始按键学习")?;
+        if session.axis_sampling.is_some() {
+            return Err("正在学习其他轴，请稍候".to_string());
+        }
+        let generation = AXIS_SAMPLING_GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+        session.target = None;
+        session.axis_sampling = Some(AxisSampling {
+            generation,
+            target: axis.clone(),
+            samples: HashMap::new(),
+            button: None,
+        });
+        generation
+    };
+    log::debug!("开始采样轴: {axis}");
+
+    thread::spawn(move || {
+        thread::sleep(AXIS_SAMPLE_DURATION);
+        finish_axis_sampling(generation);
+    });
+    Ok(())
+}
+
+/// 采样结束：取偏移最大的原始轴作为结果
+fn finish_axis_sampling(generation: u64) {
+    let mut session_guard = LEARNING_SESSION.lock().unwrap();
+    // 采样期间学习可能已被取消、切换到按键学习，或已重新开始一次新的采样
+    let Some(session) = session_guard.as_mut() else {
+        return;
+    };
+    if session.axis_sampling.as_ref().is_none_or(|sampling| sampling.generation != generation) {
+        return;
+    }
+    let Some(sampling) = session.axis_sampling.take() else {
+        return;
+    };
+    let target = sampling.target;
+    let trigger_button = trigger_button_name(&target);
+
+    let dominant = sampling
+        .samples
+        .iter()
+        .max_by(|(_, a), (_, b)| a.peak.abs().total_cmp(&b.peak.abs()))
+        .filter(|(_, sample)| sample.peak.abs() >= AXIS_LEARN_THRESHOLD)
+        .map(|(code, sample)| (*code, sample.peak < 0.0));
+
+    let mapping = &mut session.mapping;
+    let payload = match (dominant, trigger_button, sampling.button) {
+        (Some((code, inverted)), _, _) => {
+            let replaced = mapping.assign(&target, code, true);
+            if inverted {
+                mapping.inverted_axes.insert(target.clone());
+            } else {
+                mapping.inverted_axes.remove(&target);
+            }
+            // 模拟扳机以轴为准，移除之前学习的数字扳机
+            if let Some(button) = trigger_button {
+                mapping.buttons.remove(button);
+            }
+            log::info!("学习到轴 {target} (EvCode {}, 反向: {inverted})", code.into_u32());
+            AxisLearnedPayload {
+                axis: target,
+                code: Some(code.into_u32()),
+                inverted,
+                as_button: false,
+                replaced,
+            }
+        }
+        (None, Some(button), Some(code)) => {
+            let replaced = mapping.assign(button, code, false);
+            mapping.axes.remove(&target);
+            mapping.inverted_axes.remove(&target);
+            log::info!("扳机 {target} 以数字按键上报，学习为按键 {button} (EvCode {})", code.into_u32());
+            AxisLearnedPayload {
+                axis: target,
+                code: Some(code.into_u32()),
+                inverted: false,
+                as_button: true,
+                replaced,
+            }
+        }
+        _ => {
+            log::warn!("轴 {target} 学习失败：未检测到明显的轴移动");
+            AxisLearnedPayload {
+                axis: target,
+                code: None,
+                inverted: false,
+                as_button: false,
+                replaced: None,
+            }
+        }
+    };
+    drop(session_guard);
+
+    emit_event("axis_learned", payload);
+}
+
+/// 结束按键学习，`save` 为 true 时保存并立即注入映射
+#[tauri::command]
+pub fn stop_button_learning(save: bool) -> Result<(), String> {
+    IS_LEARNING.store(false, Ordering::SeqCst);
+    let Some(mut session) = LEARNING_SESSION.lock().unwrap().take() else {
+        return Ok(());
+    };
+
+    if !save {
+        log::info!("已取消按键学习: {}", session.key);
+        return Ok(());
+    }
+
+    session.mapping.platform = Some(std::env::consts::OS.to_string());
+
+    save_controller_mapping(&session.key, &session.mapping)?;
+    log::info!("✅ 已保存按键映射: {}", session.key);
+
+    if session.mapping.is_empty() {
+        set_learned(&session.key, false);
+        return Ok(());
+    }
+    let mut gilrs_guard = GLOBAL_GILRS.lock().unwrap_or_else(|e| e.into_inner());
+    let gilrs = gilrs_guard.as_mut().ok_or("gilrs 尚未初始化")?;
+    set_gilrs_mapping(gilrs, session.gamepad_id, &session.mapping)?;
+    set_learned(&session.key, true);
+    Ok(())
+}
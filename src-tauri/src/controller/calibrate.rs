@@ -1,4 +1,7 @@
 use crate::controller::controller::{RAW_CONTROLLER_DATA, CURRENT_DEVICE, DeviceInfo};
+use crate::controller::curve::ResponseCurve;
+use crate::controller::logic::DeadzoneShape;
+use crate::controller::slots::PerSlot;
 use crate::xeno_utils;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
@@ -99,6 +102,11 @@ impl StickCalibration {
     pub fn update_step_to(&mut self, step: StickTestSteps) {
         self.step = step;
     }
+
+    /// 是否已有有效的校准范围
+    pub fn is_calibrated(&self) -> bool {
+        self.stick_range.x_min != f32::MAX && self.stick_range.x_max != f32::MIN
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -134,9 +142,13 @@ pub struct CalibrationFile {
     pub right_stick_calibration: StickCalibration,
 }
 
-/// 全局控制器校准数据
-pub static CONTROLLER_CALIBRATION: Lazy<RwLock<ControllerCalibration>> =
-    Lazy::new(|| RwLock::new(ControllerCalibration::new()));
+/// 各槽位的控制器校准数据
+pub static CONTROLLER_CALIBRATION: Lazy<PerSlot<RwLock<ControllerCalibration>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(ControllerCalibration::new())));
+
+/// 手柄自带的出厂校准（如 Switch Pro 的 SPI 校准），没有用户校准时作为默认值
+static DEFAULT_CALIBRATION: Lazy<PerSlot<RwLock<Option<ControllerCalibration>>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(None)));
 
 /// 校准监听线程的句柄
 static CALIBRATION_THREAD: Lazy<Mutex<Option<JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
@@ -198,7 +210,38 @@ pub fn load_calibration(device: &DeviceInfo) {
 }
 
 pub fn reset_calibration() {
-    CONTROLLER_CALIBRATION.write().unwrap().reset();
+    let mut cali_data = CONTROLLER_CALIBRATION.write().unwrap();
+    cali_data.reset();
+    if let Some(default) = *DEFAULT_CALIBRATION.read().unwrap() {
+        *cali_data = default;
+    }
+}
+
+/// 设置（或清除）当前设备的出厂校准默认值
+///
+/// 只会填充尚未校准的摇杆，用户保存的校准文件始终优先。
+pub fn set_default_calibration(default: Option<ControllerCalibration>) {
+    let mode = match crate::setting::get_setting().calibration_mode.as_str() {
+        "square" => StickCaliMode::Square,
+        _ => StickCaliMode::Circle,
+    };
+    let default = default.map(|mut d| {
+        d.left_stick.mode = mode;
+        d.right_stick.mode = mode;
+        d
+    });
+    *DEFAULT_CALIBRATION.write().unwrap() = default;
+
+    if let Some(default) = default {
+        let mut cali_data = CONTROLLER_CALIBRATION.write().unwrap();
+        if !cali_data.left_stick.is_calibrated() {
+            cali_data.left_stick = default.left_stick;
+        }
+        if !cali_data.right_stick.is_calibrated() {
+            cali_data.right_stick = default.right_stick;
+        }
+        log::info!("已应用手柄出厂摇杆校准作为默认值");
+    }
 }
 
 
@@ -379,7 +422,8 @@ fn calibration_listener() {
 /// # Arguments
 /// * `normalized_x` - 由 poll 函数提供的、在 [-1, 1] 区间内的 X 轴值
 /// * `normalized_y` - 由 poll 函数提供的、在 [-1, 1] 区间内的 Y 轴值
-/// * `deadzone_percent` - 死区百分比 (0-100)
+/// * `shape` - 内死区、外死区与反死区
+/// * `curve` - 死区外的响应曲线
 /// * `calibration` - `StickCalibration` 结构体引用，包含在归一化空间内记录的校准数据
 ///
 /// # Returns
@@ -387,7 +431,8 @@ fn calibration_listener() {
 pub fn apply_calibration(
     normalized_x: f32,
     normalized_y: f32,
-    deadzone_percent: u8,
+    shape: &DeadzoneShape,
+    curve: &ResponseCurve,
     calibration: &StickCalibration,
 ) -> (f32, f32) {
     // 如果校准数据是初始状态（即从未校准过），则直接返回原始归一化值
@@ -422,24 +467,19 @@ pub fn apply_calibration(
         0.0
     };
 
-    // 3. 根据校准模式应用不同的死区和塑形
-    let deadzone = deadzone_percent as f32 / 100.0;
-
+    // 3. 根据校准模式应用不同的死区和塑形（两种模式共用 DeadzoneShape 的处理流程）
     let (final_x, final_y) = match calibration.mode {
         StickCaliMode::Circle => {
             // --- 圆形模式 ---
             // 1. 计算到中心的距离
             let distance = (scaled_x.powi(2) + scaled_y.powi(2)).sqrt();
             
-            // 2. 应用圆形死区
-            if distance < deadzone {
+            // 2. 对长度应用内外死区、响应曲线与反死区
+            let rescale_factor = shape.apply(distance, curve);
+            if rescale_factor <= 0.0 {
                 return (0.0, 0.0);
             }
             
-            // 3. 死区补偿与向量重缩放
-            // 将向量长度从 [deadzone, 1.0] 重新映射到 [0, 1.0]
-            let rescale_factor = (distance - deadzone) / (1.0 - deadzone);
-            
             // 4. 保持方向，应用新的长度
             let x = if distance > 0.0 { (scaled_x / distance) * rescale_factor } else { 0.0 };
             let y = if distance > 0.0 { (scaled_y / distance) * rescale_factor } else { 0.0 };
@@ -448,14 +488,7 @@ pub fn apply_calibration(
         StickCaliMode::Square => {
             // --- 方形模式 (最纯粹的轴向处理) ---
             // 1. 定义轴向死区函数
-            let apply_axial_deadzone = |val: f32| {
-                if val.abs() < deadzone {
-                    0.0
-                } else {
-                    // 死区补偿：将 [deadzone, 1.0] 映射到 [0, 1.0]
-                    (val.abs() - deadzone) / (1.0 - deadzone) * val.signum()
-                }
-            };
+            let apply_axial_deadzone = |val: f32| shape.apply(val.abs(), curve) * val.signum();
             
             // 2. 分别对X轴和Y轴应用死区
             let x = apply_axial_deadzone(scaled_x);
@@ -0,0 +1,175 @@
+//! 响应曲线
+//!
+//! 把 0..1 的输入量（扳机深度、摇杆幅度等）映射为 0..1 的输出量，
+//! 端点固定：0 → 0，1 → 1。
+
+use serde::{Deserialize, Serialize};
+
+/// 分段曲线最多允许的控制点数
+pub const MAX_PIECEWISE_POINTS: usize = 16;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseCurve {
+    /// 线性，输出等于输入
+    #[default]
+    Linear,
+    /// 幂函数 y = x^exponent：大于 1 时小幅度更细腻，小于 1 时起步更灵敏
+    Power { exponent: f32 },
+    /// S 形曲线：中心附近平缓便于精确瞄准，中段加速便于快速转身，推到底时再次平缓。
+    /// `strength` 为 0 时即线性，为 1 时为完整的 smoothstep
+    SCurve { strength: f32 },
+    /// 自定义分段线性曲线，`points` 为 (输入, 输出) 控制点，
+    /// 两端自动补上 (0, 0) 与 (1, 1)
+    Piecewise { points: Vec<[f32; 2]> },
+}
+
+impl ResponseCurve {
+    /// 计算曲线输出，输入会先限制到 0..1
+    pub fn apply(&self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        match self {
+            ResponseCurve::Linear => x,
+            ResponseCurve::Power { exponent } => x.powf(exponent.max(0.01)),
+            ResponseCurve::SCurve { strength } => {
+                let smooth = x * x * (3.0 - 2.0 * x);
+                x + (smooth - x) * strength.clamp(0.0, 1.0)
+            }
+            ResponseCurve::Piecewise { points } => piecewise(points, x),
+        }
+    }
+
+    /// 检查曲线参数是否合法，用于保存预设前的校验
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            ResponseCurve::Linear => Ok(()),
+            ResponseCurve::Power { exponent } => {
+                if (0.1..=10.0).contains(exponent) {
+                    Ok(())
+                } else {
+                    Err("曲线指数必须在 0.1-10 范围内".to_string())
+                }
+            }
+            ResponseCurve::SCurve { strength } => {
+                if (0.0..=1.0).contains(strength) {
+                    Ok(())
+                } else {
+                    Err("S 形曲线强度必须在 0-1 范围内".to_string())
+                }
+            }
+            ResponseCurve::Piecewise { points } => {
+                if points.len() > MAX_PIECEWISE_POINTS {
+                    return Err(format!("分段曲线最多 {MAX_PIECEWISE_POINTS} 个控制点"));
+                }
+                if points
+                    .iter()
+                    .any(|[x, y]| *x <= 0.0 || *x >= 1.0 || !(0.0..=1.0).contains(y))
+                {
+                    return Err("分段曲线控制点的输入值须在 0-1 之间（不含端点），输出值须在 0-1 范围内".to_string());
+                }
+                if points.windows(2).any(|w| w[1][0] <= w[0][0]) {
+                    return Err("分段曲线控制点的输入值必须严格递增".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// 在 (0, 0)、控制点、(1, 1) 组成的折线上线性插值
+fn piecewise(points: &[[f32; 2]], x: f32) -> f32 {
+    let mut prev = [0.0, 0.0];
+    for &point in points.iter().chain(std::iter::once(&[1.0, 1.0])) {
+        if x <= point[0] {
+            let span = point[0] - prev[0];
+            if span <= f32::EPSILON {
+                return point[1];
+            }
+            return prev[1] + (point[1] - prev[1]) * (x - prev[0]) / span;
+        }
+        prev = point;
+    }
+    1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curves() -> Vec<ResponseCurve> {
+        vec![
+            ResponseCurve::Linear,
+            ResponseCurve::Power { exponent: 0.5 },
+            ResponseCurve::Power { exponent: 2.5 },
+            ResponseCurve::SCurve { strength: 1.0 },
+            ResponseCurve::SCurve { strength: 0.3 },
+            ResponseCurve::Piecewise { points: vec![[0.2, 0.05], [0.5, 0.6], [0.8, 0.7]] },
+            ResponseCurve::Piecewise { points: vec![] },
+        ]
+    }
+
+    #[test]
+    fn endpoints_are_fixed() {
+        for curve in curves() {
+            assert!(curve.validate().is_ok(), "{curve:?}");
+            assert!(curve.apply(0.0).abs() < 1e-6, "{curve:?}");
+            assert!((curve.apply(1.0) - 1.0).abs() < 1e-6, "{curve:?}");
+            // 超出范围的输入先限制到 0..1
+            assert!(curve.apply(-0.5).abs() < 1e-6, "{curve:?}");
+            assert!((curve.apply(1.5) - 1.0).abs() < 1e-6, "{curve:?}");
+        }
+    }
+
+    #[test]
+    fn curves_are_monotonic() {
+        for curve in curves() {
+            let mut prev = curve.apply(0.0);
+            for i in 1..=100 {
+                let y = curve.apply(i as f32 / 100.0);
+                assert!(y >= prev, "{curve:?} 在 {} 处递减", i as f32 / 100.0);
+                assert!((0.0..=1.0).contains(&y), "{curve:?}");
+                prev = y;
+            }
+        }
+    }
+
+    #[test]
+    fn piecewise_interpolates_between_points() {
+        let curve = ResponseCurve::Piecewise { points: vec![[0.5, 0.2]] };
+        assert!((curve.apply(0.25) - 0.1).abs() < 1e-6);
+        assert!((curve.apply(0.5) - 0.2).abs() < 1e-6);
+        assert!((curve.apply(0.75) - 0.6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn validate_rejects_invalid_piecewise_points() {
+        let invalid = [
+            // 未排序 / 重复的输入值
+            vec![[0.6, 0.5], [0.4, 0.6]],
+            vec![[0.5, 0.5], [0.5, 0.6]],
+            // 输入值落在端点或范围外
+            vec![[0.0, 0.1]],
+            vec![[1.0, 0.9]],
+            vec![[-0.2, 0.1]],
+            // 输出值超出 0-1
+            vec![[0.5, 1.2]],
+            vec![[0.5, -0.1]],
+            // 控制点过多
+            (1..=MAX_PIECEWISE_POINTS + 1)
+                .map(|i| [i as f32 / (MAX_PIECEWISE_POINTS + 2) as f32, 0.5])
+                .collect(),
+        ];
+        for points in invalid {
+            let curve = ResponseCurve::Piecewise { points };
+            assert!(curve.validate().is_err(), "{curve:?}");
+        }
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_parameters() {
+        assert!(ResponseCurve::Power { exponent: 0.05 }.validate().is_err());
+        assert!(ResponseCurve::Power { exponent: 11.0 }.validate().is_err());
+        assert!(ResponseCurve::SCurve { strength: -0.1 }.validate().is_err());
+        assert!(ResponseCurve::SCurve { strength: 1.1 }.validate().is_err());
+    }
+}
@@ -31,6 +31,45 @@ impl ControllerTrigger {
     }
 }
 
+/// 体感数据：陀螺仪单位为 deg/s，加速度计单位为 g
+///
+/// 两者使用同一坐标系：分量依次沿俯仰轴（左右）、偏航轴（上下）、横滚轴（前后），
+/// 手柄平放静止时加速度约为 (0, 1, 0)。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub struct ControllerImu {
+    pub gyro: [f32; 3],  // pitch, yaw, roll
+    pub accel: [f32; 3], // pitch 轴, yaw 轴, roll 轴
+    pub has_imu: bool,   // true if the controller reports IMU data
+}
+
+/// 单个触摸点，坐标归一化到 [0, 1]，原点在触摸板左上角
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub struct TouchPoint {
+    pub active: bool,
+    pub id: u8,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// 触摸板数据（DS4 / DualSense 最多两指）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub struct ControllerTouchpad {
+    pub points: [TouchPoint; 2],
+    pub has_touchpad: bool,
+}
+
+impl ControllerTouchpad {
+    /// 当前接触的手指数
+    pub fn active_count(&self) -> usize {
+        self.points.iter().filter(|p| p.active).count()
+    }
+
+    /// 第一个接触的手指
+    pub fn primary(&self) -> Option<&TouchPoint> {
+        self.points.iter().find(|p| p.active)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct ControllerLimits {
     pub sticks_value_min: f32,
@@ -97,6 +136,25 @@ pub enum ControllerButtons {
     Right,
     Up,
     Down,
+
+    // PlayStation touchpad click
+    Touchpad,
+
+    // DualSense mute button
+    Mute,
+
+    // 触摸板分区按下（虚拟按键，由触摸板按下时手指所在位置决定）
+    TouchpadLeft,
+    TouchpadRight,
+
+    // 背键（Xbox Elite 按手柄背面标注：P1 右上、P2 右下、P3 左上、P4 左下）
+    P1,
+    P2,
+    P3,
+    P4,
+
+    // 分享 / 截图键（Xbox Series 的 Share、Switch 的 Capture）
+    Capture,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -117,6 +175,9 @@ pub struct ControllerDatas {
     pub right_stick_center: (f32, f32),
 
     pub limits: ControllerLimits,
+
+    pub imu: ControllerImu,
+    pub touchpad: ControllerTouchpad,
 }
 
 impl Default for ControllerDatas {
@@ -159,6 +220,8 @@ impl ControllerDatas {
                 triggers_value_min: 0.0,
                 triggers_value_max: 255.0,
             },
+            imu: ControllerImu::default(),
+            touchpad: ControllerTouchpad::default(),
         }
     }
 
@@ -186,6 +249,33 @@ impl ControllerDatas {
         self.get_button(button)
     }
 
+    /// 根据触摸板按下时手指所在的左右半区，更新 TouchpadLeft / TouchpadRight 虚拟按键
+    pub fn update_touchpad_regions(&mut self) {
+        let pressed = self.get_button(ControllerButtons::Touchpad);
+        let finger_x = self.touchpad.primary().map(|p| p.x);
+        self.set_button(
+            ControllerButtons::TouchpadLeft,
+            pressed && finger_x.is_some_and(|x| x < 0.5),
+        );
+        self.set_button(
+            ControllerButtons::TouchpadRight,
+            pressed && finger_x.is_some_and(|x| x >= 0.5),
+        );
+    }
+
+    /// 复制来自手柄的输入字段（按键、摇杆、扳机、量程、体感、触摸板），
+    /// 不覆盖摇杆中心、旋转状态等由本程序维护的字段
+    pub fn copy_input_from(&mut self, other: &ControllerDatas) {
+        self.buttons = other.buttons;
+        self.left_stick = other.left_stick;
+        self.right_stick = other.right_stick;
+        self.left_trigger = other.left_trigger;
+        self.right_trigger = other.right_trigger;
+        self.limits = other.limits;
+        self.imu = other.imu;
+        self.touchpad = other.touchpad;
+    }
+
     pub fn as_compact(self) -> CompactControllerDatas {
         let mut d = CompactControllerDatas::new();
         d.buttons = self.buttons;
@@ -0,0 +1,679 @@
+//! Linux evdev 原生输入后端
+//!
+//! 直接打开 `/dev/input/event*`，不经过 gilrs 的映射层：
+//! - 事件自带内核时间戳，可精确到微秒
+//! - 通过 EVIOCGABS 拿到每个轴的原始范围，用于填充 `ControllerLimits`
+//! - gilrs 识别错误（或完全不识别）的手柄，只要内核驱动遵循 gamepad 规范即可使用
+//!
+//! `EvdevState` 只负责把事件流折叠成 `ControllerDatas`，不触碰任何设备文件，
+//! 因此可以直接喂入 `InputEvent::new(..)` 构造的合成事件流进行验证。
+
+use crate::controller::backend::InputBackend;
+use crate::controller::controller::DeviceInfo;
+use crate::controller::datas::{ControllerButtons, ControllerDatas};
+use crate::controller::logic;
+use crate::controller::scripted;
+use crate::controller::slots::{self, PerSlot, MAX_SLOTS};
+use evdev::{AbsoluteAxisCode, Device, EventSummary, InputEvent, KeyCode, SynchronizationCode};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+use std::time::SystemTime;
+
+/// evdev 后端负责的全部按键（不含 LT/RT，扳机按键状态由映射模块根据阈值计算）
+const EVDEV_BUTTONS: [ControllerButtons; 20] = [
+    ControllerButtons::South,
+    ControllerButtons::East,
+    ControllerButtons::West,
+    ControllerButtons::North,
+    ControllerButtons::LB,
+    ControllerButtons::RB,
+    ControllerButtons::LStick,
+    ControllerButtons::RStick,
+    ControllerButtons::Back,
+    ControllerButtons::Start,
+    ControllerButtons::Guide,
+    ControllerButtons::Left,
+    ControllerButtons::Right,
+    ControllerButtons::Up,
+    ControllerButtons::Down,
+    ControllerButtons::P1,
+    ControllerButtons::P2,
+    ControllerButtons::P3,
+    ControllerButtons::P4,
+    ControllerButtons::Capture,
+];
+
+/// 单个绝对轴的原始范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AxisRange {
+    pub min: i32,
+    pub max: i32,
+}
+
+impl AxisRange {
+    pub fn new(min: i32, max: i32) -> Self {
+        Self { min, max }
+    }
+}
+
+/// 摇杆/扳机所使用的轴编号
+///
+/// 大多数驱动（xpad、hid-playstation、hid-nintendo）右摇杆走 RX/RY，扳机走 Z/RZ；
+/// hid-generic 下的部分蓝牙手柄则是右摇杆走 Z/RZ，扳机走 GAS/BRAKE。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvdevAxisLayout {
+    pub left_x: AbsoluteAxisCode,
+    pub left_y: AbsoluteAxisCode,
+    pub right_x: AbsoluteAxisCode,
+    pub right_y: AbsoluteAxisCode,
+    pub left_trigger: AbsoluteAxisCode,
+    pub right_trigger: AbsoluteAxisCode,
+}
+
+impl Default for EvdevAxisLayout {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+impl EvdevAxisLayout {
+    /// Linux gamepad 规范布局
+    pub fn standard() -> Self {
+        Self {
+            left_x: AbsoluteAxisCode::ABS_X,
+            left_y: AbsoluteAxisCode::ABS_Y,
+            right_x: AbsoluteAxisCode::ABS_RX,
+            right_y: AbsoluteAxisCode::ABS_RY,
+            left_trigger: AbsoluteAxisCode::ABS_Z,
+            right_trigger: AbsoluteAxisCode::ABS_RZ,
+        }
+    }
+
+    /// hid-generic 蓝牙手柄常见布局
+    pub fn generic_hid() -> Self {
+        Self {
+            left_x: AbsoluteAxisCode::ABS_X,
+            left_y: AbsoluteAxisCode::ABS_Y,
+            right_x: AbsoluteAxisCode::ABS_Z,
+            right_y: AbsoluteAxisCode::ABS_RZ,
+            left_trigger: AbsoluteAxisCode::ABS_BRAKE,
+            right_trigger: AbsoluteAxisCode::ABS_GAS,
+        }
+    }
+
+    /// 根据设备实际支持的轴推断布局
+    pub fn detect(device: &Device) -> Self {
+        match device.supported_absolute_axes() {
+            Some(axes)
+                if !axes.contains(AbsoluteAxisCode::ABS_RX)
+                    && axes.contains(AbsoluteAxisCode::ABS_GAS) =>
+            {
+                Self::generic_hid()
+            }
+            _ => Self::standard(),
+        }
+    }
+}
+
+/// evdev 事件流 → `ControllerDatas` 的状态机
+#[derive(Debug, Clone)]
+pub struct EvdevState {
+    datas: ControllerDatas,
+    layout: EvdevAxisLayout,
+    axis_ranges: HashMap<AbsoluteAxisCode, AxisRange>,
+    hat_x: i32,
+    hat_y: i32,
+    /// 最近一次 SYN_REPORT 的内核时间戳
+    last_report_time: Option<SystemTime>,
+    /// 收到 SYN_DROPPED 后，丢弃事件直到下一个 SYN_REPORT
+    dropping: bool,
+    /// 丢弃结束后需要从设备重新读取完整状态
+    needs_resync: bool,
+}
+
+impl Default for EvdevState {
+    fn default() -> Self {
+        Self::new(EvdevAxisLayout::standard())
+    }
+}
+
+impl EvdevState {
+    pub fn new(layout: EvdevAxisLayout) -> Self {
+        Self {
+            datas: ControllerDatas::new(),
+            layout,
+            axis_ranges: HashMap::new(),
+            hat_x: 0,
+            hat_y: 0,
+            last_report_time: None,
+            dropping: false,
+            needs_resync: false,
+        }
+    }
+
+    /// 从真实设备构建：推断布局并读取全部轴范围
+    pub fn from_device(device: &Device) -> Self {
+        let mut state = Self::new(EvdevAxisLayout::detect(device));
+        match device.get_absinfo() {
+            Ok(infos) => {
+                for (code, info) in infos {
+                    state.set_axis_range(code, AxisRange::new(info.minimum(), info.maximum()));
+                }
+            }
+            Err(e) => log::warn!("读取 evdev 轴范围失败: {e}"),
+        }
+        state
+    }
+
+    /// 设置某个轴的原始范围，同时刷新 `ControllerLimits`
+    pub fn set_axis_range(&mut self, code: AbsoluteAxisCode, range: AxisRange) {
+        self.axis_ranges.insert(code, range);
+
+        let sticks = self.axis_range(self.layout.left_x);
+        let triggers = self.axis_range(self.layout.left_trigger);
+        self.datas.limits.set_limits(
+            sticks.min as f32,
+            sticks.max as f32,
+            triggers.min as f32,
+            triggers.max as f32,
+        );
+    }
+
+    /// 获取轴范围，未知轴按 xpad 的默认范围处理
+    pub fn axis_range(&self, code: AbsoluteAxisCode) -> AxisRange {
+        self.axis_ranges.get(&code).copied().unwrap_or_else(|| {
+            if code == self.layout.left_trigger || code == self.layout.right_trigger {
+                AxisRange::new(0, 255)
+            } else {
+                AxisRange::new(-32768, 32767)
+            }
+        })
+    }
+
+    pub fn datas(&self) -> &ControllerDatas {
+        &self.datas
+    }
+
+    pub fn last_report_time(&self) -> Option<SystemTime> {
+        self.last_report_time
+    }
+
+    /// 是否需要从设备重新读取完整状态（SYN_DROPPED 之后），读取一次后复位
+    pub fn take_needs_resync(&mut self) -> bool {
+        std::mem::take(&mut self.needs_resync)
+    }
+
+    /// 用设备当前的完整状态覆盖事件流折叠出的状态
+    ///
+    /// `pressed_keys` 为当前按下的全部按键，未列出的按键视为松开
+    pub fn resync(&mut self, pressed_keys: &[KeyCode], axes: &[(AbsoluteAxisCode, i32)]) {
+        for button in EVDEV_BUTTONS {
+            self.datas.set_button(button, false);
+        }
+        self.datas.left_stick.is_pressed = false;
+        self.datas.right_stick.is_pressed = false;
+        for key in pressed_keys {
+            self.apply_key(*key, true);
+        }
+        for (code, value) in axes {
+            self.apply_axis(*code, *value);
+        }
+    }
+
+    /// 处理单个事件，返回 true 表示收到 SYN_REPORT（一帧数据已完整）
+    ///
+    /// 收到 SYN_DROPPED 表示内核缓冲区溢出、部分事件已丢失：此后直到下一个 SYN_REPORT
+    /// 的事件都不完整，全部丢弃，并标记需要重新同步
+    pub fn apply_event(&mut self, event: &InputEvent) -> bool {
+        if self.dropping {
+            if let EventSummary::Synchronization(_, SynchronizationCode::SYN_REPORT, _) =
+                event.destructure()
+            {
+                self.dropping = false;
+                self.needs_resync = true;
+                self.last_report_time = Some(event.timestamp());
+            }
+            return false;
+        }
+
+        match event.destructure() {
+            EventSummary::Synchronization(_, SynchronizationCode::SYN_DROPPED, _) => {
+                self.dropping = true;
+                false
+            }
+            EventSummary::Key(_, code, value) => {
+                self.apply_key(code, value != 0);
+                false
+            }
+            EventSummary::AbsoluteAxis(_, code, value) => {
+                self.apply_axis(code, value);
+                false
+            }
+            EventSummary::Synchronization(_, SynchronizationCode::SYN_REPORT, _) => {
+                self.last_report_time = Some(event.timestamp());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 依次处理一批事件，返回其中完整帧的数量
+    pub fn apply_events<'a>(&mut self, events: impl IntoIterator<Item = &'a InputEvent>) -> usize {
+        events
+            .into_iter()
+            .filter(|event| self.apply_event(event))
+            .count()
+    }
+
+    fn apply_key(&mut self, code: KeyCode, pressed: bool) {
+        // 注意：BTN_NORTH 与 BTN_X、BTN_WEST 与 BTN_Y 在内核中是同一个值，
+        // 这里按 gamepad 规范的物理方位解释
+        let button = match code {
+            KeyCode::BTN_SOUTH => ControllerButtons::South,
+            KeyCode::BTN_EAST => ControllerButtons::East,
+            KeyCode::BTN_NORTH => ControllerButtons::North,
+            KeyCode::BTN_WEST => ControllerButtons::West,
+            KeyCode::BTN_TL => ControllerButtons::LB,
+            KeyCode::BTN_TR => ControllerButtons::RB,
+            KeyCode::BTN_SELECT => ControllerButtons::Back,
+            KeyCode::BTN_START => ControllerButtons::Start,
+            KeyCode::BTN_MODE => ControllerButtons::Guide,
+            KeyCode::BTN_THUMBL => {
+                self.datas.left_stick.is_pressed = pressed;
+                ControllerButtons::LStick
+            }
+            KeyCode::BTN_THUMBR => {
+                self.datas.right_stick.is_pressed = pressed;
+                ControllerButtons::RStick
+            }
+            KeyCode::BTN_DPAD_UP => ControllerButtons::Up,
+            KeyCode::BTN_DPAD_DOWN => ControllerButtons::Down,
+            KeyCode::BTN_DPAD_LEFT => ControllerButtons::Left,
+            KeyCode::BTN_DPAD_RIGHT => ControllerButtons::Right,
+            // xpad 把 Elite 背键依次报告为 TRIGGER_HAPPY5-8（右上、右下、左上、左下）
+            KeyCode::BTN_TRIGGER_HAPPY5 => ControllerButtons::P1,
+            KeyCode::BTN_TRIGGER_HAPPY6 => ControllerButtons::P2,
+            KeyCode::BTN_TRIGGER_HAPPY7 => ControllerButtons::P3,
+            KeyCode::BTN_TRIGGER_HAPPY8 => ControllerButtons::P4,
+            // xpad 的 Xbox Series 分享键 / hid-nintendo 的截图键
+            KeyCode::KEY_RECORD | KeyCode::BTN_Z => ControllerButtons::Capture,
+            _ => return,
+        };
+        self.datas.set_button(button, pressed);
+    }
+
+    fn apply_axis(&mut self, code: AbsoluteAxisCode, value: i32) {
+        let layout = self.layout;
+        match code {
+            AbsoluteAxisCode::ABS_HAT0X => {
+                self.hat_x = value.signum();
+                self.datas.set_button(ControllerButtons::Left, self.hat_x < 0);
+                self.datas.set_button(ControllerButtons::Right, self.hat_x > 0);
+            }
+            AbsoluteAxisCode::ABS_HAT0Y => {
+                self.hat_y = value.signum();
+                self.datas.set_button(ControllerButtons::Up, self.hat_y < 0);
+                self.datas.set_button(ControllerButtons::Down, self.hat_y > 0);
+            }
+            c if c == layout.left_x => self.datas.left_stick.x = self.normalize_stick(c, value),
+            // evdev 的 Y 轴向下为正，统一翻转为向上为正（与 XInput/gilrs 一致）
+            c if c == layout.left_y => self.datas.left_stick.y = -self.normalize_stick(c, value),
+            c if c == layout.right_x => self.datas.right_stick.x = self.normalize_stick(c, value),
+            c if c == layout.right_y => self.datas.right_stick.y = -self.normalize_stick(c, value),
+            c if c == layout.left_trigger => {
+                self.datas.left_trigger.value = self.normalize_trigger(c, value);
+                self.datas.left_trigger.has_pressure = true;
+            }
+            c if c == layout.right_trigger => {
+                self.datas.right_trigger.value = self.normalize_trigger(c, value);
+                self.datas.right_trigger.has_pressure = true;
+            }
+            _ => {}
+        }
+    }
+
+    fn normalize_stick(&self, code: AbsoluteAxisCode, value: i32) -> f32 {
+        let range = self.axis_range(code);
+        (logic::normalize(value, range.min, range.max, -1.0, 1.0).unwrap_or(0.0) as f32)
+            .clamp(-1.0, 1.0)
+    }
+
+    fn normalize_trigger(&self, code: AbsoluteAxisCode, value: i32) -> f32 {
+        let range = self.axis_range(code);
+        (logic::normalize(value, range.min, range.max, 0.0, 1.0).unwrap_or(0.0) as f32)
+            .clamp(0.0, 1.0)
+    }
+}
+
+/// 已打开的 evdev 手柄
+struct EvdevController {
+    path: PathBuf,
+    vendor_id: u16,
+    product_id: u16,
+    device: Device,
+    state: EvdevState,
+}
+
+/// 当前打开的 evdev 设备（由 listen 线程独占使用）
+static EVDEV_CONTROLLER: Lazy<PerSlot<Mutex<Option<EvdevController>>>> =
+    Lazy::new(|| PerSlot::new(|| Mutex::new(None)));
+
+/// 最近一帧的内核时间戳
+pub static LAST_EVDEV_REPORT_TIME: Lazy<PerSlot<RwLock<Option<SystemTime>>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(None)));
+
+/// 判断 evdev 设备是否为手柄（而非同一手柄附带的触摸板/体感子设备）
+fn is_gamepad(device: &Device) -> bool {
+    // 跳过本程序通过 uinput 创建的虚拟手柄
+    if device.name() == Some(crate::mapping::virtual_pad::VIRTUAL_PAD_NAME) {
+        return false;
+    }
+    let has_buttons = device
+        .supported_keys()
+        .is_some_and(|keys| keys.contains(KeyCode::BTN_SOUTH) || keys.contains(KeyCode::BTN_GAMEPAD));
+    let has_sticks = device
+        .supported_absolute_axes()
+        .is_some_and(|axes| axes.contains(AbsoluteAxisCode::ABS_X) && axes.contains(AbsoluteAxisCode::ABS_Y));
+    has_buttons && has_sticks
+}
+
+fn parse_hex_id(id: &str) -> u16 {
+    u16::from_str_radix(id, 16).unwrap_or(0)
+}
+
+/// 枚举系统中所有 evdev 手柄
+pub fn list_gamepads() -> Vec<DeviceInfo> {
+    evdev::enumerate()
+        .filter(|(_, device)| is_gamepad(device))
+        .map(|(path, device)| {
+            let id = device.input_id();
+            let vid = format!("{:04x}", id.vendor());
+            let mut info = DeviceInfo::new(
+                device.name().unwrap_or("Unknown evdev gamepad").to_string(),
+                vid.clone(),
+                crate::controller::detect_controller_type(&vid),
+            );
+            info.product_id = Some(format!("{:04x}", id.product()));
+            log::debug!("evdev 检测到手柄: {} ({path:?})", info.name);
+            info
+        })
+        .collect()
+}
+
+/// 按 VID/PID 查找并打开对应的 evdev 手柄
+fn open_controller(device: &DeviceInfo) -> Option<EvdevController> {
+    let vendor_id = parse_hex_id(&device.vendor_id);
+    let product_id = parse_hex_id(device.product_id.as_deref().unwrap_or("0000"));
+
+    // 跳过其他槽位已打开的同型号手柄
+    let active_slot = slots::active_slot();
+    let claimed: Vec<PathBuf> = (0..MAX_SLOTS)
+        .filter(|slot| *slot != active_slot)
+        .filter_map(|slot| EVDEV_CONTROLLER.slot(slot).lock().unwrap().as_ref().map(|c| c.path.clone()))
+        .collect();
+
+    let (path, evdev_device) = evdev::enumerate().find(|(path, d)| {
+        let id = d.input_id();
+        id.vendor() == vendor_id && id.product() == product_id && is_gamepad(d) && !claimed.contains(path)
+    })?;
+
+    if let Err(e) = evdev_device.set_nonblocking(true) {
+        log::error!("evdev 设备设置非阻塞失败 ({path:?}): {e}");
+        return None;
+    }
+
+    log::info!("✅ evdev 已打开手柄: {} ({path:?})", device.name);
+    let state = EvdevState::from_device(&evdev_device);
+    Some(EvdevController {
+        path,
+        vendor_id,
+        product_id,
+        device: evdev_device,
+        state,
+    })
+}
+
+/// SYN_DROPPED 之后从设备读取当前完整的按键与轴状态
+fn resync_from_device(controller: &mut EvdevController) -> std::io::Result<()> {
+    let pressed_keys: Vec<KeyCode> = controller.device.get_key_state()?.iter().collect();
+    let axes: Vec<(AbsoluteAxisCode, i32)> = controller
+        .device
+        .get_absinfo()?
+        .map(|(code, info)| (code, info.value()))
+        .collect();
+    controller.state.resync(&pressed_keys, &axes);
+    Ok(())
+}
+
+/// 关闭当前 evdev 设备
+pub fn close() {
+    if let Some(controller) = EVDEV_CONTROLLER.lock().unwrap().take() {
+        log::info!("evdev 设备已关闭: {:?}", controller.path);
+    }
+}
+
+/// 确保当前槽位已打开该设备（与已打开的设备不一致时重新打开）
+fn ensure_open(slot: &mut Option<EvdevController>, device: &DeviceInfo) {
+    let vendor_id = parse_hex_id(&device.vendor_id);
+    let product_id = parse_hex_id(device.product_id.as_deref().unwrap_or("0000"));
+    if slot
+        .as_ref()
+        .is_some_and(|c| c.vendor_id != vendor_id || c.product_id != product_id)
+    {
+        *slot = None;
+    }
+    if slot.is_none() {
+        *slot = open_controller(device);
+    }
+}
+
+/// evdev 手柄轮询入口，输入写入 `controller_data`
+/// 返回 true 表示设备已打开并完成一次读取，false 表示 evdev 不可用（调用方应回退 gilrs）
+pub fn poll_evdev_controller(device: &DeviceInfo, controller_data: &mut ControllerDatas) -> bool {
+    let mut guard = EVDEV_CONTROLLER.lock().unwrap();
+    ensure_open(&mut guard, device);
+    let Some(controller) = guard.as_mut() else {
+        return false;
+    };
+
+    // 非阻塞读取内核缓冲区中所有待处理事件
+    let read_result = match controller.device.fetch_events() {
+        Ok(events) => {
+            for event in events {
+                controller.state.apply_event(&event);
+            }
+            Ok(())
+        }
+        Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(()),
+        Err(e) => Err(e),
+    }
+    .and_then(|_| {
+        if controller.state.take_needs_resync() {
+            log::debug!("evdev 事件丢失 (SYN_DROPPED)，重新同步设备状态");
+            resync_from_device(controller)
+        } else {
+            Ok(())
+        }
+    });
+    if let Err(e) = read_result {
+        log::warn!("evdev 读取失败，关闭设备 ({:?}): {e}", controller.path);
+        *guard = None;
+        return false;
+    }
+
+    let datas = *controller.state.datas();
+    *LAST_EVDEV_REPORT_TIME.write().unwrap() = controller.state.last_report_time();
+    drop(guard);
+
+    for button in EVDEV_BUTTONS {
+        controller_data.set_button(button, datas.get_button(button));
+    }
+    controller_data.left_stick = datas.left_stick;
+    controller_data.right_stick = datas.right_stick;
+    controller_data.left_trigger.value = datas.left_trigger.value;
+    controller_data.left_trigger.has_pressure = datas.left_trigger.has_pressure;
+    controller_data.right_trigger.value = datas.right_trigger.value;
+    controller_data.right_trigger.has_pressure = datas.right_trigger.has_pressure;
+    controller_data.limits = datas.limits;
+    true
+}
+
+/// Linux evdev 输入后端：所有手柄都可尝试，内核驱动不遵循 gamepad 规范时回退 gilrs
+pub struct EvdevBackend;
+
+impl InputBackend for EvdevBackend {
+    fn name(&self) -> &'static str {
+        "evdev"
+    }
+
+    fn supports(&self, device: &DeviceInfo) -> bool {
+        !scripted::is_scripted(device)
+    }
+
+    fn enumerate(&self) -> Vec<DeviceInfo> {
+        list_gamepads()
+    }
+
+    fn open(&self, device: &DeviceInfo) -> bool {
+        let mut guard = EVDEV_CONTROLLER.lock().unwrap();
+        ensure_open(&mut guard, device);
+        guard.is_some()
+    }
+
+    fn poll(&self, device: &DeviceInfo, datas: &mut ControllerDatas) -> bool {
+        poll_evdev_controller(device, datas)
+    }
+
+    fn close(&self) {
+        close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use evdev::EventType;
+
+    fn key(code: KeyCode, value: i32) -> InputEvent {
+        InputEvent::new(EventType::KEY.0, code.0, value)
+    }
+
+    fn abs(code: AbsoluteAxisCode, value: i32) -> InputEvent {
+        InputEvent::new(EventType::ABSOLUTE.0, code.0, value)
+    }
+
+    fn syn(code: SynchronizationCode) -> InputEvent {
+        InputEvent::new(EventType::SYNCHRONIZATION.0, code.0, 0)
+    }
+
+    #[test]
+    fn stick_y_is_inverted_to_up_positive() {
+        let mut state = EvdevState::default();
+        state.set_axis_range(AbsoluteAxisCode::ABS_Y, AxisRange::new(-32768, 32767));
+        state.set_axis_range(AbsoluteAxisCode::ABS_RY, AxisRange::new(-32768, 32767));
+
+        let frames = state.apply_events(&[
+            abs(AbsoluteAxisCode::ABS_Y, -32768),
+            abs(AbsoluteAxisCode::ABS_RY, 32767),
+            syn(SynchronizationCode::SYN_REPORT),
+        ]);
+
+        assert_eq!(frames, 1);
+        // evdev 向上推为负值，转换后向上为正
+        assert!((state.datas().left_stick.y - 1.0).abs() < 1e-4);
+        assert!((state.datas().right_stick.y + 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn hat_maps_to_dpad_buttons() {
+        let mut state = EvdevState::default();
+        state.apply_events(&[
+            abs(AbsoluteAxisCode::ABS_HAT0X, -1),
+            abs(AbsoluteAxisCode::ABS_HAT0Y, 1),
+            syn(SynchronizationCode::SYN_REPORT),
+        ]);
+        let datas = *state.datas();
+        assert!(datas.get_button(ControllerButtons::Left));
+        assert!(!datas.get_button(ControllerButtons::Right));
+        assert!(datas.get_button(ControllerButtons::Down));
+        assert!(!datas.get_button(ControllerButtons::Up));
+
+        state.apply_events(&[
+            abs(AbsoluteAxisCode::ABS_HAT0X, 1),
+            abs(AbsoluteAxisCode::ABS_HAT0Y, 0),
+            syn(SynchronizationCode::SYN_REPORT),
+        ]);
+        let datas = *state.datas();
+        assert!(!datas.get_button(ControllerButtons::Left));
+        assert!(datas.get_button(ControllerButtons::Right));
+        assert!(!datas.get_button(ControllerButtons::Down));
+    }
+
+    #[test]
+    fn non_symmetric_axis_ranges_are_normalized() {
+        let mut state = EvdevState::default();
+        // hid-nintendo 等驱动常见的非对称范围
+        state.set_axis_range(AbsoluteAxisCode::ABS_X, AxisRange::new(0, 4095));
+        state.set_axis_range(AbsoluteAxisCode::ABS_Z, AxisRange::new(0, 1023));
+
+        state.apply_events(&[
+            abs(AbsoluteAxisCode::ABS_X, 0),
+            abs(AbsoluteAxisCode::ABS_Z, 1023),
+            syn(SynchronizationCode::SYN_REPORT),
+        ]);
+        assert!((state.datas().left_stick.x + 1.0).abs() < 1e-4);
+        assert!((state.datas().left_trigger.value - 1.0).abs() < 1e-4);
+        assert!(state.datas().left_trigger.has_pressure);
+
+        state.apply_events(&[
+            abs(AbsoluteAxisCode::ABS_X, 4095),
+            abs(AbsoluteAxisCode::ABS_Z, 0),
+            syn(SynchronizationCode::SYN_REPORT),
+        ]);
+        assert!((state.datas().left_stick.x - 1.0).abs() < 1e-4);
+        assert!(state.datas().left_trigger.value.abs() < 1e-4);
+
+        let limits = state.datas().limits;
+        assert_eq!(limits.sticks_value_min, 0.0);
+        assert_eq!(limits.sticks_value_max, 4095.0);
+        assert_eq!(limits.triggers_value_max, 1023.0);
+    }
+
+    #[test]
+    fn syn_dropped_discards_events_until_next_report() {
+        let mut state = EvdevState::default();
+        state.apply_events(&[
+            key(KeyCode::BTN_SOUTH, 1),
+            syn(SynchronizationCode::SYN_REPORT),
+        ]);
+        assert!(state.datas().get_button(ControllerButtons::South));
+
+        // 丢弃期间的事件不完整，不应生效，也不计为完整帧
+        let frames = state.apply_events(&[
+            syn(SynchronizationCode::SYN_DROPPED),
+            key(KeyCode::BTN_EAST, 1),
+            syn(SynchronizationCode::SYN_REPORT),
+        ]);
+        assert_eq!(frames, 0);
+        assert!(!state.datas().get_button(ControllerButtons::East));
+        assert!(state.take_needs_resync());
+        assert!(!state.take_needs_resync());
+
+        // 重新同步：South 已在丢失的事件中松开，East 仍按住
+        state.resync(&[KeyCode::BTN_EAST], &[(AbsoluteAxisCode::ABS_HAT0X, 1)]);
+        assert!(!state.datas().get_button(ControllerButtons::South));
+        assert!(state.datas().get_button(ControllerButtons::East));
+        assert!(state.datas().get_button(ControllerButtons::Right));
+
+        // 之后的事件恢复正常处理
+        let frames = state.apply_events(&[
+            key(KeyCode::BTN_EAST, 0),
+            syn(SynchronizationCode::SYN_REPORT),
+        ]);
+        assert_eq!(frames, 1);
+        assert!(!state.datas().get_button(ControllerButtons::East));
+    }
+}
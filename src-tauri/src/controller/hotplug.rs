@@ -0,0 +1,119 @@
+//! 设备热插拔监听
+//!
+//! 事件来源：
+//! - gilrs 的 `Connected` / `Disconnected` 事件（所有平台）
+//! - Linux 下 udev 的 input / hidraw 子系统 add / remove 事件（覆盖 evdev 与 hidapi 后端）
+//!
+//! 事件汇总到工作线程，短时间内的连续事件合并处理（插入一个手柄通常会产生多个设备节点事件）：
+//! 重新扫描设备并发送 `update_devices` 事件，主槽位空闲且开启了“记住上次连接”时自动重连。
+
+use crate::controller::controller::{self, CURRENT_DEVICE};
+use crate::controller::slots::PRIMARY_SLOT;
+use crate::setting::get_setting;
+use once_cell::sync::Lazy;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// 合并连续事件的等待时间
+const HOTPLUG_DEBOUNCE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotplugEvent {
+    Added,
+    Removed,
+}
+
+/// 热插拔工作线程的发送器
+static HOTPLUG_SENDER: Lazy<Sender<HotplugEvent>> = Lazy::new(|| {
+    let (tx, rx): (Sender<HotplugEvent>, Receiver<HotplugEvent>) = channel();
+    thread::spawn(move || hotplug_worker(rx));
+    tx
+});
+
+/// 通知发生了设备插拔
+pub fn notify(event: HotplugEvent) {
+    if let Err(e) = HOTPLUG_SENDER.send(event) {
+        log::error!("发送热插拔事件失败: {e}");
+    }
+}
+
+fn hotplug_worker(rx: Receiver<HotplugEvent>) {
+    while let Ok(first) = rx.recv() {
+        let mut any_added = first == HotplugEvent::Added;
+        while let Ok(event) = rx.recv_timeout(HOTPLUG_DEBOUNCE) {
+            any_added |= event == HotplugEvent::Added;
+        }
+        handle_device_change(any_added);
+    }
+}
+
+/// 刷新设备列表，并在有新设备插入时尝试重连上次连接的设备
+fn handle_device_change(any_added: bool) {
+    let devices = controller::query_devices();
+    log::info!("🔌 设备热插拔，当前可用设备 {} 个", devices.len());
+
+    if !any_added {
+        return;
+    }
+
+    let settings = get_setting();
+    let Some(last_device) = settings.last_connected_device.filter(|_| settings.remember_last_connection) else {
+        return;
+    };
+    if CURRENT_DEVICE.slot(PRIMARY_SLOT).read().unwrap().device_path.is_some() {
+        return;
+    }
+
+    let Some(device_info) = controller::find_last_connected_device(devices, &last_device) else {
+        return;
+    };
+    log::info!("上次连接的设备已重新插入，自动连接: {}", device_info.name);
+    if controller::use_device(device_info.name.clone(), Some(PRIMARY_SLOT)) {
+        controller::emit_event("auto_connect_success", device_info);
+    } else {
+        log::error!("自动重连设备失败: {}", device_info.name);
+    }
+}
+
+/// Linux 下监听 udev 事件
+#[cfg(target_os = "linux")]
+pub fn udev_listen() {
+    thread::spawn(|| {
+        let socket = match udev::MonitorBuilder::new()
+            .and_then(|b| b.match_subsystem("input"))
+            .and_then(|b| b.match_subsystem("hidraw"))
+            .and_then(|b| b.listen())
+        {
+            Ok(socket) => socket,
+            Err(e) => {
+                log::error!("创建 udev 监听失败，热插拔仅依赖 gilrs 事件: {e}");
+                return;
+            }
+        };
+        log::info!("🎧 启动 udev 热插拔监听");
+
+        loop {
+            // udev 监听套接字为非阻塞，读空后稍作等待
+            for event in socket.iter() {
+                // 只关心具体的设备节点（/dev/input/event*、/dev/hidraw*）
+                if event.devnode().is_none() {
+                    continue;
+                }
+                match event.event_type() {
+                    udev::EventType::Add => notify(HotplugEvent::Added),
+                    udev::EventType::Remove => notify(HotplugEvent::Removed),
+                    _ => {}
+                }
+            }
+            thread::sleep(Duration::from_millis(200));
+        }
+    });
+}
+
+/// 启动平台相关的热插拔监听（gilrs 事件在 `gilrs_listen` 中转发）
+pub fn initialize() {
+    #[cfg(target_os = "linux")]
+    udev_listen();
+}
+
@@ -0,0 +1,65 @@
+//! 手柄灯光输出（灯条颜色 / 玩家指示灯）
+//!
+//! 每个监听周期按当前生效的预设（副预设激活时使用副预设）同步灯光，
+//! 让主/副预设的切换状态直接显示在手柄上：
+//! - PlayStation：DS4 灯条；DualSense 灯条 + 玩家指示灯（见 `ps4` 模块）
+//! - Switch Pro：玩家指示灯（见 `switch_pro` 模块）
+//! - 其他设备没有可控制的灯光，直接忽略
+//!
+//! 各后端自行记录上次写入的状态，灯光未变化时不会重复发送输出报告。
+
+use crate::controller::controller::{ControllerType, CURRENT_DEVICE};
+use crate::controller::slots::{self, MAX_SLOTS};
+use crate::controller::{ps4, switch_pro};
+use crate::preset;
+
+/// 预设未指定颜色时各槽位的默认灯条颜色（蓝、红、绿、粉，与 PS 主机一致）
+const SLOT_COLORS: [(u8, u8, u8); MAX_SLOTS] = [
+    (0x00, 0x00, 0x40),
+    (0x40, 0x00, 0x00),
+    (0x00, 0x40, 0x00),
+    (0x20, 0x00, 0x20),
+];
+
+/// 解析 "#RRGGBB" 颜色（`#` 可省略）
+pub fn parse_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// 当前生效预设的灯光配置
+fn active_preset_led(use_sub_preset: bool) -> (Option<String>, Option<u8>) {
+    if use_sub_preset {
+        if let Some(sub) = preset::CURRENT_SUB_PRESET.read().unwrap().as_ref() {
+            return (sub.items.led_color.clone(), sub.items.player_led);
+        }
+    }
+    let preset = preset::CURRENT_PRESET.read().unwrap();
+    (preset.items.led_color.clone(), preset.items.player_led)
+}
+
+/// 将当前生效预设的灯光同步到当前槽位的手柄
+pub fn sync(use_sub_preset: bool) {
+    let slot = slots::active_slot();
+    let (led_color, player_led) = active_preset_led(use_sub_preset);
+    let color = led_color
+        .as_deref()
+        .and_then(parse_color)
+        .unwrap_or(SLOT_COLORS[slot]);
+    let player = player_led.unwrap_or(slot as u8 + 1);
+
+    let controller_type = CURRENT_DEVICE.read().unwrap().controller_type;
+    match controller_type {
+        ControllerType::PlayStation => {
+            ps4::set_led(color, player);
+        }
+        ControllerType::Switch => {
+            switch_pro::set_player_lights(player);
+        }
+        _ => {}
+    }
+}
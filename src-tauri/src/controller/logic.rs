@@ -1,8 +1,10 @@
 use crate::controller::controller::{
     CONTROLLER_DATA, JOYSTICK_ROTATION_STATES, JoystickRotationState, JoystickSource,
 };
+use crate::controller::curve::ResponseCurve;
 use crate::controller::datas::{ControllerDatas, ControllerStick, JoystickRotation};
-use crate::{controller, preset};
+use crate::preset::{self, PresetItems};
+use crate::controller;
 use num_traits::ToPrimitive;
 use std::time::Instant;
 
@@ -32,6 +34,77 @@ where
     Some(scaled)
 }
 
+/// 死区塑形参数，均为 0-1 的比例
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DeadzoneShape {
+    /// 内死区：低于该值输出 0
+    pub inner: f32,
+    /// 外死区：高于 1 - outer 即输出满值，用于磨损后推不到底的摇杆 / 扳机
+    pub outer: f32,
+    /// 反死区：离开内死区后输出直接从该值起步，抵消游戏自带的死区
+    pub anti: f32,
+}
+
+impl DeadzoneShape {
+    /// 不做任何处理
+    pub const NONE: DeadzoneShape = DeadzoneShape {
+        inner: 0.0,
+        outer: 0.0,
+        anti: 0.0,
+    };
+
+    pub fn from_percent(inner: u8, outer: u8, anti: u8) -> Self {
+        Self {
+            inner: inner as f32 / 100.0,
+            outer: outer as f32 / 100.0,
+            anti: anti as f32 / 100.0,
+        }
+    }
+
+    /// 处理一个 0-1 的幅度：内死区 → 外死区重缩放 → 响应曲线 → 反死区
+    pub fn apply(&self, magnitude: f32, curve: &ResponseCurve) -> f32 {
+        if magnitude <= 0.0 || magnitude < self.inner {
+            return 0.0;
+        }
+        let span = 1.0 - self.outer - self.inner;
+        let t = if span > f32::EPSILON {
+            ((magnitude - self.inner) / span).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let anti = self.anti.clamp(0.0, 1.0);
+        anti + (1.0 - anti) * curve.apply(t)
+    }
+}
+
+/// 预设中左、右摇杆的死区塑形参数
+pub fn stick_deadzone_shapes(items: &PresetItems) -> (DeadzoneShape, DeadzoneShape) {
+    (
+        DeadzoneShape::from_percent(
+            items.deadzone_left,
+            items.outer_deadzone_left,
+            items.anti_deadzone_left,
+        ),
+        DeadzoneShape::from_percent(items.deadzone, items.outer_deadzone, items.anti_deadzone),
+    )
+}
+
+/// 预设中左、右扳机的死区塑形参数
+pub fn trigger_deadzone_shapes(items: &PresetItems) -> (DeadzoneShape, DeadzoneShape) {
+    (
+        DeadzoneShape::from_percent(
+            items.trigger_deadzone_left,
+            items.trigger_outer_deadzone_left,
+            0,
+        ),
+        DeadzoneShape::from_percent(
+            items.trigger_deadzone_right,
+            items.trigger_outer_deadzone_right,
+            0,
+        ),
+    )
+}
+
 pub(crate) fn get_calibrated_stick_values(
     raw_lx: f32,
     raw_ly: f32,
@@ -40,18 +113,21 @@ pub(crate) fn get_calibrated_stick_values(
 ) -> (f32, f32, f32, f32) {
     let preset = preset::get_current_preset();
     let cali_data = controller::calibrate::get_current_calibration();
+    let (shape_left, shape_right) = stick_deadzone_shapes(&preset.items);
 
     let (lx, ly) = controller::calibrate::apply_calibration(
         raw_lx,
         raw_ly,
-        preset.items.deadzone_left,
+        &shape_left,
+        &preset.items.stick_curve_left,
         &cali_data.left_stick,
     );
 
     let (rx, ry) = controller::calibrate::apply_calibration(
         raw_rx,
         raw_ry,
-        preset.items.deadzone,
+        &shape_right,
+        &preset.items.stick_curve_right,
         &cali_data.right_stick,
     );
     (lx, ly, rx, ry)
@@ -112,14 +188,19 @@ pub fn update_joystick_rotation_state(source: JoystickSource, x: f32, y: f32) ->
     rotation_this_frame
 }
 
-pub fn apply_deadzone_for_stick(controller_stick: &mut ControllerStick, deadzone: f32) {
+/// 对摇杆幅度应用圆形死区塑形与响应曲线（方向保持不变）
+pub fn apply_deadzone_for_stick(
+    controller_stick: &mut ControllerStick,
+    shape: &DeadzoneShape,
+    curve: &ResponseCurve,
+) {
     let magnitude =
         (controller_stick.x * controller_stick.x + controller_stick.y * controller_stick.y).sqrt();
-    if magnitude < deadzone {
+    let rescale_factor = shape.apply(magnitude, curve);
+    if rescale_factor <= 0.0 {
         controller_stick.x = 0.0;
         controller_stick.y = 0.0;
     } else {
-        let rescale_factor = (magnitude - deadzone) / (1.0 - deadzone);
         let norm_x = controller_stick.x / magnitude;
         let norm_y = controller_stick.y / magnitude;
 
@@ -131,11 +212,24 @@ pub fn apply_deadzone_for_stick(controller_stick: &mut ControllerStick, deadzone
 pub fn apply_deadzone(controller_data: &mut ControllerDatas) {
     let preset = preset::get_current_preset();
 
-    let deadzone_left = preset.items.deadzone_left as f32 / 100.0;
-    let deadzone_right = preset.items.deadzone as f32 / 100.0;
+    let (shape_left, shape_right) = stick_deadzone_shapes(&preset.items);
+    apply_deadzone_for_stick(
+        &mut controller_data.left_stick,
+        &shape_left,
+        &preset.items.stick_curve_left,
+    );
+    apply_deadzone_for_stick(
+        &mut controller_data.right_stick,
+        &shape_right,
+        &preset.items.stick_curve_right,
+    );
 
-    apply_deadzone_for_stick(&mut controller_data.left_stick, deadzone_left);
-    apply_deadzone_for_stick(&mut controller_data.right_stick, deadzone_right);
+    // 扳机只有内外死区，不使用响应曲线
+    let (trigger_left, trigger_right) = trigger_deadzone_shapes(&preset.items);
+    let linear = ResponseCurve::Linear;
+    controller_data.left_trigger.value = trigger_left.apply(controller_data.left_trigger.value, &linear);
+    controller_data.right_trigger.value =
+        trigger_right.apply(controller_data.right_trigger.value, &linear);
 }
 
 pub fn check_sticks_rotation(controller_data: &mut ControllerDatas) {
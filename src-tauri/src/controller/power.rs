@@ -0,0 +1,154 @@
+//! 手柄电量与连接方式
+//!
+//! 各后端在轮询时上报电量状态：
+//! - PlayStation / Switch Pro：从 HID 输入报告中解析（见 `ps4`、`switch_pro` 模块）
+//! - Xbox（Windows）：XInput 电池信息
+//! - 其他设备：gilrs `power_info()`
+//!
+//! XInput 与 gilrs 查询有系统调用开销，按 [`QUERY_INTERVAL`] 节流。
+//! 状态变化时向前端发送 `update_controller_power`，
+//! 电量从阈值之上降到 [`LOW_BATTERY_LEVEL`] 及以下时发送 `controller_battery_low` 警告。
+
+use crate::controller::controller::{emit_event, CURRENT_DEVICE};
+use crate::controller::slots::{self, PerSlot};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// 低电量警告阈值 (%)
+pub const LOW_BATTERY_LEVEL: u8 = 20;
+
+/// XInput / gilrs 电量查询间隔
+const QUERY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 连接方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionType {
+    /// USB 有线
+    Wired,
+    /// 蓝牙
+    Bluetooth,
+    /// 其他无线（如 Xbox 无线适配器）
+    Wireless,
+    Unknown,
+}
+
+/// 手柄电量状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PowerState {
+    /// 电量百分比，None 表示未知（如纯有线供电的手柄）
+    pub level: Option<u8>,
+    /// 是否正在充电
+    pub charging: bool,
+    pub connection: ConnectionType,
+}
+
+/// 发送给前端的电量事件
+#[derive(Debug, Clone, Serialize)]
+struct PowerEvent {
+    slot: usize,
+    name: String,
+    /// None 表示设备已断开
+    power: Option<PowerState>,
+}
+
+/// 各槽位最近一次上报的电量状态
+static POWER_STATE: Lazy<PerSlot<RwLock<Option<PowerState>>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(None)));
+
+/// 当前槽位是否已发出低电量警告（充电或电量回升后重置）
+static LOW_BATTERY_WARNED: Lazy<PerSlot<RwLock<bool>>> = Lazy::new(|| PerSlot::new(|| RwLock::new(false)));
+
+/// XInput / gilrs 上次查询时间
+static LAST_QUERY: Lazy<PerSlot<Mutex<Option<Instant>>>> = Lazy::new(|| PerSlot::new(|| Mutex::new(None)));
+
+fn emit(event: &str, power: Option<PowerState>) {
+    let payload = PowerEvent {
+        slot: slots::active_slot(),
+        name: CURRENT_DEVICE.read().unwrap().name.clone(),
+        power,
+    };
+    emit_event(event, payload);
+}
+
+/// 当前槽位的电量状态
+pub fn current() -> Option<PowerState> {
+    *POWER_STATE.read().unwrap()
+}
+
+/// 是否到了下一次查询电量的时间（用于 XInput / gilrs 节流）
+pub fn query_due() -> bool {
+    let mut last = LAST_QUERY.lock().unwrap();
+    if last.is_some_and(|t| t.elapsed() < QUERY_INTERVAL) {
+        return false;
+    }
+    *last = Some(Instant::now());
+    true
+}
+
+/// 上报当前槽位的电量状态，变化时通知前端
+pub fn update(state: PowerState) {
+    {
+        let mut current = POWER_STATE.write().unwrap();
+        if *current == Some(state) {
+            return;
+        }
+        *current = Some(state);
+    }
+    log::debug!("[槽位 {}] 电量状态: {state:?}", slots::active_slot());
+    emit("update_controller_power", Some(state));
+
+    let mut warned = LOW_BATTERY_WARNED.write().unwrap();
+    match state.level {
+        Some(level) if level <= LOW_BATTERY_LEVEL && !state.charging => {
+            if !*warned {
+                *warned = true;
+                log::warn!("🔋 [槽位 {}] 手柄电量低: {level}%", slots::active_slot());
+                emit("controller_battery_low", Some(state));
+            }
+        }
+        _ => *warned = false,
+    }
+}
+
+/// 清除当前槽位的电量状态（设备断开时调用）
+pub fn clear() {
+    *LAST_QUERY.lock().unwrap() = None;
+    *LOW_BATTERY_WARNED.write().unwrap() = false;
+    if POWER_STATE.write().unwrap().take().is_some() {
+        emit("update_controller_power", None);
+    }
+}
+
+/// 从 gilrs 查询电量（按间隔节流）
+pub fn update_from_gilrs(gamepad: &gilrs::Gamepad) {
+    if !query_due() {
+        return;
+    }
+    let state = match gamepad.power_info() {
+        gilrs::PowerInfo::Unknown => PowerState { level: None, charging: false, connection: ConnectionType::Unknown },
+        gilrs::PowerInfo::Wired => PowerState { level: None, charging: false, connection: ConnectionType::Wired },
+        gilrs::PowerInfo::Discharging(level) => PowerState {
+            level: Some(level.min(100)),
+            charging: false,
+            connection: ConnectionType::Wireless,
+        },
+        // 充电中通常意味着插着线
+        gilrs::PowerInfo::Charging(level) => PowerState {
+            level: Some(level.min(100)),
+            charging: true,
+            connection: ConnectionType::Wired,
+        },
+        gilrs::PowerInfo::Charged => PowerState { level: Some(100), charging: false, connection: ConnectionType::Wired },
+    };
+    update(state);
+}
+
+/// 查询指定槽位（缺省为主槽位）的电量状态
+#[tauri::command]
+pub fn get_controller_power(slot: Option<usize>) -> Result<Option<PowerState>, String> {
+    let slot = slots::resolve_slot(slot)?;
+    Ok(slots::with_slot(slot, current))
+}
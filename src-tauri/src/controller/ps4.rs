@@ -1,47 +1,897 @@
-use std::sync::RwLock;
+//! DualShock 4 / DualSense 原生 HID 驱动
+//!
+//! 通过 hidapi 直接读取输入报告，不依赖 gilrs 的 SDL 映射，可拿到
+//! gilrs 拿不到的触摸板、PS / 麦克风静音键与六轴体感数据。
+//!
+//! 报告解析全部是纯函数（`parse_ds4_report` / `parse_dualsense_report`），
+//! 输入原始报告字节即可得到 `ControllerDatas`，方便用抓包数据直接验证。
+//!
+//! 支持的报告：
+//! - DS4 USB `0x01`（64 字节）、DS4 蓝牙 `0x11`（78 字节）
+//! - DualSense USB `0x01`（64 字节）、DualSense 蓝牙 `0x31`（78 字节）
+//! - 蓝牙未切换到完整模式时的精简 `0x01` 报告（仅摇杆/按键/扳机）
+//!
+//! 完整报告同时带有电量与充电状态，解析后交给 `power` 模块上报。
+//!
+//! 输出报告（震动 / 灯条 / 玩家指示灯）：
+//! - DS4 USB `0x05`（32 字节）、DS4 蓝牙 `0x11`（78 字节，带 CRC32）
+//! - DualSense USB `0x02`（48 字节）、DualSense 蓝牙 `0x31`（78 字节，带 CRC32）
 
-use ds4_rs;
-use dualsense_rs::{DualSense};
-use hidapi::{HidApi};
+use crate::controller::backend::InputBackend;
+use crate::controller::controller::{ControllerType, DeviceInfo};
+use crate::controller::datas::{
+    ControllerButtons, ControllerDatas, ControllerImu, ControllerTouchpad, TouchPoint,
+};
+use crate::controller::power::{self, ConnectionType, PowerState};
+use crate::controller::scripted;
+use crate::controller::slots::{self, PerSlot, MAX_SLOTS};
+use hidapi::{BusType, HidApi, HidDevice};
 use once_cell::sync::Lazy;
-use crate::controller::controller::DeviceInfo;
-
-static DUALSENSE_INSTANCE: Lazy<RwLock<Option<DualSense>>> = Lazy::new(|| RwLock::new(
-    None
-));
-
-pub fn poll_ps4_controller(device: &DeviceInfo) {
-    // let vid = &device.vendor_id;
-    // let pid = device.product_id.as_deref().unwrap();
-    // let serial = device.serial_number.as_deref().unwrap();
-    let path = device.device_path.as_deref().unwrap();
-    let ds = DUALSENSE_INSTANCE.write().unwrap();
-    if ds.is_none() {
-        let mut controller = DualSense::new_path(path);
-        controller.on_dpad_changed(Box::new(|dpad| {
-            println!("dpad: {:#?}", dpad);
-        }));
-        let handle = controller.run();
-        *DUALSENSE_INSTANCE.write().unwrap() = Some(controller);
-        // handle.join().ok();
+use std::ffi::CString;
+use std::sync::Mutex;
+
+/// 本驱动负责的全部按键（不含 LT/RT，扳机按键状态由映射模块根据阈值计算）
+const PS_BUTTONS: [ControllerButtons; 19] = [
+    ControllerButtons::South,
+    ControllerButtons::East,
+    ControllerButtons::West,
+    ControllerButtons::North,
+    ControllerButtons::LB,
+    ControllerButtons::RB,
+    ControllerButtons::LStick,
+    ControllerButtons::RStick,
+    ControllerButtons::Back,
+    ControllerButtons::Start,
+    ControllerButtons::Guide,
+    ControllerButtons::Left,
+    ControllerButtons::Right,
+    ControllerButtons::Up,
+    ControllerButtons::Down,
+    ControllerButtons::Touchpad,
+    ControllerButtons::Mute,
+    ControllerButtons::TouchpadLeft,
+    ControllerButtons::TouchpadRight,
+];
+
+/// 陀螺仪原始值每 deg/s 对应的计数（BMI055 ±2000 dps 量程）
+const GYRO_LSB_PER_DEG_S: f32 = 16.0;
+/// 加速度计原始值每 g 对应的计数
+const ACCEL_LSB_PER_G: f32 = 8192.0;
+
+const DS4_TOUCHPAD_WIDTH: f32 = 1920.0;
+const DS4_TOUCHPAD_HEIGHT: f32 = 942.0;
+const DUALSENSE_TOUCHPAD_WIDTH: f32 = 1920.0;
+const DUALSENSE_TOUCHPAD_HEIGHT: f32 = 1080.0;
+
+/// PlayStation 手柄型号
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PsModel {
+    DualShock4,
+    DualSense,
+}
+
+impl PsModel {
+    /// 根据 Sony 的 PID 判断型号
+    pub fn from_product_id(pid: &str) -> Option<Self> {
+        match pid.to_ascii_lowercase().as_str() {
+            "05c4" | "09cc" | "0ba0" => Some(PsModel::DualShock4), // DS4 v1 / v2 / 无线适配器
+            "0ce6" | "0df2" => Some(PsModel::DualSense),           // DualSense / DualSense Edge
+            _ => None,
+        }
+    }
+}
+
+/// 报告来源的连接方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PsConnection {
+    Usb,
+    Bluetooth,
+}
+
+impl PsConnection {
+    fn connection_type(self) -> ConnectionType {
+        match self {
+            PsConnection::Usb => ConnectionType::Wired,
+            PsConnection::Bluetooth => ConnectionType::Bluetooth,
+        }
+    }
+}
+
+/// 一帧解析结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PsReport {
+    pub datas: ControllerDatas,
+    pub connection: PsConnection,
+    /// 电量状态（精简报告不含电量）
+    pub power: Option<PowerState>,
+}
+
+// ---------------------- 报告解析 ----------------------
+
+fn stick_axis(raw: u8) -> f32 {
+    ((raw as f32 - 127.5) / 127.5).clamp(-1.0, 1.0)
+}
+
+fn trigger_axis(raw: u8) -> f32 {
+    raw as f32 / 255.0
+}
+
+fn read_i16(report: &[u8], offset: usize) -> i16 {
+    i16::from_le_bytes([report[offset], report[offset + 1]])
+}
+
+/// 摇杆：原始值 0..255，Y 轴向下为正，这里翻转为向上为正
+fn parse_sticks(datas: &mut ControllerDatas, data: &[u8]) {
+    datas.left_stick.x = stick_axis(data[0]);
+    datas.left_stick.y = -stick_axis(data[1]);
+    datas.right_stick.x = stick_axis(data[2]);
+    datas.right_stick.y = -stick_axis(data[3]);
+}
+
+/// 十字键：低 4 位为 hat 方向，0=上 顺时针递增，8=松开
+fn parse_dpad(datas: &mut ControllerDatas, hat: u8) {
+    let (up, right, down, left) = match hat & 0x0F {
+        0 => (true, false, false, false),
+        1 => (true, true, false, false),
+        2 => (false, true, false, false),
+        3 => (false, true, true, false),
+        4 => (false, false, true, false),
+        5 => (false, false, true, true),
+        6 => (false, false, false, true),
+        7 => (true, false, false, true),
+        _ => (false, false, false, false),
+    };
+    datas.set_button(ControllerButtons::Up, up);
+    datas.set_button(ControllerButtons::Right, right);
+    datas.set_button(ControllerButtons::Down, down);
+    datas.set_button(ControllerButtons::Left, left);
+}
+
+/// 三个按键字节，DS4 与 DualSense 布局一致（DualSense 多了 bit2 静音键）
+fn parse_buttons(datas: &mut ControllerDatas, b0: u8, b1: u8, b2: u8) {
+    parse_dpad(datas, b0);
+    datas.set_button(ControllerButtons::West, b0 & 0x10 != 0); // Square
+    datas.set_button(ControllerButtons::South, b0 & 0x20 != 0); // Cross
+    datas.set_button(ControllerButtons::East, b0 & 0x40 != 0); // Circle
+    datas.set_button(ControllerButtons::North, b0 & 0x80 != 0); // Triangle
+
+    datas.set_button(ControllerButtons::LB, b1 & 0x01 != 0);
+    datas.set_button(ControllerButtons::RB, b1 & 0x02 != 0);
+    datas.set_button(ControllerButtons::Back, b1 & 0x10 != 0); // Share / Create
+    datas.set_button(ControllerButtons::Start, b1 & 0x20 != 0); // Options
+    datas.set_button(ControllerButtons::LStick, b1 & 0x40 != 0);
+    datas.set_button(ControllerButtons::RStick, b1 & 0x80 != 0);
+    datas.left_stick.is_pressed = b1 & 0x40 != 0;
+    datas.right_stick.is_pressed = b1 & 0x80 != 0;
+
+    datas.set_button(ControllerButtons::Guide, b2 & 0x01 != 0); // PS
+    datas.set_button(ControllerButtons::Touchpad, b2 & 0x02 != 0);
+}
+
+fn parse_triggers(datas: &mut ControllerDatas, l2: u8, r2: u8) {
+    datas.left_trigger.value = trigger_axis(l2);
+    datas.right_trigger.value = trigger_axis(r2);
+    datas.left_trigger.has_pressure = true;
+    datas.right_trigger.has_pressure = true;
+}
+
+/// 六轴：陀螺仪 3 x i16 后紧跟加速度计 3 x i16
+fn parse_imu(data: &[u8], offset: usize) -> ControllerImu {
+    let mut imu = ControllerImu {
+        has_imu: true,
+        ..Default::default()
+    };
+    for i in 0..3 {
+        imu.gyro[i] = read_i16(data, offset + i * 2) as f32 / GYRO_LSB_PER_DEG_S;
+        imu.accel[i] = read_i16(data, offset + 6 + i * 2) as f32 / ACCEL_LSB_PER_G;
+    }
+    imu
+}
+
+/// 单个触摸点 4 字节：bit7=未触摸、低 7 位为触摸 ID，随后 12 位 X + 12 位 Y
+fn parse_touch_point(data: &[u8], width: f32, height: f32) -> TouchPoint {
+    let x = data[1] as u16 | ((data[2] as u16 & 0x0F) << 8);
+    let y = (data[2] as u16 >> 4) | ((data[3] as u16) << 4);
+    TouchPoint {
+        active: data[0] & 0x80 == 0,
+        id: data[0] & 0x7F,
+        x: (x as f32 / width).clamp(0.0, 1.0),
+        y: (y as f32 / height).clamp(0.0, 1.0),
+    }
+}
+
+fn parse_touchpad(data: &[u8], offset: usize, width: f32, height: f32) -> ControllerTouchpad {
+    ControllerTouchpad {
+        points: [
+            parse_touch_point(&data[offset..offset + 4], width, height),
+            parse_touch_point(&data[offset + 4..offset + 8], width, height),
+        ],
+        has_touchpad: true,
+    }
+}
+
+fn new_datas() -> ControllerDatas {
+    let mut datas = ControllerDatas::new();
+    datas.limits.set_limits(0.0, 255.0, 0.0, 255.0);
+    datas
+}
+
+/// 蓝牙未进入完整模式时的精简报告（DS4 与 DualSense 格式相同）
+/// data: LX LY RX RY B0 B1 B2 L2 R2
+fn parse_basic_report(data: &[u8]) -> Option<ControllerDatas> {
+    if data.len() < 9 {
+        return None;
+    }
+    let mut datas = new_datas();
+    parse_sticks(&mut datas, data);
+    parse_buttons(&mut datas, data[4], data[5], data[6]);
+    parse_triggers(&mut datas, data[7], data[8]);
+    Some(datas)
+}
+
+/// DS4 完整状态包（去掉报告头之后）
+/// 0-3 摇杆, 4-6 按键, 7-8 扳机, 12-23 六轴, 33.. 触摸点
+fn parse_ds4_state(data: &[u8]) -> Option<ControllerDatas> {
+    if data.len() < 42 {
+        return None;
+    }
+    let mut datas = new_datas();
+    parse_sticks(&mut datas, data);
+    parse_buttons(&mut datas, data[4], data[5], data[6]);
+    parse_triggers(&mut datas, data[7], data[8]);
+    datas.imu = parse_imu(data, 12);
+    datas.touchpad = parse_touchpad(data, 34, DS4_TOUCHPAD_WIDTH, DS4_TOUCHPAD_HEIGHT);
+    datas.update_touchpad_regions();
+    Some(datas)
+}
+
+/// DualSense 完整状态包（去掉报告头之后）
+/// 0-3 摇杆, 4-5 扳机, 7-9 按键, 15-26 六轴, 32.. 触摸点
+fn parse_dualsense_state(data: &[u8]) -> Option<ControllerDatas> {
+    if data.len() < 40 {
+        return None;
+    }
+    let mut datas = new_datas();
+    parse_sticks(&mut datas, data);
+    parse_triggers(&mut datas, data[4], data[5]);
+    parse_buttons(&mut datas, data[7], data[8], data[9]);
+    datas.set_button(ControllerButtons::Mute, data[9] & 0x04 != 0);
+    datas.imu = parse_imu(data, 15);
+    datas.touchpad = parse_touchpad(data, 32, DUALSENSE_TOUCHPAD_WIDTH, DUALSENSE_TOUCHPAD_HEIGHT);
+    datas.update_touchpad_regions();
+    Some(datas)
+}
+
+/// DS4 电量（完整状态包第 29 字节）：低 4 位电量档位，bit4 插线
+/// 插线时 0-9 为充电中、10/11 为已充满；未插线时 0-10 对应 0-100%
+fn parse_ds4_power(data: &[u8], connection: PsConnection) -> PowerState {
+    let status = data[29];
+    let capacity = status & 0x0F;
+    let cable = status & 0x10 != 0;
+    let (level, charging) = match (cable, capacity) {
+        (true, 0..=9) => (Some(capacity * 10 + 5), true),
+        (true, 10 | 11) => (Some(100), false),
+        (true, _) => (None, false),
+        (false, _) => (Some((capacity * 10 + 5).min(100)), false),
+    };
+    PowerState { level, charging, connection: connection.connection_type() }
+}
+
+/// DualSense 电量（完整状态包第 52 字节）：低 4 位电量档位，高 4 位充电状态
+/// 0 放电，1 充电中，2 已充满，其他为温度/电压异常
+fn parse_dualsense_power(data: &[u8], connection: PsConnection) -> Option<PowerState> {
+    let status = *data.get(52)?;
+    let capacity = ((status & 0x0F) * 10 + 5).min(100);
+    let (level, charging) = match status >> 4 {
+        0x0 => (Some(capacity), false),
+        0x1 => (Some(capacity), true),
+        0x2 => (Some(100), false),
+        _ => (None, false),
+    };
+    Some(PowerState { level, charging, connection: connection.connection_type() })
+}
+
+/// 解析 DS4 输入报告（含报告 ID）
+pub fn parse_ds4_report(report: &[u8]) -> Option<PsReport> {
+    match (report.first()?, report.len()) {
+        (0x01, len) if len >= 64 => Some(PsReport {
+            datas: parse_ds4_state(&report[1..])?,
+            connection: PsConnection::Usb,
+            power: Some(parse_ds4_power(&report[1..], PsConnection::Usb)),
+        }),
+        (0x01, _) => Some(PsReport {
+            datas: parse_basic_report(&report[1..])?,
+            connection: PsConnection::Bluetooth,
+            power: None,
+        }),
+        // 蓝牙报告在报告 ID 后还有 2 字节的轮询/CRC 标志
+        (0x11, len) if len >= 3 => Some(PsReport {
+            datas: parse_ds4_state(&report[3..])?,
+            connection: PsConnection::Bluetooth,
+            power: Some(parse_ds4_power(&report[3..], PsConnection::Bluetooth)),
+        }),
+        _ => None,
+    }
+}
+
+/// 解析 DualSense 输入报告（含报告 ID）
+pub fn parse_dualsense_report(report: &[u8]) -> Option<PsReport> {
+    match (report.first()?, report.len()) {
+        (0x01, len) if len >= 64 => Some(PsReport {
+            datas: parse_dualsense_state(&report[1..])?,
+            connection: PsConnection::Usb,
+            power: parse_dualsense_power(&report[1..], PsConnection::Usb),
+        }),
+        (0x01, _) => Some(PsReport {
+            datas: parse_basic_report(&report[1..])?,
+            connection: PsConnection::Bluetooth,
+            power: None,
+        }),
+        // 蓝牙报告在报告 ID 后还有 1 字节序号
+        (0x31, len) if len >= 2 => Some(PsReport {
+            datas: parse_dualsense_state(&report[2..])?,
+            connection: PsConnection::Bluetooth,
+            power: parse_dualsense_power(&report[2..], PsConnection::Bluetooth),
+        }),
+        _ => None,
+    }
+}
+
+/// 按型号分发解析
+pub fn parse_report(model: PsModel, report: &[u8]) -> Option<PsReport> {
+    match model {
+        PsModel::DualShock4 => parse_ds4_report(report),
+        PsModel::DualSense => parse_dualsense_report(report),
+    }
+}
+
+// ---------------------- 输出报告 ----------------------
+
+/// 蓝牙输出报告长度（DS4 与 DualSense 相同）
+const BT_OUTPUT_REPORT_LEN: usize = 78;
+
+/// DualSense 玩家指示灯图案（5 颗灯，居中对称点亮），下标为玩家编号 - 1
+const DUALSENSE_PLAYER_LEDS: [u8; 4] = [0x04, 0x0A, 0x15, 0x1B];
+
+/// 手柄输出状态（马达强度 0-255）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PsOutputState {
+    /// 左侧低频（强）马达
+    pub strong_motor: u8,
+    /// 右侧高频（弱）马达
+    pub weak_motor: u8,
+    /// 灯条颜色 (R, G, B)，None 时不修改手柄当前颜色
+    pub lightbar: Option<(u8, u8, u8)>,
+    /// 玩家指示灯位图（仅 DualSense），None 时不修改
+    pub player_leds: Option<u8>,
+}
+
+/// 蓝牙输出报告使用的 CRC32（IEEE），校验数据前需要拼上 0xA2 头
+fn bt_crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in std::iter::once(&0xA2u8).chain(data) {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// 在蓝牙报告末尾 4 字节写入 CRC32
+fn append_bt_crc(report: &mut [u8]) {
+    let len = report.len();
+    let crc = bt_crc32(&report[..len - 4]);
+    report[len - 4..].copy_from_slice(&crc.to_le_bytes());
+}
+
+/// DS4 输出报告
+fn build_ds4_output_report(bluetooth: bool, state: &PsOutputState) -> Vec<u8> {
+    // 公共部分：flags(0x01 马达, 0x02 灯条) + 2 字节保留 + 弱马达 + 强马达 + RGB
+    let (mut report, offset) = if bluetooth {
+        let mut report = vec![0u8; BT_OUTPUT_REPORT_LEN];
+        report[0] = 0x11;
+        report[1] = 0xC0; // 启用 HID + CRC
+        (report, 3)
+    } else {
+        let mut report = vec![0u8; 32];
+        report[0] = 0x05;
+        (report, 1)
+    };
+    report[offset] = 0x01;
+    report[offset + 3] = state.weak_motor;
+    report[offset + 4] = state.strong_motor;
+    if let Some((r, g, b)) = state.lightbar {
+        report[offset] |= 0x02;
+        report[offset + 5] = r;
+        report[offset + 6] = g;
+        report[offset + 7] = b;
+    }
+
+    if bluetooth {
+        append_bt_crc(&mut report);
+    }
+    report
+}
+
+/// DualSense 输出报告
+fn build_dualsense_output_report(bluetooth: bool, seq: u8, state: &PsOutputState) -> Vec<u8> {
+    let (mut report, offset) = if bluetooth {
+        let mut report = vec![0u8; BT_OUTPUT_REPORT_LEN];
+        report[0] = 0x31;
+        report[1] = (seq & 0x0F) << 4;
+        report[2] = 0x10; // DS_OUTPUT_TAG
+        (report, 3)
     } else {
-        // 已有实例，进行状态更新等操作
-        // let controller = ds.as_ref().unwrap();
-        // controller.update_state();
+        let mut report = vec![0u8; 48];
+        report[0] = 0x02;
+        (report, 1)
+    };
+    // valid_flag0: bit0 兼容震动，bit1 允许修改震动
+    report[offset] = 0x03;
+    report[offset + 2] = state.weak_motor;
+    report[offset + 3] = state.strong_motor;
+    // valid_flag1: bit2 灯条颜色，bit4 玩家指示灯
+    if let Some((r, g, b)) = state.lightbar {
+        report[offset + 1] |= 0x04;
+        // valid_flag2 bit1 + lightbar_setup 0x02：让固件释放灯条（否则蓝牙配对后的蓝色呼吸灯会覆盖颜色）
+        report[offset + 38] = 0x02;
+        report[offset + 41] = 0x02;
+        report[offset + 44] = r;
+        report[offset + 45] = g;
+        report[offset + 46] = b;
+    }
+    if let Some(leds) = state.player_leds {
+        report[offset + 1] |= 0x10;
+        report[offset + 43] = leds;
+    }
+
+    if bluetooth {
+        append_bt_crc(&mut report);
     }
+    report
 }
 
+/// 构造输出报告（含报告 ID）
+pub fn build_output_report(model: PsModel, bluetooth: bool, seq: u8, state: &PsOutputState) -> Vec<u8> {
+    match model {
+        PsModel::DualShock4 => build_ds4_output_report(bluetooth, state),
+        PsModel::DualSense => build_dualsense_output_report(bluetooth, seq, state),
+    }
+}
 
-pub fn initialize() {
-    println!("initialize ps4 controller");
+// ---------------------- 设备读写 ----------------------
+
+/// 已打开的 PlayStation 手柄
+struct PsController {
+    path: String,
+    model: PsModel,
+    device: HidDevice,
+    bluetooth: bool,
+    /// DualSense 蓝牙输出报告序号
+    output_seq: u8,
+    output: PsOutputState,
+    last_report: Option<PsReport>,
+}
+
+impl PsController {
+    fn write_output(&mut self) -> bool {
+        let report = build_output_report(self.model, self.bluetooth, self.output_seq, &self.output);
+        self.output_seq = self.output_seq.wrapping_add(1) & 0x0F;
+        match self.device.write(&report) {
+            Ok(_) => true,
+            Err(e) => {
+                log::warn!("PlayStation 手柄写入输出报告失败 ({}): {e}", self.path);
+                false
+            }
+        }
+    }
+}
 
-    // let mut controller = DualSense::new_serial(vendor_id, product_id, serial);
+/// 当前打开的 PlayStation 手柄（由 listen 线程独占使用）
+static PS_CONTROLLER: Lazy<PerSlot<Mutex<Option<PsController>>>> =
+    Lazy::new(|| PerSlot::new(|| Mutex::new(None)));
 
+/// 其他槽位是否已打开同一 HID 路径，避免两个槽位驱动同一个手柄
+fn claimed_by_other_slot(path: &str) -> bool {
+    let active_slot = slots::active_slot();
+    (0..MAX_SLOTS)
+        .filter(|slot| *slot != active_slot)
+        .any(|slot| PS_CONTROLLER.slot(slot).lock().unwrap().as_ref().is_some_and(|c| c.path == path))
+}
+
+fn open_controller(device: &DeviceInfo) -> Option<PsController> {
+    let path = device.device_path.as_deref()?;
+    // wgi:xxxx 为 hidapi 不可见设备的占位路径，无法打开
+    if path.starts_with("wgi:") {
+        return None;
+    }
+    if claimed_by_other_slot(path) {
+        log::debug!("PlayStation 手柄已被其他槽位打开，跳过 ({path})");
+        return None;
+    }
+    let model = device
+        .sub_product_id
+        .as_deref()
+        .and_then(PsModel::from_product_id)
+        .or_else(|| device.product_id.as_deref().and_then(PsModel::from_product_id))?;
+
+    let api = match HidApi::new() {
+        Ok(api) => api,
+        Err(e) => {
+            log::error!("初始化 hidapi 失败: {e}");
+            return None;
+        }
+    };
+    let c_path = CString::new(path).ok()?;
+    let hid_device = match api.open_path(&c_path) {
+        Ok(d) => d,
+        Err(e) => {
+            log::warn!("打开 PlayStation 手柄失败 ({path}): {e}");
+            return None;
+        }
+    };
+    if let Err(e) = hid_device.set_blocking_mode(false) {
+        log::error!("设置非阻塞模式失败 ({path}): {e}");
+        return None;
+    }
+    let bluetooth = hid_device
+        .get_device_info()
+        .is_ok_and(|info| info.bus_type() == BusType::Bluetooth);
+
+    // 读取校准特性报告会让蓝牙连接切换到完整报告模式（DS4: 0x02，DualSense: 0x05）
+    let mut feature = [0u8; 64];
+    feature[0] = match model {
+        PsModel::DualShock4 => 0x02,
+        PsModel::DualSense => 0x05,
+    };
+    if let Err(e) = hid_device.get_feature_report(&mut feature) {
+        log::debug!("读取校准特性报告失败（USB 连接可忽略）: {e}");
+    }
+
+    log::info!(
+        "✅ 已打开 PlayStation 手柄: {} ({model:?}, {})",
+        device.name,
+        if bluetooth { "蓝牙" } else { "USB" }
+    );
+    Some(PsController {
+        path: path.to_string(),
+        model,
+        device: hid_device,
+        bluetooth,
+        output_seq: 0,
+        output: PsOutputState::default(),
+        last_report: None,
+    })
+}
 
+/// 关闭当前 PlayStation 手柄
+pub fn close() {
+    if let Some(mut controller) = PS_CONTROLLER.lock().unwrap().take() {
+        // 关闭前停止震动，避免马达停在最后的状态
+        if controller.output.strong_motor != 0 || controller.output.weak_motor != 0 {
+            controller.output.strong_motor = 0;
+            controller.output.weak_motor = 0;
+            controller.write_output();
+        }
+        log::info!("PlayStation 手柄已关闭: {}", controller.path);
+    }
+}
+
+/// 设置当前槽位 PlayStation 手柄的马达强度（0.0-1.0）
+/// 返回 false 表示手柄未通过 HID 打开（调用方应回退 gilrs）
+pub fn set_rumble(strong: f32, weak: f32) -> bool {
+    let mut guard = PS_CONTROLLER.lock().unwrap();
+    let Some(controller) = guard.as_mut() else {
+        return false;
+    };
+    controller.output.strong_motor = (strong.clamp(0.0, 1.0) * 255.0).round() as u8;
+    controller.output.weak_motor = (weak.clamp(0.0, 1.0) * 255.0).round() as u8;
+    controller.write_output()
+}
+
+/// 设置当前槽位 PlayStation 手柄的灯条颜色与玩家指示灯（编号 1-4，仅 DualSense 有指示灯）
+/// 与上次写入的状态相同时不重复发送；返回 false 表示手柄未通过 HID 打开
+pub fn set_led(color: (u8, u8, u8), player: u8) -> bool {
+    let mut guard = PS_CONTROLLER.lock().unwrap();
+    let Some(controller) = guard.as_mut() else {
+        return false;
+    };
+    let player_leds = match controller.model {
+        PsModel::DualShock4 => None,
+        PsModel::DualSense => Some(DUALSENSE_PLAYER_LEDS[(player.clamp(1, 4) - 1) as usize]),
+    };
+    if controller.output.lightbar == Some(color) && controller.output.player_leds == player_leds {
+        return true;
+    }
+    controller.output.lightbar = Some(color);
+    controller.output.player_leds = player_leds;
+    controller.write_output()
+}
+
+/// 确保当前槽位已打开该设备（设备路径变化时重新打开）
+fn ensure_open(slot: &mut Option<PsController>, device: &DeviceInfo) {
+    if slot
+        .as_ref()
+        .is_some_and(|c| Some(c.path.as_str()) != device.device_path.as_deref())
+    {
+        *slot = None;
+    }
+    if slot.is_none() {
+        *slot = open_controller(device);
+    }
+}
+
+/// PlayStation 手柄轮询入口，输入写入 `controller_data`
+/// 返回 true 表示成功读取，false 表示 HID 不可用（调用方应回退下一个后端）
+pub fn poll_ps4_controller(device: &DeviceInfo, controller_data: &mut ControllerDatas) -> bool {
+    let mut guard = PS_CONTROLLER.lock().unwrap();
+    ensure_open(&mut guard, device);
+    let Some(controller) = guard.as_mut() else {
+        return false;
+    };
+
+    // 读空缓冲区，只保留最新一帧
+    let mut buf = [0u8; 128];
+    loop {
+        match controller.device.read_timeout(&mut buf, 0) {
+            Ok(0) => break,
+            Ok(len) => {
+                if let Some(report) = parse_report(controller.model, &buf[..len]) {
+                    controller.last_report = Some(report);
+                }
+            }
+            Err(e) => {
+                log::warn!("PlayStation 手柄读取失败，关闭设备 ({}): {e}", controller.path);
+                *guard = None;
+                return false;
+            }
+        }
+    }
+
+    let Some(report) = controller.last_report else {
+        // 尚未收到任何报告，保持上一帧数据
+        return true;
+    };
+    drop(guard);
+
+    if let Some(state) = report.power {
+        power::update(state);
+    }
+
+    let datas = report.datas;
+    for button in PS_BUTTONS {
+        controller_data.set_button(button, datas.get_button(button));
+    }
+    controller_data.left_stick = datas.left_stick;
+    controller_data.right_stick = datas.right_stick;
+    controller_data.left_trigger.value = datas.left_trigger.value;
+    controller_data.left_trigger.has_pressure = datas.left_trigger.has_pressure;
+    controller_data.right_trigger.value = datas.right_trigger.value;
+    controller_data.right_trigger.has_pressure = datas.right_trigger.has_pressure;
+    controller_data.limits = datas.limits;
+    controller_data.imu = datas.imu;
+    controller_data.touchpad = datas.touchpad;
+    true
+}
 
-    // controller.on_dpad_changed(Box::new(|dpad| {
-    //     println!("dpad: {:#?}", dpad);
-    // }));
-    // let handle = controller.run();
-    // handle.join().ok();
+/// DS4 / DualSense hidapi 输入后端
+pub struct PsHidBackend;
+
+impl InputBackend for PsHidBackend {
+    fn name(&self) -> &'static str {
+        "PlayStation HID"
+    }
+
+    fn supports(&self, device: &DeviceInfo) -> bool {
+        device.controller_type == ControllerType::PlayStation && !scripted::is_scripted(device)
+    }
+
+    fn open(&self, device: &DeviceInfo) -> bool {
+        let mut guard = PS_CONTROLLER.lock().unwrap();
+        ensure_open(&mut guard, device);
+        guard.is_some()
+    }
+
+    fn poll(&self, device: &DeviceInfo, datas: &mut ControllerDatas) -> bool {
+        poll_ps4_controller(device, datas)
+    }
+
+    fn close(&self) {
+        close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 按报告格式手工构造的输入报告（非真实抓包），蓝牙报告末尾为按 0xA1 头计算的有效 CRC32：
+    // - DS4：左摇杆推到左下、右摇杆推到右上，L2 按到底；十字键右 + Cross + L1 + Options + PS，
+    //   单指按在触摸板中央
+    // - DualSense：左摇杆推到右上、右摇杆推到左下，R2 按到底；Triangle + R1 + Create + 静音 +
+    //   触摸板按下，单指按在右下角
+
+    /// 64 字节
+    const DS4_USB: &str = "
+        01 00 ff ff 00 22 21 05 ff 00 34 12 1a a0 00 f0
+        ff 00 00 00 00 00 20 00 00 00 00 00 00 00 15 00
+        00 00 01 01 c0 73 1d 80 00 00 00 00 00 00 00 00
+        00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+    ";
+    /// 78 字节
+    const DS4_BT: &str = "
+        11 c0 00 00 ff ff 00 22 21 05 ff 00 34 12 1a a0
+        00 f0 ff 00 00 00 00 00 20 00 00 00 00 00 00 00
+        08 00 00 00 01 01 c0 73 1d 80 00 00 00 00 00 00
+        00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+        00 00 00 00 00 00 00 00 00 00 72 a6 ff 13
+    ";
+    /// 64 字节
+    const DUALSENSE_USB: &str = "
+        01 ff 00 00 ff 00 ff 2a 88 12 06 00 00 00 00 00
+        00 00 00 00 20 00 00 20 00 00 00 00 00 00 00 00
+        00 05 80 87 43 80 00 00 00 00 00 00 00 00 00 00
+        00 00 00 00 00 14 00 00 00 00 00 00 00 00 00 00
+    ";
+    /// 78 字节
+    const DUALSENSE_BT: &str = "
+        31 10 ff 00 00 ff 00 ff 2a 88 12 06 00 00 00 00
+        00 00 00 00 00 20 00 00 20 00 00 00 00 00 00 00
+        00 00 05 80 87 43 80 00 00 00 00 00 00 00 00 00
+        00 00 00 00 00 00 07 00 00 00 00 00 00 00 00 00
+        00 00 00 00 00 00 00 00 00 00 c6 d7 08 c5
+    ";
+    /// 蓝牙未切换到完整模式时的精简报告，十字键下 + Square，R2 约按下 1/4
+    const DS4_BT_BASIC: &str = "
+        01 80 80 80 80 14 00 00 00 40
+    ";
+
+    fn hex(s: &str) -> Vec<u8> {
+        s.split_whitespace()
+            .map(|b| u8::from_str_radix(b, 16).unwrap())
+            .collect()
+    }
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    fn assert_ds4_state(datas: &ControllerDatas) {
+        assert!(approx(datas.left_stick.x, -1.0) && approx(datas.left_stick.y, -1.0));
+        assert!(approx(datas.right_stick.x, 1.0) && approx(datas.right_stick.y, 1.0));
+        assert!(approx(datas.left_trigger.value, 1.0) && approx(datas.right_trigger.value, 0.0));
+
+        for button in [
+            ControllerButtons::Right,
+            ControllerButtons::South,
+            ControllerButtons::LB,
+            ControllerButtons::Start,
+            ControllerButtons::Guide,
+        ] {
+            assert!(datas.get_button(button), "{button:?} 应为按下");
+        }
+        for button in [
+            ControllerButtons::Up,
+            ControllerButtons::Down,
+            ControllerButtons::Left,
+            ControllerButtons::East,
+            ControllerButtons::Touchpad,
+        ] {
+            assert!(!datas.get_button(button), "{button:?} 应为松开");
+        }
+
+        assert!(datas.imu.has_imu);
+        assert!(approx(datas.imu.gyro[0], 10.0) && approx(datas.imu.gyro[1], -1.0));
+        assert!(approx(datas.imu.accel[1], 1.0));
+
+        let touch = datas.touchpad.points;
+        assert!(touch[0].active && touch[0].id == 1);
+        assert!(approx(touch[0].x, 0.5) && approx(touch[0].y, 0.5));
+        assert!(!touch[1].active);
+    }
+
+    fn assert_dualsense_state(datas: &ControllerDatas) {
+        assert!(approx(datas.left_stick.x, 1.0) && approx(datas.left_stick.y, 1.0));
+        assert!(approx(datas.right_stick.x, -1.0) && approx(datas.right_stick.y, -1.0));
+        assert!(approx(datas.left_trigger.value, 0.0) && approx(datas.right_trigger.value, 1.0));
+
+        for button in [
+            ControllerButtons::North,
+            ControllerButtons::RB,
+            ControllerButtons::Back,
+            ControllerButtons::Mute,
+            ControllerButtons::Touchpad,
+        ] {
+            assert!(datas.get_button(button), "{button:?} 应为按下");
+        }
+        // hat = 8 表示十字键松开
+        for button in [
+            ControllerButtons::Up,
+            ControllerButtons::Right,
+            ControllerButtons::Down,
+            ControllerButtons::Left,
+            ControllerButtons::Guide,
+        ] {
+            assert!(!datas.get_button(button), "{button:?} 应为松开");
+        }
+
+        assert!(approx(datas.imu.gyro[2], 2.0) && approx(datas.imu.accel[0], 1.0));
+
+        let touch = datas.touchpad.points;
+        assert!(touch[0].active && touch[0].id == 5);
+        assert!(approx(touch[0].x, 1.0) && approx(touch[0].y, 1.0));
+        assert!(!touch[1].active);
+        // 手指在右半区按下触摸板
+        assert!(datas.get_button(ControllerButtons::TouchpadRight));
+    }
+
+    #[test]
+    fn ds4_usb_report() {
+        let report = parse_ds4_report(&hex(DS4_USB)).unwrap();
+        assert_eq!(report.connection, PsConnection::Usb);
+        assert_ds4_state(&report.datas);
+        // 插线充电中，电量档位 5
+        assert_eq!(
+            report.power,
+            Some(PowerState { level: Some(55), charging: true, connection: ConnectionType::Wired })
+        );
+    }
+
+    #[test]
+    fn ds4_bluetooth_report() {
+        let report = parse_ds4_report(&hex(DS4_BT)).unwrap();
+        assert_eq!(report.connection, PsConnection::Bluetooth);
+        assert_ds4_state(&report.datas);
+        assert_eq!(
+            report.power,
+            Some(PowerState { level: Some(85), charging: false, connection: ConnectionType::Bluetooth })
+        );
+    }
+
+    #[test]
+    fn dualsense_usb_report() {
+        let report = parse_dualsense_report(&hex(DUALSENSE_USB)).unwrap();
+        assert_eq!(report.connection, PsConnection::Usb);
+        assert_dualsense_state(&report.datas);
+        assert_eq!(
+            report.power,
+            Some(PowerState { level: Some(45), charging: true, connection: ConnectionType::Wired })
+        );
+    }
+
+    #[test]
+    fn dualsense_bluetooth_report() {
+        let report = parse_dualsense_report(&hex(DUALSENSE_BT)).unwrap();
+        assert_eq!(report.connection, PsConnection::Bluetooth);
+        assert_dualsense_state(&report.datas);
+        assert_eq!(
+            report.power,
+            Some(PowerState { level: Some(75), charging: false, connection: ConnectionType::Bluetooth })
+        );
+    }
+
+    #[test]
+    fn basic_bluetooth_report() {
+        for model in [PsModel::DualShock4, PsModel::DualSense] {
+            let report = parse_report(model, &hex(DS4_BT_BASIC)).unwrap();
+            let datas = report.datas;
+            assert_eq!(report.connection, PsConnection::Bluetooth);
+            assert_eq!(report.power, None);
+            assert!(datas.left_stick.x.abs() < 0.01 && datas.right_stick.y.abs() < 0.01);
+            assert!(datas.get_button(ControllerButtons::Down));
+            assert!(datas.get_button(ControllerButtons::West));
+            assert!(approx(datas.right_trigger.value, 64.0 / 255.0));
+            assert!(!datas.imu.has_imu && !datas.touchpad.has_touchpad);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_or_truncated_reports() {
+        assert!(parse_ds4_report(&[]).is_none());
+        assert!(parse_ds4_report(&[0x05, 0, 0, 0]).is_none());
+        assert!(parse_ds4_report(&[0x01, 0x80, 0x80]).is_none());
+        assert!(parse_ds4_report(&hex(DS4_BT)[..20]).is_none());
+        assert!(parse_dualsense_report(&hex(DUALSENSE_BT)[..30]).is_none());
+    }
 }
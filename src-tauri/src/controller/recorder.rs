@@ -0,0 +1,428 @@
+//! 手柄输入录制与回放
+//!
+//! 录制：监听线程每个周期在 `poll_controller` 之后把本槽位的原始 `ControllerDatas`
+//! 连同周期时间戳写入文件；回放：每个监听周期按顺序把一帧原始数据写回 `POLLED_CONTROLLER_DATA`，
+//! 代替 `poll_controller`，后续死区、旋转检测与映射流程完全不变。
+//! 录制与回放都以监听周期为单位，周期抖动不会导致丢帧，死区等处理也总是作用在原始数据上。
+//! 双击、长按等判定依赖周期之间的实际时间，因此文件头记录录制时的轮询频率，
+//! 回放期间切换到该频率，结束后恢复。
+//! 没有手柄也可以回放，用于复现双击、长按、摇杆旋转等与时序相关的问题。
+//!
+//! 文件格式（`.xcrec`，位于配置目录 `recordings/` 下）：
+//! - 6 字节魔数 `XCREC\0` + 1 字节版本号（当前为 2，版本 1 没有记录轮询频率）
+//! - bincode 编码的 [`RecordingHeader`]
+//! - 若干 bincode 编码的 [`RecordedFrame`]，直到文件结束
+
+use crate::controller::controller::{
+    default_devices, emit_event, set_frequency, DeviceInfo, CURRENT_DEVICE, FREQ, POLLED_CONTROLLER_DATA,
+};
+use crate::controller::datas::ControllerDatas;
+use crate::controller::slots::{self, PerSlot};
+use crate::xeno_utils;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+const RECORDINGS_DIR: &str = "recordings";
+const RECORDING_EXT: &str = "xcrec";
+const MAGIC: &[u8; 6] = b"XCREC\0";
+const FORMAT_VERSION: u8 = 2;
+/// 占位设备的路径前缀
+const REPLAY_PATH_PREFIX: &str = "replay:";
+
+/// 录制文件头
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    /// 录制时的设备（回放时用于选择按键布局）
+    pub device: DeviceInfo,
+    /// 录制开始时间（Unix 毫秒）
+    pub created_at_ms: u64,
+    /// 录制时的轮询频率（Hz）
+    pub polling_frequency: u32,
+}
+
+/// 一个监听周期的数据
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// 相对第一帧的时间（微秒），回放按帧顺序进行，该字段仅用于分析录制时的周期间隔
+    pub t_us: u64,
+    pub datas: ControllerDatas,
+}
+
+struct Recorder {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    started: Option<Instant>,
+    frames: usize,
+}
+
+struct Replay {
+    frames: Vec<RecordedFrame>,
+    next: usize,
+    looped: bool,
+    /// 回放开始时槽位没有设备，使用录制的设备信息占位，结束后需要清除
+    placeholder_device: bool,
+    /// 已写入空闲帧，下个周期清理
+    finished: bool,
+    /// 录制时的轮询频率
+    frequency: u32,
+    /// 回放前的轮询频率，与录制时不同时回放期间临时切换，结束后恢复
+    previous_frequency: Option<u32>,
+}
+
+impl Replay {
+    /// 恢复回放前的轮询频率；回放期间频率被用户修改过时保持用户的设置
+    fn restore_frequency(&self) {
+        let Some(previous) = self.previous_frequency else {
+            return;
+        };
+        if *FREQ.read().unwrap() == self.frequency {
+            log::info!("回放结束，恢复轮询频率 {previous} Hz");
+            set_frequency(previous);
+        }
+    }
+}
+
+static RECORDER: Lazy<PerSlot<Mutex<Option<Recorder>>>> = Lazy::new(|| PerSlot::new(|| Mutex::new(None)));
+static REPLAY: Lazy<PerSlot<Mutex<Option<Replay>>>> = Lazy::new(|| PerSlot::new(|| Mutex::new(None)));
+
+fn recordings_dir() -> Result<PathBuf, String> {
+    xeno_utils::ensure_dir(&PathBuf::from(RECORDINGS_DIR)).ok_or_else(|| "无法创建录制目录".to_string())
+}
+
+/// 只允许访问录制目录下的文件
+fn recording_path(name: &str) -> Result<PathBuf, String> {
+    let file_name = PathBuf::from(name);
+    if file_name.components().count() != 1 {
+        return Err(format!("无效的录制文件名: {name}"));
+    }
+    let mut path = recordings_dir()?.join(file_name);
+    if path.extension().is_none() {
+        path.set_extension(RECORDING_EXT);
+    }
+    Ok(path)
+}
+
+fn unix_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 读取录制文件
+pub fn read_recording(path: &PathBuf) -> Result<(RecordingHeader, Vec<RecordedFrame>), String> {
+    let file = File::open(path).map_err(|e| format!("打开录制文件失败: {e}"))?;
+    let mut reader = BufReader::new(file);
+
+    let mut magic = [0u8; 7];
+    reader.read_exact(&mut magic).map_err(|e| format!("读取录制文件头失败: {e}"))?;
+    if &magic[..6] != MAGIC {
+        return Err("不是有效的录制文件".to_string());
+    }
+    if magic[6] != FORMAT_VERSION {
+        return Err(format!("不支持的录制文件版本: {}", magic[6]));
+    }
+    let header: RecordingHeader =
+        bincode::deserialize_from(&mut reader).map_err(|e| format!("解析录制文件头失败: {e}"))?;
+
+    let mut frames = Vec::new();
+    loop {
+        match bincode::deserialize_from::<_, RecordedFrame>(&mut reader) {
+            Ok(frame) => frames.push(frame),
+            Err(e) => {
+                // 正常结束或录制中断导致的末尾残帧
+                if !matches!(*e, bincode::ErrorKind::Io(ref io) if io.kind() == std::io::ErrorKind::UnexpectedEof) {
+                    log::warn!("录制文件 {path:?} 第 {} 帧解析失败，忽略后续数据: {e}", frames.len());
+                }
+                break;
+            }
+        }
+    }
+    Ok((header, frames))
+}
+
+// ---------------------- 监听线程调用 ----------------------
+
+/// 当前槽位是否正在回放
+pub fn is_replaying() -> bool {
+    REPLAY.lock().unwrap().is_some()
+}
+
+/// 录制一帧（当前槽位未在录制时直接返回）
+pub fn record_frame(tick: Instant, datas: &ControllerDatas) {
+    let mut guard = RECORDER.lock().unwrap();
+    let Some(recorder) = guard.as_mut() else {
+        return;
+    };
+    let started = *recorder.started.get_or_insert(tick);
+    let frame = RecordedFrame {
+        t_us: tick.saturating_duration_since(started).as_micros() as u64,
+        datas: *datas,
+    };
+    if let Err(e) = bincode::serialize_into(&mut recorder.writer, &frame) {
+        log::error!("写入录制文件失败，停止录制 ({:?}): {e}", recorder.path);
+        *guard = None;
+        return;
+    }
+    recorder.frames += 1;
+}
+
+/// 只覆盖后端轮询会写入的输入字段
+fn apply_input(datas: &ControllerDatas) {
+    POLLED_CONTROLLER_DATA.write().unwrap().copy_input_from(datas);
+}
+
+/// 回放一帧，代替 `poll_controller`
+///
+/// 每个周期写入下一帧录制数据，与录制时一帧对应一个监听周期。与轮询一样写入未经处理的
+/// `POLLED_CONTROLLER_DATA`，死区与响应曲线不会在上一周期的处理结果上重复叠加。
+/// 播放结束后先写入一帧空闲数据让映射释放按键，下个周期再清除回放状态（以及占位设备）
+pub fn replay_frame() {
+    let mut guard = REPLAY.lock().unwrap();
+    let Some(replay) = guard.as_mut() else {
+        return;
+    };
+
+    if replay.finished {
+        let placeholder = replay.placeholder_device;
+        replay.restore_frequency();
+        *guard = None;
+        drop(guard);
+        // 回放期间用户可能连接了真实设备，只清除仍在使用的占位设备
+        let mut current_device = CURRENT_DEVICE.write().unwrap();
+        if placeholder && current_device.device_path.as_deref().is_some_and(|p| p.starts_with(REPLAY_PATH_PREFIX)) {
+            *current_device = default_devices()[0].clone();
+        }
+        drop(current_device);
+        let slot = slots::active_slot();
+        log::info!("⏹️ [槽位 {slot}] 回放结束");
+        emit_event("replay_finished", slot);
+        return;
+    }
+
+    if replay.next >= replay.frames.len() && replay.looped {
+        replay.next = 0;
+    }
+    let current = match replay.frames.get(replay.next) {
+        Some(frame) => {
+            replay.next += 1;
+            frame.datas
+        }
+        None => {
+            replay.finished = true;
+            ControllerDatas::new()
+        }
+    };
+    drop(guard);
+
+    apply_input(&current);
+}
+
+/// 停止当前槽位的录制与回放（设备断开时调用）
+pub fn stop_slot() {
+    if let Some(recorder) = RECORDER.lock().unwrap().take() {
+        finish_recording(recorder);
+    }
+    if let Some(replay) = REPLAY.lock().unwrap().take() {
+        replay.restore_frequency();
+    }
+}
+
+fn finish_recording(mut recorder: Recorder) -> Option<String> {
+    if let Err(e) = recorder.writer.flush() {
+        log::error!("保存录制文件失败 ({:?}): {e}", recorder.path);
+    }
+    log::info!("⏹️ 录制结束: {:?}，共 {} 帧", recorder.path, recorder.frames);
+    recorder.path.file_name().map(|n| n.to_string_lossy().into_owned())
+}
+
+// ---------------------- Tauri 命令 ----------------------
+
+/// 开始录制指定槽位（缺省为主槽位），返回录制文件名
+#[tauri::command]
+pub fn start_recording(name: Option<String>, slot: Option<usize>) -> Result<String, String> {
+    let slot = slots::resolve_slot(slot)?;
+    slots::with_slot(slot, || {
+        let device = CURRENT_DEVICE.read().unwrap().clone();
+        if device.device_path.is_none() {
+            return Err("请先连接设备".to_string());
+        }
+        if is_replaying() {
+            return Err("回放中无法录制".to_string());
+        }
+
+        let created_at_ms = unix_ms();
+        let name = name.unwrap_or_else(|| format!("recording_{created_at_ms}"));
+        let path = recording_path(&name)?;
+        let file = File::create(&path).map_err(|e| format!("创建录制文件失败: {e}"))?;
+        let mut writer = BufWriter::new(file);
+        writer
+            .write_all(MAGIC)
+            .and_then(|_| writer.write_all(&[FORMAT_VERSION]))
+            .map_err(|e| format!("写入录制文件失败: {e}"))?;
+        let polling_frequency = *FREQ.read().unwrap();
+        bincode::serialize_into(&mut writer, &RecordingHeader { device, created_at_ms, polling_frequency })
+            .map_err(|e| format!("写入录制文件失败: {e}"))?;
+
+        log::info!("⏺️ [槽位 {slot}] 开始录制: {path:?}");
+        let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or(name);
+        let previous = RECORDER.lock().unwrap().replace(Recorder { path, writer, started: None, frames: 0 });
+        if let Some(previous) = previous {
+            finish_recording(previous);
+        }
+        Ok(file_name)
+    })
+}
+
+/// 停止录制，返回录制文件名（未在录制时返回 None）
+#[tauri::command]
+pub fn stop_recording(slot: Option<usize>) -> Result<Option<String>, String> {
+    let slot = slots::resolve_slot(slot)?;
+    Ok(slots::with_slot(slot, || RECORDER.lock().unwrap().take().and_then(finish_recording)))
+}
+
+/// 列出录制目录下的录制文件
+#[tauri::command]
+pub fn list_recordings() -> Result<Vec<String>, String> {
+    let dir = recordings_dir()?;
+    let mut names: Vec<String> = fs::read_dir(&dir)
+        .map_err(|e| format!("读取录制目录失败: {e}"))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == RECORDING_EXT))
+        .filter_map(|path| path.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// 在指定槽位回放录制文件；槽位没有设备时使用录制时的设备信息占位
+#[tauri::command]
+pub fn start_replay(name: String, looped: bool, slot: Option<usize>) -> Result<(), String> {
+    let slot = slots::resolve_slot(slot)?;
+    let path = recording_path(&name)?;
+    let (header, frames) = read_recording(&path)?;
+    if frames.is_empty() {
+        return Err("录制文件中没有数据".to_string());
+    }
+    slots::with_slot(slot, || begin_replay(&name, header, frames, looped))
+}
+
+/// 在当前槽位开始回放已读取的录制数据
+fn begin_replay(name: &str, header: RecordingHeader, frames: Vec<RecordedFrame>, looped: bool) -> Result<(), String> {
+    if RECORDER.lock().unwrap().is_some() {
+        return Err("录制中无法回放".to_string());
+    }
+    let mut current_device = CURRENT_DEVICE.write().unwrap();
+    let placeholder_device = current_device.device_path.is_none();
+    if placeholder_device {
+        let mut device = header.device.clone();
+        device.device_path = Some(format!("{REPLAY_PATH_PREFIX}{name}"));
+        *current_device = device;
+    }
+    drop(current_device);
+
+    log::info!("▶️ [槽位 {}] 开始回放: {name}，共 {} 帧", slots::active_slot(), frames.len());
+    let mut guard = REPLAY.lock().unwrap();
+    // 替换正在进行的回放时先恢复它切换前的频率
+    if let Some(replay) = guard.take() {
+        replay.restore_frequency();
+    }
+    let frequency = header.polling_frequency.clamp(1, 8000);
+    let current_frequency = *FREQ.read().unwrap();
+    let previous_frequency = (frequency != current_frequency).then(|| {
+        log::info!("录制时的轮询频率为 {frequency} Hz，回放期间由 {current_frequency} Hz 切换为该频率");
+        set_frequency(frequency);
+        current_frequency
+    });
+
+    *guard = Some(Replay {
+        frames,
+        next: 0,
+        looped,
+        placeholder_device,
+        finished: false,
+        frequency,
+        previous_frequency,
+    });
+    Ok(())
+}
+
+/// 停止回放（由监听线程在下个周期释放按键并清理）
+#[tauri::command]
+pub fn stop_replay(slot: Option<usize>) -> Result<(), String> {
+    let slot = slots::resolve_slot(slot)?;
+    slots::with_slot(slot, || {
+        if let Some(replay) = REPLAY.lock().unwrap().as_mut() {
+            replay.looped = false;
+            replay.next = replay.frames.len();
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::controller::ControllerType;
+    use crate::controller::slots::MAX_SLOTS;
+    use crate::mapping::output;
+
+    const TEST_SLOT: usize = MAX_SLOTS - 2;
+
+    fn recording(polling_frequency: u32) -> (RecordingHeader, Vec<RecordedFrame>) {
+        let header = RecordingHeader {
+            device: DeviceInfo::new("回放测试手柄".to_string(), "045e".to_string(), ControllerType::Xbox),
+            created_at_ms: 0,
+            polling_frequency,
+        };
+        let frames = (0..2)
+            .map(|i| RecordedFrame {
+                t_us: i * 1_000_000 / polling_frequency as u64,
+                datas: ControllerDatas::new(),
+            })
+            .collect();
+        (header, frames)
+    }
+
+    #[test]
+    fn replay_switches_to_recorded_polling_frequency() {
+        // 与其他驱动 listen 流程的测试互斥，避免回放帧被其他测试的监听周期消费
+        let _lock = output::RECORDING_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let original = *FREQ.read().unwrap();
+        set_frequency(1000);
+
+        slots::with_slot(TEST_SLOT, || {
+            let (header, frames) = recording(125);
+            begin_replay("frequency", header, frames, false).unwrap();
+            assert_eq!(*FREQ.read().unwrap(), 125);
+
+            // 两帧录制数据、一帧空闲数据，第四个周期清理回放状态
+            for _ in 0..4 {
+                assert!(is_replaying());
+                replay_frame();
+            }
+            assert!(!is_replaying());
+            assert!(CURRENT_DEVICE.read().unwrap().device_path.is_none());
+        });
+        assert_eq!(*FREQ.read().unwrap(), 1000);
+
+        // 回放期间用户修改了频率时，停止回放不覆盖用户的设置
+        slots::with_slot(TEST_SLOT, || {
+            let (header, frames) = recording(250);
+            begin_replay("frequency", header, frames, false).unwrap();
+            assert_eq!(*FREQ.read().unwrap(), 250);
+            set_frequency(500);
+            stop_slot();
+            *CURRENT_DEVICE.write().unwrap() = default_devices()[0].clone();
+        });
+        assert_eq!(*FREQ.read().unwrap(), 500);
+
+        set_frequency(original);
+    }
+}
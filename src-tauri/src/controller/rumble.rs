@@ -0,0 +1,138 @@
+//! 手柄震动输出
+//!
+//! 按槽位当前设备选择输出后端：
+//! - PlayStation：hidapi 输出报告（见 `ps4` 模块）
+//! - Xbox（Windows）：XInput `set_state`
+//! - 其他设备或原生后端不可用时：gilrs 力反馈
+//!
+//! 震动到时后自动停止；新的震动会覆盖正在进行的震动。
+
+use crate::controller::controller::{ControllerType, CURRENT_DEVICE, GILRS_GAMEPAD_ID, GLOBAL_GILRS};
+use crate::controller::ps4;
+use crate::controller::slots::{self, PerSlot};
+#[cfg(target_os = "windows")]
+use crate::controller::xbox;
+use crate::mapping;
+use gilrs::ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Replay, Ticks};
+use gilrs::GamepadId;
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// 单次震动的最长时长，防止误配置导致手柄一直震动
+const MAX_RUMBLE_MS: u32 = 5000;
+
+/// 每次震动递增，到时停止前检查是否已被新的震动覆盖
+static RUMBLE_GENERATION: Lazy<PerSlot<AtomicU64>> = Lazy::new(|| PerSlot::new(|| AtomicU64::new(0)));
+
+/// 正在播放的 gilrs 力反馈效果，丢弃即停止
+static GILRS_EFFECT: Lazy<PerSlot<Mutex<Option<Effect>>>> = Lazy::new(|| PerSlot::new(|| Mutex::new(None)));
+
+/// 查找当前槽位设备对应的 gilrs 手柄：优先使用已绑定的 ID，否则按 VID/PID 匹配
+fn find_gilrs_gamepad(gilrs: &gilrs::Gilrs) -> Option<GamepadId> {
+    let bound_id = *GILRS_GAMEPAD_ID.read().unwrap();
+    let device = CURRENT_DEVICE.read().unwrap();
+    gilrs
+        .gamepads()
+        .find(|(id, gamepad)| match bound_id {
+            Some(bound) => usize::from(*id) == bound,
+            None => {
+                gamepad.name() != mapping::virtual_pad::VIRTUAL_PAD_NAME
+                    && gamepad.vendor_id().is_some_and(|vid| format!("{vid:04x}").eq_ignore_ascii_case(&device.vendor_id))
+                    && gamepad.product_id().zip(device.product_id.as_deref())
+                        .is_some_and(|(pid, d_pid)| format!("{pid:04x}").eq_ignore_ascii_case(d_pid))
+            }
+        })
+        .map(|(id, _)| id)
+}
+
+fn set_gilrs_rumble(strong: f32, weak: f32, duration: Duration) -> bool {
+    let mut effect_guard = GILRS_EFFECT.lock().unwrap();
+    *effect_guard = None;
+    if strong <= 0.0 && weak <= 0.0 {
+        return true;
+    }
+
+    let mut gilrs_guard = GLOBAL_GILRS.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(gilrs) = gilrs_guard.as_mut() else {
+        return false;
+    };
+    let Some(id) = find_gilrs_gamepad(gilrs) else {
+        return false;
+    };
+    if !gilrs.gamepad(id).is_ff_supported() {
+        log::debug!("gilrs 手柄 {id} 不支持力反馈");
+        return false;
+    }
+
+    // 效果默认无限循环，到时由 stop 丢弃
+    let scheduling = Replay {
+        play_for: Ticks::from_ms(duration.as_millis() as u32),
+        ..Default::default()
+    };
+    let effect = EffectBuilder::new()
+        .add_effect(BaseEffect {
+            kind: BaseEffectType::Strong { magnitude: (strong.clamp(0.0, 1.0) * u16::MAX as f32) as u16 },
+            scheduling,
+            ..Default::default()
+        })
+        .add_effect(BaseEffect {
+            kind: BaseEffectType::Weak { magnitude: (weak.clamp(0.0, 1.0) * u16::MAX as f32) as u16 },
+            scheduling,
+            ..Default::default()
+        })
+        .gamepads(&[id])
+        .finish(gilrs);
+
+    match effect.map_err(|e| e.to_string()).and_then(|effect| effect.play().map(|_| effect).map_err(|e| e.to_string())) {
+        Ok(effect) => {
+            *effect_guard = Some(effect);
+            true
+        }
+        Err(e) => {
+            log::warn!("gilrs 力反馈播放失败: {e}");
+            false
+        }
+    }
+}
+
+/// 设置当前槽位设备的马达强度，返回是否有后端成功输出
+fn set_motors(strong: f32, weak: f32, duration: Duration) -> bool {
+    let controller_type = CURRENT_DEVICE.read().unwrap().controller_type;
+    let handled = match controller_type {
+        ControllerType::PlayStation => ps4::set_rumble(strong, weak),
+        #[cfg(target_os = "windows")]
+        ControllerType::Xbox => xbox::set_rumble(strong, weak),
+        _ => false,
+    };
+    handled || set_gilrs_rumble(strong, weak, duration)
+}
+
+/// 让当前槽位的手柄震动指定时长（强度 0.0-1.0）
+pub fn rumble(strong: f32, weak: f32, duration_ms: u32) {
+    let slot = slots::active_slot();
+    let generation = RUMBLE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let duration = Duration::from_millis(duration_ms.clamp(1, MAX_RUMBLE_MS) as u64);
+
+    if !set_motors(strong, weak, duration) {
+        log::debug!("槽位 {slot} 的设备不支持震动");
+        return;
+    }
+
+    thread::spawn(move || {
+        thread::sleep(duration);
+        slots::with_slot(slot, || {
+            if RUMBLE_GENERATION.load(Ordering::SeqCst) == generation {
+                set_motors(0.0, 0.0, Duration::ZERO);
+            }
+        });
+    });
+}
+
+/// 立即停止当前槽位的震动
+pub fn stop() {
+    RUMBLE_GENERATION.fetch_add(1, Ordering::SeqCst);
+    set_motors(0.0, 0.0, Duration::ZERO);
+}
@@ -0,0 +1,268 @@
+//! 脚本输入后端
+//!
+//! 在内存中模拟手柄：调用方预先压入若干帧 [`ControllerDatas`]，监听线程每个周期取出一帧，
+//! 取完后保持最后一帧。脚本设备的路径以 `script:` 开头，只由 [`ScriptedBackend`] 驱动，
+//! 其他后端不会尝试打开。
+//!
+//! 用于在没有真实手柄（如无图形界面的 Linux）时驱动完整的 listen → 映射流程。
+
+use crate::controller::backend::InputBackend;
+use crate::controller::controller::{ControllerType, DeviceInfo};
+use crate::controller::datas::ControllerDatas;
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// 脚本设备的路径前缀
+pub const SCRIPTED_PATH_PREFIX: &str = "script:";
+/// 脚本设备使用的厂商 ID（不与真实设备冲突）
+const SCRIPTED_VENDOR_ID: &str = "0000";
+
+struct ScriptedDevice {
+    info: DeviceInfo,
+    /// 待输出的帧
+    frames: VecDeque<ControllerDatas>,
+    /// 最近输出的一帧（帧队列为空时保持）
+    current: ControllerDatas,
+}
+
+static DEVICES: Lazy<Mutex<Vec<ScriptedDevice>>> = Lazy::new(|| Mutex::new(Vec::new()));
+static NEXT_ID: Lazy<Mutex<u16>> = Lazy::new(|| Mutex::new(1));
+
+/// 是否为脚本设备
+pub fn is_scripted(device: &DeviceInfo) -> bool {
+    device
+        .device_path
+        .as_deref()
+        .is_some_and(|p| p.starts_with(SCRIPTED_PATH_PREFIX))
+}
+
+/// 添加一个脚本设备，返回其设备信息（可直接用于 `connect_device`）
+pub fn add_device(name: &str, controller_type: ControllerType) -> DeviceInfo {
+    let id = {
+        let mut next = NEXT_ID.lock().unwrap();
+        let id = *next;
+        *next = next.wrapping_add(1).max(1);
+        id
+    };
+    let mut info = DeviceInfo::new(name.to_string(), SCRIPTED_VENDOR_ID.to_string(), controller_type);
+    info.product_id = Some(format!("{id:04x}"));
+    info.device_path = Some(format!("{SCRIPTED_PATH_PREFIX}{id}"));
+
+    log::info!("添加脚本设备: {name} ({})", info.device_path.as_deref().unwrap_or_default());
+    DEVICES.lock().unwrap().push(ScriptedDevice {
+        info: info.clone(),
+        frames: VecDeque::new(),
+        current: ControllerDatas::new(),
+    });
+    info
+}
+
+/// 移除脚本设备，之后该设备轮询失败，等同于拔出
+pub fn remove_device(path: &str) -> bool {
+    let mut devices = DEVICES.lock().unwrap();
+    let before = devices.len();
+    devices.retain(|d| d.info.device_path.as_deref() != Some(path));
+    before != devices.len()
+}
+
+/// 向脚本设备追加待输出的帧
+pub fn push_frames(path: &str, frames: impl IntoIterator<Item = ControllerDatas>) -> bool {
+    let mut devices = DEVICES.lock().unwrap();
+    match devices.iter_mut().find(|d| d.info.device_path.as_deref() == Some(path)) {
+        Some(device) => {
+            device.frames.extend(frames);
+            true
+        }
+        None => {
+            log::warn!("脚本设备不存在: {path}");
+            false
+        }
+    }
+}
+
+/// 脚本设备剩余未输出的帧数
+pub fn pending_frames(path: &str) -> Option<usize> {
+    DEVICES
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|d| d.info.device_path.as_deref() == Some(path))
+        .map(|d| d.frames.len())
+}
+
+/// 脚本输入后端
+pub struct ScriptedBackend;
+
+impl InputBackend for ScriptedBackend {
+    fn name(&self) -> &'static str {
+        "脚本"
+    }
+
+    fn supports(&self, device: &DeviceInfo) -> bool {
+        is_scripted(device)
+    }
+
+    fn enumerate(&self) -> Vec<DeviceInfo> {
+        DEVICES.lock().unwrap().iter().map(|d| d.info.clone()).collect()
+    }
+
+    fn open(&self, device: &DeviceInfo) -> bool {
+        DEVICES
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|d| d.info.device_path == device.device_path)
+    }
+
+    fn poll(&self, device: &DeviceInfo, datas: &mut ControllerDatas) -> bool {
+        let mut devices = DEVICES.lock().unwrap();
+        let Some(scripted) = devices.iter_mut().find(|d| d.info.device_path == device.device_path) else {
+            return false;
+        };
+        if let Some(frame) = scripted.frames.pop_front() {
+            scripted.current = frame;
+        }
+        datas.copy_input_from(&scripted.current);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::controller::{disconnect_device, listen_tick, use_device, CONTROLLER_DATA};
+    use crate::controller::curve::ResponseCurve;
+    use crate::controller::datas::ControllerButtons;
+    use crate::controller::slots::{self, MAX_SLOTS};
+    use crate::mapping::output::{self, OutputEvent, OutputSinkKind};
+    use crate::mapping::{self, MappingUpdateConfig, GLOBAL_MAPPING_CACHE};
+    use crate::preset;
+    use std::time::Instant;
+
+    /// 不写入设置文件（主槽位会记住上次连接的设备），使用最后一个槽位
+    const TEST_SLOT: usize = MAX_SLOTS - 1;
+
+    fn frame(south: bool, left_trigger: f32) -> ControllerDatas {
+        let mut datas = ControllerDatas::new();
+        datas.set_button(ControllerButtons::South, south);
+        datas.left_trigger.value = left_trigger;
+        datas
+    }
+
+    fn key_events(events: Vec<OutputEvent>) -> Vec<(enigo::Key, enigo::Direction)> {
+        events
+            .into_iter()
+            .filter_map(|event| match event {
+                OutputEvent::Key { key, direction } => Some((key, direction)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn scripted_frames_drive_listen_and_mapping() {
+        let _lock = output::RECORDING_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        output::take_recorded_events();
+
+        let device = add_device("脚本测试手柄 listen", ControllerType::Xbox);
+        let path = device.device_path.clone().unwrap();
+        assert!(use_device(device.name.clone(), Some(TEST_SLOT)));
+
+        slots::with_slot(TEST_SLOT, || {
+            preset::CURRENT_PRESET.write().unwrap().items.output_sink = OutputSinkKind::Recording;
+            let mapping = mapping::create_mapping_from_config(
+                MappingUpdateConfig::new(1)
+                    .with_composed_button("A".to_string())
+                    .with_composed_shortcut_key("K".to_string()),
+            )
+            .unwrap();
+            *GLOBAL_MAPPING_CACHE.write().unwrap() = vec![mapping];
+        });
+
+        assert!(push_frames(&path, [frame(true, 1.0), frame(false, 0.0)]));
+        let mut last_devices = Default::default();
+
+        // 第一帧：按下 A
+        listen_tick(&mut last_devices, Instant::now());
+        assert_eq!(pending_frames(&path), Some(1));
+        let datas = *CONTROLLER_DATA.slot(TEST_SLOT).read().unwrap();
+        assert!(datas.get_button(ControllerButtons::South));
+        assert!((datas.left_trigger.value - 1.0).abs() < 1e-6);
+        assert_eq!(
+            key_events(output::wait_for_recorded_events(1)),
+            vec![(enigo::Key::Unicode('k'), enigo::Direction::Press)]
+        );
+
+        // 第二帧：松开 A
+        listen_tick(&mut last_devices, Instant::now());
+        assert_eq!(pending_frames(&path), Some(0));
+        let datas = *CONTROLLER_DATA.slot(TEST_SLOT).read().unwrap();
+        assert!(!datas.get_button(ControllerButtons::South));
+        assert_eq!(datas.left_trigger.value, 0.0);
+        assert_eq!(
+            key_events(output::wait_for_recorded_events(1)),
+            vec![(enigo::Key::Unicode('k'), enigo::Direction::Release)]
+        );
+
+        // 帧队列取空后保持最后一帧，不再产生输出
+        listen_tick(&mut last_devices, Instant::now());
+        assert!(!CONTROLLER_DATA.slot(TEST_SLOT).read().unwrap().get_button(ControllerButtons::South));
+
+        assert!(disconnect_device(Some(TEST_SLOT)));
+        assert!(remove_device(&path));
+        slots::with_slot(TEST_SLOT, || GLOBAL_MAPPING_CACHE.write().unwrap().clear());
+    }
+
+    #[test]
+    fn stale_frames_are_not_reshaped() {
+        let _lock = output::RECORDING_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let device = add_device("脚本测试手柄 stale", ControllerType::Xbox);
+        let path = device.device_path.clone().unwrap();
+        assert!(use_device(device.name.clone(), Some(TEST_SLOT)));
+
+        let original = slots::with_slot(TEST_SLOT, || {
+            let mut preset = preset::CURRENT_PRESET.write().unwrap();
+            let original = preset.clone();
+            preset.items.output_sink = OutputSinkKind::Recording;
+            preset.items.deadzone_left = 0;
+            preset.items.outer_deadzone_left = 0;
+            preset.items.anti_deadzone_left = 50;
+            preset.items.trigger_deadzone_left = 0;
+            preset.items.trigger_outer_deadzone_left = 20;
+            preset.items.deadzone = 0;
+            preset.items.outer_deadzone = 0;
+            preset.items.anti_deadzone = 0;
+            preset.items.stick_curve_right = ResponseCurve::Power { exponent: 0.5 };
+            original
+        });
+
+        let mut datas = frame(false, 0.5);
+        datas.left_stick.x = 0.2;
+        datas.right_stick.y = 0.25;
+        assert!(push_frames(&path, [datas]));
+        let mut last_devices = Default::default();
+
+        // 反死区：0.5 + 0.5 * 0.2；外死区：0.5 / 0.8
+        let expected_stick = 0.6;
+        let expected_trigger = 0.625;
+        // 响应曲线：0.25^0.5
+        let expected_curve = 0.5;
+        listen_tick(&mut last_devices, Instant::now());
+
+        // 设备已移除但槽位尚未清理：轮询失败，保留的上一帧不能被再次塑形
+        assert!(remove_device(&path));
+        for _ in 0..3 {
+            let datas = *CONTROLLER_DATA.slot(TEST_SLOT).read().unwrap();
+            assert!((datas.left_stick.x - expected_stick).abs() < 1e-5, "{}", datas.left_stick.x);
+            assert!((datas.left_trigger.value - expected_trigger).abs() < 1e-5, "{}", datas.left_trigger.value);
+            assert!((datas.right_stick.y - expected_curve).abs() < 1e-5, "{}", datas.right_stick.y);
+            listen_tick(&mut last_devices, Instant::now());
+        }
+
+        assert!(disconnect_device(Some(TEST_SLOT)));
+        slots::with_slot(TEST_SLOT, || *preset::CURRENT_PRESET.write().unwrap() = original);
+        output::take_recorded_events();
+    }
+}
@@ -0,0 +1,80 @@
+//! 多手柄设备槽位
+//!
+//! 每个槽位拥有独立的设备、采样数据、校准、预设与映射状态，互不干扰。
+//! 运行时状态使用 [`PerSlot`] 包装：它按当前线程的“活动槽位”解引用到对应槽位的值，
+//! 因此原有的 `CONTROLLER_DATA.write()` 等写法无需改动即可作用于当前槽位。
+//!
+//! - 监听线程在处理每个槽位前通过 [`with_slot`] 切换活动槽位
+//! - 其他线程（Tauri 命令等）默认处于 0 号槽位，即前端界面操作的主槽位
+//! - 需要跨槽位访问时使用 [`PerSlot::slot`]
+
+use std::cell::Cell;
+use std::ops::Deref;
+
+/// 最多同时使用的手柄数量
+pub const MAX_SLOTS: usize = 4;
+
+/// 主槽位：前端界面显示与编辑的槽位
+pub const PRIMARY_SLOT: usize = 0;
+
+thread_local! {
+    static ACTIVE_SLOT: Cell<usize> = const { Cell::new(PRIMARY_SLOT) };
+}
+
+/// 当前线程的活动槽位
+pub fn active_slot() -> usize {
+    ACTIVE_SLOT.with(|slot| slot.get())
+}
+
+/// 在指定槽位的上下文中执行闭包，结束后（包括 panic）恢复原槽位
+pub fn with_slot<R>(slot: usize, f: impl FnOnce() -> R) -> R {
+    struct Restore(usize);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            ACTIVE_SLOT.with(|slot| slot.set(self.0));
+        }
+    }
+
+    let slot = slot.min(MAX_SLOTS - 1);
+    let _restore = Restore(ACTIVE_SLOT.with(|s| s.replace(slot)));
+    f()
+}
+
+/// 校验前端传入的槽位号，缺省时使用当前活动槽位
+pub fn resolve_slot(slot: Option<usize>) -> Result<usize, String> {
+    match slot {
+        None => Ok(active_slot()),
+        Some(s) if s < MAX_SLOTS => Ok(s),
+        Some(s) => Err(format!("槽位 {s} 超出范围 (0-{})", MAX_SLOTS - 1)),
+    }
+}
+
+/// 每个槽位各持有一份的值，解引用到当前活动槽位
+pub struct PerSlot<T> {
+    slots: [T; MAX_SLOTS],
+}
+
+impl<T> PerSlot<T> {
+    pub fn new(init: impl Fn() -> T) -> Self {
+        Self {
+            slots: std::array::from_fn(|_| init()),
+        }
+    }
+
+    /// 访问指定槽位的值
+    pub fn slot(&self, slot: usize) -> &T {
+        &self.slots[slot]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.slots.iter()
+    }
+}
+
+impl<T> Deref for PerSlot<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.slots[active_slot()]
+    }
+}
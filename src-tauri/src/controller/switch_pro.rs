@@ -0,0 +1,616 @@
+//! Nintendo Switch Pro 手柄原生 HID 驱动
+//!
+//! gilrs 对 Pro 手柄的支持依赖 SDL 映射，经常出现按键错位、摇杆范围不对或
+//! 完全收不到数据（未握手时手柄只发送 0x3F 精简报告）。这里直接走 hidapi：
+//!
+//! 1. USB 连接先完成 0x80 握手（02 → 03 → 02 → 04），让手柄改走纯 HID 通讯
+//! 2. 子命令 0x03/0x30 切换到 0x30 完整报告模式，0x40/0x01 打开 IMU
+//! 3. 子命令 0x10 读取 SPI Flash 中的摇杆出厂校准，作为 `StickCalibration` 默认值
+//! 4. 轮询时解析 0x30 报告中的 12 位摇杆、按键、六轴与电量数据
+//!
+//! 报告解析为纯函数，可直接用抓包数据验证。
+
+use crate::controller::backend::InputBackend;
+use crate::controller::calibrate::{self, ControllerCalibration, StickCalibration, StickRange};
+use crate::controller::controller::{ControllerType, DeviceInfo, RAW_CONTROLLER_DATA};
+use crate::controller::curve::ResponseCurve;
+use crate::controller::logic::DeadzoneShape;
+use crate::controller::datas::{ControllerButtons, ControllerDatas, ControllerImu};
+use crate::controller::power::{self, ConnectionType, PowerState};
+use crate::controller::scripted;
+use crate::controller::slots::{self, PerSlot, MAX_SLOTS};
+use hidapi::{BusType, HidApi, HidDevice};
+use once_cell::sync::Lazy;
+use std::ffi::CString;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 本驱动负责的全部按键（ZL/ZR 为数字扳机，通过扳机值体现）
+const SWITCH_BUTTONS: [ControllerButtons; 16] = [
+    ControllerButtons::South,
+    ControllerButtons::East,
+    ControllerButtons::West,
+    ControllerButtons::North,
+    ControllerButtons::LB,
+    ControllerButtons::RB,
+    ControllerButtons::LStick,
+    ControllerButtons::RStick,
+    ControllerButtons::Back,
+    ControllerButtons::Start,
+    ControllerButtons::Guide,
+    ControllerButtons::Left,
+    ControllerButtons::Right,
+    ControllerButtons::Up,
+    ControllerButtons::Down,
+    ControllerButtons::Capture,
+];
+
+/// Switch Pro 手柄 PID
+const PRO_CONTROLLER_PID: &str = "2009";
+
+/// 12 位摇杆原始值的理论中心
+const STICK_RAW_CENTER: f32 = 2048.0;
+
+/// 加速度计 ±8G 量程：每 g 对应的计数
+const ACCEL_LSB_PER_G: f32 = 4096.0;
+/// 陀螺仪 ±2000 dps 量程：每计数对应的 deg/s
+const GYRO_DEG_S_PER_LSB: f32 = 0.070;
+
+/// SPI Flash 中的摇杆校准地址
+const SPI_USER_LEFT_STICK_CAL: u32 = 0x8010;
+const SPI_USER_RIGHT_STICK_CAL: u32 = 0x801B;
+const SPI_FACTORY_LEFT_STICK_CAL: u32 = 0x603D;
+const SPI_FACTORY_RIGHT_STICK_CAL: u32 = 0x6046;
+/// 用户校准区块前的有效标志
+const SPI_USER_CAL_MAGIC: [u8; 2] = [0xB2, 0xA1];
+
+/// 子命令
+const SUBCMD_SET_INPUT_MODE: u8 = 0x03;
+const SUBCMD_SPI_READ: u8 = 0x10;
+const SUBCMD_SET_PLAYER_LIGHTS: u8 = 0x30;
+const SUBCMD_ENABLE_IMU: u8 = 0x40;
+const INPUT_MODE_FULL: u8 = 0x30;
+
+/// 玩家指示灯图案（低 4 位常亮），下标为玩家编号 - 1
+const PLAYER_LIGHT_PATTERNS: [u8; 4] = [0x01, 0x03, 0x07, 0x0F];
+
+/// 子命令包中的中性震动数据（左右马达各 4 字节）
+const NEUTRAL_RUMBLE: [u8; 8] = [0x00, 0x01, 0x40, 0x40, 0x00, 0x01, 0x40, 0x40];
+
+const REPLY_TIMEOUT: Duration = Duration::from_millis(300);
+
+// ---------------------- 报告解析 ----------------------
+
+/// 3 字节打包的两个 12 位值
+pub fn decode_u12_pair(data: &[u8]) -> (u16, u16) {
+    let a = data[0] as u16 | ((data[1] as u16 & 0x0F) << 8);
+    let b = (data[1] as u16 >> 4) | ((data[2] as u16) << 4);
+    (a, b)
+}
+
+/// 摇杆原始值按理论中心归一化到 [-1, 1]（Y 轴原始值向上增大，无需翻转）
+fn normalize_stick(raw: u16) -> f32 {
+    ((raw as f32 - STICK_RAW_CENTER) / STICK_RAW_CENTER).clamp(-1.0, 1.0)
+}
+
+/// 解析 0x30 完整报告，得到未校准的 `ControllerDatas`
+///
+/// 布局：3-5 按键，6-8 左摇杆，9-11 右摇杆，13-48 三帧六轴（每帧加速度 3×i16 + 陀螺仪 3×i16）
+pub fn parse_full_report(report: &[u8]) -> Option<ControllerDatas> {
+    if report.len() < 49 || report[0] != 0x30 {
+        return None;
+    }
+    let (right, shared, left) = (report[3], report[4], report[5]);
+
+    let mut datas = ControllerDatas::new();
+    datas.limits.set_limits(0.0, 4095.0, 0.0, 1.0);
+
+    // 按键按标签对应到布局表：Y→North, X→West, B→South, A→East
+    datas.set_button(ControllerButtons::North, right & 0x01 != 0); // Y
+    datas.set_button(ControllerButtons::West, right & 0x02 != 0); // X
+    datas.set_button(ControllerButtons::South, right & 0x04 != 0); // B
+    datas.set_button(ControllerButtons::East, right & 0x08 != 0); // A
+    datas.set_button(ControllerButtons::RB, right & 0x40 != 0); // R
+    datas.set_button(ControllerButtons::Back, shared & 0x01 != 0); // Minus
+    datas.set_button(ControllerButtons::Start, shared & 0x02 != 0); // Plus
+    datas.set_button(ControllerButtons::RStick, shared & 0x04 != 0);
+    datas.set_button(ControllerButtons::LStick, shared & 0x08 != 0);
+    datas.set_button(ControllerButtons::Guide, shared & 0x10 != 0); // Home
+    datas.set_button(ControllerButtons::Capture, shared & 0x20 != 0); // Capture
+    datas.set_button(ControllerButtons::Down, left & 0x01 != 0);
+    datas.set_button(ControllerButtons::Up, left & 0x02 != 0);
+    datas.set_button(ControllerButtons::Right, left & 0x04 != 0);
+    datas.set_button(ControllerButtons::Left, left & 0x08 != 0);
+    datas.set_button(ControllerButtons::LB, left & 0x40 != 0); // L
+    datas.left_stick.is_pressed = shared & 0x08 != 0;
+    datas.right_stick.is_pressed = shared & 0x04 != 0;
+
+    // ZL / ZR 为数字扳机
+    datas.left_trigger.value = if left & 0x80 != 0 { 1.0 } else { 0.0 };
+    datas.right_trigger.value = if right & 0x80 != 0 { 1.0 } else { 0.0 };
+
+    let (lx, ly) = decode_u12_pair(&report[6..9]);
+    let (rx, ry) = decode_u12_pair(&report[9..12]);
+    datas.left_stick.x = normalize_stick(lx);
+    datas.left_stick.y = normalize_stick(ly);
+    datas.right_stick.x = normalize_stick(rx);
+    datas.right_stick.y = normalize_stick(ry);
+
+    // 取最新的一帧六轴（第三帧）
+    let imu_frame = &report[37..49];
+    let read_i16 = |i: usize| i16::from_le_bytes([imu_frame[i], imu_frame[i + 1]]) as f32;
+    datas.imu = ControllerImu {
+        // 传感器 X 为横滚、Y 为俯仰、Z 为偏航，陀螺仪与加速度计都转换为 pitch / yaw / roll 顺序
+        gyro: [
+            read_i16(8) * GYRO_DEG_S_PER_LSB,
+            read_i16(10) * GYRO_DEG_S_PER_LSB,
+            read_i16(6) * GYRO_DEG_S_PER_LSB,
+        ],
+        accel: [
+            read_i16(2) / ACCEL_LSB_PER_G,
+            read_i16(4) / ACCEL_LSB_PER_G,
+            read_i16(0) / ACCEL_LSB_PER_G,
+        ],
+        has_imu: true,
+    };
+    Some(datas)
+}
+
+/// 解析 0x30 报告第 2 字节的电量：高 4 位中 bit1-3 为电量档位 (0-4)，bit0 为充电中
+pub fn parse_power(report: &[u8], bluetooth: bool) -> Option<PowerState> {
+    if report.len() < 3 || report[0] != 0x30 {
+        return None;
+    }
+    let battery = report[2] >> 4;
+    Some(PowerState {
+        level: Some(((battery >> 1).min(4)) * 25),
+        charging: battery & 0x01 != 0,
+        connection: if bluetooth { ConnectionType::Bluetooth } else { ConnectionType::Wired },
+    })
+}
+
+/// SPI 中 9 字节的摇杆校准块
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawStickCalibration {
+    pub center: (u16, u16),
+    pub above_center: (u16, u16),
+    pub below_center: (u16, u16),
+}
+
+impl RawStickCalibration {
+    /// 左摇杆块顺序：上方范围、中心、下方范围
+    pub fn parse_left(data: &[u8]) -> Option<Self> {
+        Self::parse(data, [0, 1, 2])
+    }
+
+    /// 右摇杆块顺序：中心、下方范围、上方范围
+    pub fn parse_right(data: &[u8]) -> Option<Self> {
+        Self::parse(data, [2, 0, 1])
+    }
+
+    /// order 依次为 (上方范围, 中心, 下方范围) 在块中的位置
+    fn parse(data: &[u8], order: [usize; 3]) -> Option<Self> {
+        if data.len() < 9 || data[..9].iter().all(|b| *b == 0xFF) {
+            return None;
+        }
+        let pairs = [
+            decode_u12_pair(&data[0..3]),
+            decode_u12_pair(&data[3..6]),
+            decode_u12_pair(&data[6..9]),
+        ];
+        Some(Self {
+            above_center: pairs[order[0]],
+            center: pairs[order[1]],
+            below_center: pairs[order[2]],
+        })
+    }
+
+    /// 转换为归一化空间（与 `normalize_stick` 一致）下的 `StickCalibration`
+    pub fn to_stick_calibration(&self) -> StickCalibration {
+        let to_norm = |v: f32| (v - STICK_RAW_CENTER) / STICK_RAW_CENTER;
+        let (cx, cy) = (self.center.0 as f32, self.center.1 as f32);
+        let mut cali = StickCalibration::new();
+        cali.stick_center = (to_norm(cx), to_norm(cy));
+        cali.stick_range = StickRange {
+            x_min: to_norm(cx - self.below_center.0 as f32),
+            x_max: to_norm(cx + self.above_center.0 as f32),
+            y_min: to_norm(cy - self.below_center.1 as f32),
+            y_max: to_norm(cy + self.above_center.1 as f32),
+        };
+        cali
+    }
+}
+
+// ---------------------- 设备通讯 ----------------------
+
+/// 已打开的 Switch Pro 手柄
+struct SwitchProController {
+    path: String,
+    device: HidDevice,
+    packet_counter: u8,
+    /// 最近一次发送的玩家指示灯图案
+    player_lights: Option<u8>,
+    /// 已发送、等待 0x21 回复的子命令及发送时间，回复在 `poll` 读取报告时消费
+    pending_reply: Option<(u8, Instant)>,
+    bluetooth: bool,
+    last_datas: Option<ControllerDatas>,
+    last_power: Option<PowerState>,
+}
+
+/// 当前打开的 Switch Pro 手柄（由 listen 线程独占使用）
+static SWITCH_PRO_CONTROLLER: Lazy<PerSlot<Mutex<Option<SwitchProController>>>> =
+    Lazy::new(|| PerSlot::new(|| Mutex::new(None)));
+
+impl SwitchProController {
+    /// 等待满足条件的报告，超时返回 None
+    fn wait_for_report(&self, mut matches: impl FnMut(&[u8]) -> bool) -> Option<Vec<u8>> {
+        let deadline = Instant::now() + REPLY_TIMEOUT;
+        let mut buf = [0u8; 64];
+        while Instant::now() < deadline {
+            match self.device.read_timeout(&mut buf, 20) {
+                Ok(len) if len > 0 && matches(&buf[..len]) => return Some(buf[..len].to_vec()),
+                Ok(_) => {}
+                Err(e) => {
+                    log::warn!("Switch Pro 读取回复失败: {e}");
+                    return None;
+                }
+            }
+        }
+        None
+    }
+
+    /// USB 握手命令（0x80 xx），等待 0x81 xx 回复
+    fn usb_command(&self, cmd: u8) -> bool {
+        if let Err(e) = self.device.write(&[0x80, cmd]) {
+            log::warn!("Switch Pro USB 命令 0x80 {cmd:02x} 发送失败: {e}");
+            return false;
+        }
+        // 0x80 0x04 之后手柄不再回复
+        cmd == 0x04 || self.wait_for_report(|r| r[0] == 0x81 && r.get(1) == Some(&cmd)).is_some()
+    }
+
+    /// 只发送子命令，不等待回复
+    fn send_subcommand(&mut self, subcmd: u8, args: &[u8]) -> bool {
+        let mut packet = [0u8; 49];
+        packet[0] = 0x01;
+        packet[1] = self.packet_counter;
+        packet[2..10].copy_from_slice(&NEUTRAL_RUMBLE);
+        packet[10] = subcmd;
+        packet[11..11 + args.len()].copy_from_slice(args);
+        self.packet_counter = (self.packet_counter + 1) & 0x0F;
+
+        if let Err(e) = self.device.write(&packet) {
+            log::warn!("Switch Pro 子命令 0x{subcmd:02x} 发送失败: {e}");
+            return false;
+        }
+        true
+    }
+
+    /// 发送子命令并等待对应的 0x21 回复（仅用于握手阶段，会阻塞最多 `REPLY_TIMEOUT`）
+    fn subcommand(&mut self, subcmd: u8, args: &[u8]) -> Option<Vec<u8>> {
+        if !self.send_subcommand(subcmd, args) {
+            return None;
+        }
+        let reply = self.wait_for_report(|r| r[0] == 0x21 && r.get(14) == Some(&subcmd));
+        if reply.is_none() {
+            log::warn!("Switch Pro 子命令 0x{subcmd:02x} 未收到回复");
+        }
+        reply
+    }
+
+    /// 读取 SPI Flash
+    fn spi_read(&mut self, address: u32, len: u8) -> Option<Vec<u8>> {
+        let mut args = [0u8; 5];
+        args[..4].copy_from_slice(&address.to_le_bytes());
+        args[4] = len;
+        let reply = self.subcommand(SUBCMD_SPI_READ, &args)?;
+        // 回复：15-18 地址，19 长度，20.. 数据
+        let data_start = 20;
+        let data_end = data_start + len as usize;
+        (reply.len() >= data_end && reply[15..19] == address.to_le_bytes())
+            .then(|| reply[data_start..data_end].to_vec())
+    }
+
+    /// 读取摇杆校准：优先用户校准（带有效标志），否则使用出厂校准
+    fn read_stick_calibration(&mut self) -> Option<ControllerCalibration> {
+        let read_block = |this: &mut Self, user: u32, factory: u32| {
+            if let Some(data) = this.spi_read(user, 11) {
+                if data[..2] == SPI_USER_CAL_MAGIC {
+                    return Some(data[2..11].to_vec());
+                }
+            }
+            this.spi_read(factory, 9)
+        };
+
+        let left = read_block(self, SPI_USER_LEFT_STICK_CAL, SPI_FACTORY_LEFT_STICK_CAL)
+            .and_then(|d| RawStickCalibration::parse_left(&d))?;
+        let right = read_block(self, SPI_USER_RIGHT_STICK_CAL, SPI_FACTORY_RIGHT_STICK_CAL)
+            .and_then(|d| RawStickCalibration::parse_right(&d))?;
+        log::debug!("Switch Pro 摇杆校准: 左 {left:?}, 右 {right:?}");
+
+        Some(ControllerCalibration {
+            left_stick: left.to_stick_calibration(),
+            right_stick: right.to_stick_calibration(),
+        })
+    }
+
+    /// 完成握手并切换到完整报告模式
+    fn initialize(&mut self, bus_type: BusType) -> bool {
+        if bus_type == BusType::Usb {
+            for cmd in [0x02, 0x03, 0x02, 0x04] {
+                if !self.usb_command(cmd) {
+                    return false;
+                }
+            }
+        }
+
+        if self.subcommand(SUBCMD_SET_INPUT_MODE, &[INPUT_MODE_FULL]).is_none() {
+            return false;
+        }
+        if self.subcommand(SUBCMD_ENABLE_IMU, &[0x01]).is_none() {
+            log::warn!("Switch Pro 开启 IMU 失败，体感数据不可用");
+        }
+
+        match self.read_stick_calibration() {
+            Some(cali) => calibrate::set_default_calibration(Some(cali)),
+            None => log::warn!("Switch Pro 读取摇杆校准失败，使用理论中心"),
+        }
+        true
+    }
+}
+
+/// 其他槽位是否已打开同一 HID 路径，避免两个槽位驱动同一个手柄
+fn claimed_by_other_slot(path: &str) -> bool {
+    let active_slot = slots::active_slot();
+    (0..MAX_SLOTS)
+        .filter(|slot| *slot != active_slot)
+        .any(|slot| SWITCH_PRO_CONTROLLER.slot(slot).lock().unwrap().as_ref().is_some_and(|c| c.path == path))
+}
+
+fn open_controller(device: &DeviceInfo) -> Option<SwitchProController> {
+    let path = device.device_path.as_deref()?;
+    if path.starts_with("wgi:") {
+        return None;
+    }
+    if claimed_by_other_slot(path) {
+        log::debug!("Switch Pro 手柄已被其他槽位打开，跳过 ({path})");
+        return None;
+    }
+    let is_pro = [device.sub_product_id.as_deref(), device.product_id.as_deref()]
+        .iter()
+        .any(|pid| pid.is_some_and(|p| p.eq_ignore_ascii_case(PRO_CONTROLLER_PID)));
+    if !is_pro {
+        return None;
+    }
+
+    let api = match HidApi::new() {
+        Ok(api) => api,
+        Err(e) => {
+            log::error!("初始化 hidapi 失败: {e}");
+            return None;
+        }
+    };
+    let c_path = CString::new(path).ok()?;
+    let hid_device = match api.open_path(&c_path) {
+        Ok(d) => d,
+        Err(e) => {
+            log::warn!("打开 Switch Pro 手柄失败 ({path}): {e}");
+            return None;
+        }
+    };
+    let bus_type = hid_device
+        .get_device_info()
+        .map(|info| info.bus_type())
+        .unwrap_or(BusType::Unknown);
+
+    let mut controller = SwitchProController {
+        path: path.to_string(),
+        device: hid_device,
+        packet_counter: 0,
+        player_lights: None,
+        pending_reply: None,
+        bluetooth: bus_type == BusType::Bluetooth,
+        last_datas: None,
+        last_power: None,
+    };
+    if !controller.initialize(bus_type) {
+        log::warn!("Switch Pro 握手失败 ({path})");
+        return None;
+    }
+    if let Err(e) = controller.device.set_blocking_mode(false) {
+        log::error!("设置非阻塞模式失败 ({path}): {e}");
+        return None;
+    }
+
+    log::info!("✅ 已打开 Switch Pro 手柄: {} ({bus_type:?})", device.name);
+    Some(controller)
+}
+
+/// 关闭当前 Switch Pro 手柄，同时清除出厂校准默认值
+pub fn close() {
+    if let Some(controller) = SWITCH_PRO_CONTROLLER.lock().unwrap().take() {
+        log::info!("Switch Pro 手柄已关闭: {}", controller.path);
+        calibrate::set_default_calibration(None);
+    }
+}
+
+/// 设置当前槽位 Switch Pro 手柄的玩家指示灯（编号 1-4，Pro 手柄没有 RGB 灯条）
+/// 与上次设置的图案相同时不重复发送；返回 false 表示手柄未打开或发送失败。
+/// 由 listen 线程调用，只发送子命令不等待回复，回复由下一次轮询读取报告时消费
+pub fn set_player_lights(player: u8) -> bool {
+    let mut guard = SWITCH_PRO_CONTROLLER.lock().unwrap();
+    let Some(controller) = guard.as_mut() else {
+        return false;
+    };
+    let pattern = PLAYER_LIGHT_PATTERNS[(player.clamp(1, 4) - 1) as usize];
+    if controller.player_lights == Some(pattern) {
+        return true;
+    }
+    // 失败也记录，避免每个周期都重复发送
+    controller.player_lights = Some(pattern);
+    if !controller.send_subcommand(SUBCMD_SET_PLAYER_LIGHTS, &[pattern]) {
+        return false;
+    }
+    controller.pending_reply = Some((SUBCMD_SET_PLAYER_LIGHTS, Instant::now()));
+    true
+}
+
+/// 确保当前槽位已打开该设备（设备路径变化时重新打开）
+fn ensure_open(slot: &mut Option<SwitchProController>, device: &DeviceInfo) {
+    if slot
+        .as_ref()
+        .is_some_and(|c| Some(c.path.as_str()) != device.device_path.as_deref())
+    {
+        *slot = None;
+    }
+    if slot.is_none() {
+        *slot = open_controller(device);
+    }
+}
+
+/// Switch Pro 手柄轮询入口，输入写入 `controller_data`
+/// 返回 true 表示成功读取，false 表示 HID 不可用（调用方应回退下一个后端）
+pub fn poll_switch_pro_controller(device: &DeviceInfo, controller_data: &mut ControllerDatas) -> bool {
+    let mut guard = SWITCH_PRO_CONTROLLER.lock().unwrap();
+    ensure_open(&mut guard, device);
+    let Some(controller) = guard.as_mut() else {
+        return false;
+    };
+
+    // 读空缓冲区，只保留最新一帧
+    let mut buf = [0u8; 64];
+    loop {
+        match controller.device.read_timeout(&mut buf, 0) {
+            Ok(0) => break,
+            Ok(len) => {
+                let report = &buf[..len];
+                if let Some((subcmd, _)) = controller.pending_reply {
+                    if report[0] == 0x21 && report.get(14) == Some(&subcmd) {
+                        log::debug!("Switch Pro 子命令 0x{subcmd:02x} 已确认");
+                        controller.pending_reply = None;
+                    }
+                }
+                if let Some(datas) = parse_full_report(report) {
+                    controller.last_datas = Some(datas);
+                    controller.last_power = parse_power(report, controller.bluetooth);
+                }
+            }
+            Err(e) => {
+                log::warn!("Switch Pro 读取失败，关闭设备 ({}): {e}", controller.path);
+                *guard = None;
+                return false;
+            }
+        }
+    }
+
+    if let Some((subcmd, sent_at)) = controller.pending_reply {
+        if sent_at.elapsed() >= REPLY_TIMEOUT {
+            log::warn!("Switch Pro 子命令 0x{subcmd:02x} 未收到回复");
+            controller.pending_reply = None;
+        }
+    }
+
+    let Some(datas) = controller.last_datas else {
+        return true;
+    };
+    let last_power = controller.last_power;
+    drop(guard);
+
+    if let Some(state) = last_power {
+        power::update(state);
+    }
+
+    // 原始归一化值供校准向导使用
+    {
+        let mut raw_data = RAW_CONTROLLER_DATA.write().unwrap();
+        raw_data.left_stick = datas.left_stick;
+        raw_data.right_stick = datas.right_stick;
+    }
+
+    // 应用校准（用户校准或 SPI 出厂校准），死区和响应曲线由 listen 统一处理
+    let cali = calibrate::get_current_calibration();
+    let linear = ResponseCurve::Linear;
+    let (lx, ly) = calibrate::apply_calibration(datas.left_stick.x, datas.left_stick.y, &DeadzoneShape::NONE, &linear, &cali.left_stick);
+    let (rx, ry) = calibrate::apply_calibration(datas.right_stick.x, datas.right_stick.y, &DeadzoneShape::NONE, &linear, &cali.right_stick);
+
+    for button in SWITCH_BUTTONS {
+        controller_data.set_button(button, datas.get_button(button));
+    }
+    controller_data.left_stick = datas.left_stick;
+    controller_data.right_stick = datas.right_stick;
+    controller_data.left_stick.x = lx;
+    controller_data.left_stick.y = ly;
+    controller_data.right_stick.x = rx;
+    controller_data.right_stick.y = ry;
+    controller_data.left_trigger.value = datas.left_trigger.value;
+    controller_data.left_trigger.has_pressure = false;
+    controller_data.right_trigger.value = datas.right_trigger.value;
+    controller_data.right_trigger.has_pressure = false;
+    controller_data.limits = datas.limits;
+    controller_data.imu = datas.imu;
+    true
+}
+
+/// Switch Pro hidapi 输入后端
+pub struct SwitchProBackend;
+
+impl InputBackend for SwitchProBackend {
+    fn name(&self) -> &'static str {
+        "Switch Pro HID"
+    }
+
+    fn supports(&self, device: &DeviceInfo) -> bool {
+        device.controller_type == ControllerType::Switch && !scripted::is_scripted(device)
+    }
+
+    fn open(&self, device: &DeviceInfo) -> bool {
+        let mut guard = SWITCH_PRO_CONTROLLER.lock().unwrap();
+        ensure_open(&mut guard, device);
+        guard.is_some()
+    }
+
+    fn poll(&self, device: &DeviceInfo, datas: &mut ControllerDatas) -> bool {
+        poll_switch_pro_controller(device, datas)
+    }
+
+    fn close(&self) {
+        close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 从手柄 SPI 0x603D 读出的左摇杆出厂校准块（上方范围、中心、下方范围）
+    const FACTORY_LEFT_STICK_CAL: [u8; 9] = [0x00, 0x66, 0x66, 0x00, 0xC8, 0x7C, 0x65, 0xE5, 0x5B];
+    /// 从手柄 SPI 0x6046 读出的右摇杆出厂校准块（中心、下方范围、上方范围）
+    const FACTORY_RIGHT_STICK_CAL: [u8; 9] = [0x00, 0xC8, 0x7C, 0x65, 0xE5, 0x5B, 0x00, 0x66, 0x66];
+
+    const EXPECTED: RawStickCalibration = RawStickCalibration {
+        center: (2048, 1996),
+        above_center: (1536, 1638),
+        below_center: (1381, 1470),
+    };
+
+    #[test]
+    fn parses_left_stick_factory_calibration() {
+        assert_eq!(RawStickCalibration::parse_left(&FACTORY_LEFT_STICK_CAL), Some(EXPECTED));
+    }
+
+    #[test]
+    fn parses_right_stick_factory_calibration() {
+        assert_eq!(RawStickCalibration::parse_right(&FACTORY_RIGHT_STICK_CAL), Some(EXPECTED));
+
+        let cali = EXPECTED.to_stick_calibration();
+        assert_eq!(cali.stick_center.0, 0.0);
+        assert!(cali.stick_range.x_min < cali.stick_center.0 && cali.stick_center.0 < cali.stick_range.x_max);
+        assert!(cali.stick_range.y_min < cali.stick_center.1 && cali.stick_center.1 < cali.stick_range.y_max);
+    }
+
+    #[test]
+    fn rejects_erased_calibration_block() {
+        assert_eq!(RawStickCalibration::parse_right(&[0xFF; 9]), None);
+        assert_eq!(RawStickCalibration::parse_left(&[0x00; 4]), None);
+    }
+}
@@ -1,20 +1,33 @@
 cfg_if::cfg_if! {
     if #[cfg(target_os = "windows")] {
-        use crate::controller::controller::{self, get_xinput, DeviceInfo, CONTROLLER_DATA, JoystickSource};
+        use crate::controller::controller::{self, get_xinput, ControllerType, DeviceInfo, JoystickSource};
+        use crate::controller::backend::InputBackend;
+        use crate::controller::scripted;
     } else if #[cfg(target_os = "linux")] {
-        use crate::controller::controller::{self, disconnect_device, DeviceInfo, CONTROLLER_DATA, RAW_CONTROLLER_DATA, JoystickSource};
+        use crate::controller::controller::{self, disconnect_device, DeviceInfo, RAW_CONTROLLER_DATA, JoystickSource};
     }
 }
-use crate::controller::datas::ControllerButtons;
+use crate::controller::datas::{ControllerButtons, ControllerDatas};
 use crate::controller::logic;
 
 #[cfg(target_os = "windows")]
-use rusty_xinput::XInputState;
+use rusty_xinput::{BatteryLevel, BatteryType, XInputState};
+#[cfg(target_os = "windows")]
+use crate::controller::power::{self, ConnectionType, PowerState};
 
 const MAX_XINPUT_DEVICES: usize = 4;
 
 use std::collections::HashMap;
 use once_cell::sync::Lazy;
+#[cfg(target_os = "windows")]
+use crate::controller::slots::{self, PerSlot, MAX_SLOTS};
+#[cfg(target_os = "windows")]
+use std::sync::RwLock;
+
+/// 各槽位绑定的 XInput 用户索引，多个同型号手柄时避免重复读取同一个
+#[cfg(target_os = "windows")]
+static XINPUT_USER_INDEX: Lazy<PerSlot<RwLock<Option<usize>>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(None)));
 
 /// 物理设备 (vid, pid) → XInput 层 (vid, pid) 映射表。
 /// GIP 协议手柄在 XInput 层呈现为微软 VID(045e)+虚拟 PID，与物理 VID/PID 不同。
@@ -27,11 +40,9 @@ static XINPUT_ID_MAP: Lazy<HashMap<(&str, &str), (&str, &str)>> = Lazy::new(|| {
 
 /// Xbox控制器状态轮询处理 (Windows)
 #[cfg(target_os = "windows")]
-fn _poll_xbox_controller_state(state: XInputState) {
-    // let mut controller_data = ControllerDatas::new();
-    let mut controller_data = CONTROLLER_DATA.write().unwrap();
+fn _poll_xbox_controller_state(state: XInputState, controller_data: &mut ControllerDatas) {
 
-    // 按钮状态检测
+    // 按钮状态检测（XInput 不报告 Series 分享键与 Elite 背键）
     let buttons = [
         (state.south_button(), ControllerButtons::South, "Xbox A 键（South）"),
         (state.east_button(), ControllerButtons::East, "Xbox B 键（East）"),
@@ -100,14 +111,24 @@ fn _poll_xbox_controller_state(state: XInputState) {
     controller_data.left_trigger.has_pressure = true;
 }
 
-/// Xbox控制器轮询入口 (Windows)
+/// Xbox控制器轮询入口 (Windows)，输入写入 `controller_data`
 /// 返回 true 表示匹配到设备并成功轮询，false 表示 XInput 未找到匹配设备
 #[cfg(target_os = "windows")]
-pub fn poll_xbox_controller(_device: &DeviceInfo) -> bool {
+pub fn poll_xbox_controller(_device: &DeviceInfo, controller_data: &mut ControllerDatas) -> bool {
     let xinput = get_xinput();
     let mut got_device = false;
 
+    let active_slot = slots::active_slot();
+    let bound_index = *XINPUT_USER_INDEX.read().unwrap();
+    let claimed: Vec<usize> = (0..MAX_SLOTS)
+        .filter(|slot| *slot != active_slot)
+        .filter_map(|slot| *XINPUT_USER_INDEX.slot(slot).read().unwrap())
+        .collect();
+
     for i in 0..MAX_XINPUT_DEVICES {
+        if claimed.contains(&i) || bound_index.is_some_and(|b| b != i) {
+            continue;
+        }
         match xinput
             .get_state_ex(i as u32)
             .or_else(|_| xinput.get_state(i as u32))
@@ -147,7 +168,11 @@ pub fn poll_xbox_controller(_device: &DeviceInfo) -> bool {
                     && (pid.eq_ignore_ascii_case(expected_pid) || pid.eq_ignore_ascii_case(d_sub_pid))
                 {
                     got_device = true;
-                    _poll_xbox_controller_state(state);
+                    *XINPUT_USER_INDEX.write().unwrap() = Some(i);
+                    _poll_xbox_controller_state(state, controller_data);
+                    if power::query_due() {
+                        update_xinput_power(i);
+                    }
                     break;
                 } else {
                     // GIP 控制器在 XInput 层呈现为 045e:02ff，与 WGI 层 VID 不同是预期行为。
@@ -174,6 +199,7 @@ pub fn poll_xbox_controller(_device: &DeviceInfo) -> bool {
     }
 
     if !got_device {
+        *XINPUT_USER_INDEX.write().unwrap() = None;
         static NO_MATCH_CNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
         let nc = NO_MATCH_CNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
         if nc % 125 == 0 {
@@ -183,8 +209,89 @@ pub fn poll_xbox_controller(_device: &DeviceInfo) -> bool {
     got_device
 }
 
+/// 查询 XInput 电池信息并上报 (Windows)
+#[cfg(target_os = "windows")]
+fn update_xinput_power(index: usize) {
+    let info = match get_xinput().get_gamepad_battery_information(index as u32) {
+        Ok(info) => info,
+        Err(e) => {
+            log::debug!("XInput 获取电池信息失败 (索引 {index}): {e:?}");
+            return;
+        }
+    };
+    let state = if info.battery_type == BatteryType::DISCONNECTED {
+        return;
+    } else if info.battery_type == BatteryType::WIRED {
+        PowerState { level: None, charging: false, connection: ConnectionType::Wired }
+    } else {
+        // XInput 只提供四档电量
+        let level = if info.battery_level == BatteryLevel::FULL {
+            100
+        } else if info.battery_level == BatteryLevel::MEDIUM {
+            60
+        } else if info.battery_level == BatteryLevel::LOW {
+            20
+        } else {
+            5
+        };
+        PowerState { level: Some(level), charging: false, connection: ConnectionType::Wireless }
+    };
+    power::update(state);
+}
+
+/// 设置当前槽位 XInput 手柄的马达强度（0.0-1.0） (Windows)
+/// 返回 false 表示槽位未绑定 XInput 用户索引（调用方应回退 gilrs）
+#[cfg(target_os = "windows")]
+pub fn set_rumble(strong: f32, weak: f32) -> bool {
+    let Some(index) = *XINPUT_USER_INDEX.read().unwrap() else {
+        return false;
+    };
+    let left = (strong.clamp(0.0, 1.0) * u16::MAX as f32) as u16;
+    let right = (weak.clamp(0.0, 1.0) * u16::MAX as f32) as u16;
+    match get_xinput().set_state(index as u32, left, right) {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("XInput 设置震动失败 (索引 {index}): {e:?}");
+            false
+        }
+    }
+}
+
 /// Xbox控制器轮询入口 (Linux)
+/// Linux 下没有 XInput，xpad/xpadneo 驱动暴露标准 evdev 手柄，直接走 evdev 后端。
+/// 返回 true 表示 evdev 成功轮询，false 表示调用方应回退 gilrs
 #[cfg(target_os = "linux")]
-pub fn poll_xbox_controller(_device: &DeviceInfo) {
-    println!("poll_xbox_controllers");
+pub fn poll_xbox_controller(device: &DeviceInfo, controller_data: &mut ControllerDatas) -> bool {
+    crate::controller::evdev_input::poll_evdev_controller(device, controller_data)
+}
+
+/// XInput 输入后端 (Windows)：未匹配到 XInput 设备时回退后续后端（gilrs）
+#[cfg(target_os = "windows")]
+pub struct XInputBackend;
+
+#[cfg(target_os = "windows")]
+impl InputBackend for XInputBackend {
+    fn name(&self) -> &'static str {
+        "XInput"
+    }
+
+    fn supports(&self, device: &DeviceInfo) -> bool {
+        device.controller_type == ControllerType::Xbox && !scripted::is_scripted(device)
+    }
+
+    fn poll(&self, device: &DeviceInfo, datas: &mut ControllerDatas) -> bool {
+        if poll_xbox_controller(device, datas) {
+            return true;
+        }
+        static FALLBACK_CNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let fc = FALLBACK_CNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        if fc % 125 == 0 {
+            log::debug!("XInput 未匹配，回退 gilrs 轮询");
+        }
+        false
+    }
+
+    fn close(&self) {
+        *XINPUT_USER_INDEX.write().unwrap() = None;
+    }
 }
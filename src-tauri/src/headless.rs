@@ -0,0 +1,249 @@
+//! 无界面（守护进程）模式
+//!
+//! 不创建 Tauri 窗口，直接启动设置、预设、映射与手柄监听，适合从不打开界面的机器。
+//! 核心引擎与界面模式完全相同（见 `setup::initialize`），只是没有前端接收事件。
+//!
+//! 命令行：
+//! ```text
+//! XialloControl --headless [--preset <预设名>] [--device <设备名>]... [--verbose]
+//! XialloControl --list-devices
+//! XialloControl --list-presets
+//! ```
+//! `--device` 可重复，依次绑定到槽位 0、1、2……；设备尚未插入时会持续等待。
+//! 未指定 `--device` 时按“记住上次连接”设置自动连接。
+
+use crate::app_state::AppState;
+use crate::controller::slots::MAX_SLOTS;
+use crate::{controller, preset, setting, setup};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri_plugin_log::fern;
+
+pub const USAGE: &str = "\
+用法:
+  XialloControl                          启动图形界面
+  XialloControl --headless [选项]        无界面运行映射引擎
+  XialloControl --list-devices           列出可用手柄后退出
+  XialloControl --list-presets           列出预设后退出
+
+选项:
+  --preset <名称>    使用指定预设（默认为上次使用的预设）
+  --device <名称>    连接指定手柄，可重复（依次绑定槽位 0-3）
+  --verbose          输出调试日志
+  --help             显示本帮助";
+
+/// gilrs 初始化等待时间
+const GILRS_READY_TIMEOUT: Duration = Duration::from_secs(3);
+/// 等待指定设备插入时的重试间隔
+const DEVICE_RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeadlessCommand {
+    /// 持续运行映射引擎
+    Run,
+    ListDevices,
+    ListPresets,
+    Help,
+}
+
+#[derive(Debug, Clone)]
+pub struct HeadlessOptions {
+    pub command: HeadlessCommand,
+    pub preset: Option<String>,
+    /// 依次绑定到槽位 0、1、2……的设备名称
+    pub devices: Vec<String>,
+    pub verbose: bool,
+}
+
+impl HeadlessOptions {
+    /// 解析命令行参数（不含程序名）
+    ///
+    /// 没有任何无界面相关参数时返回 `Ok(None)`，由调用方启动图形界面；
+    /// 未识别的参数（如开机自启动附带的参数）同样忽略
+    pub fn parse(args: &[String]) -> Result<Option<Self>, String> {
+        let mut options = HeadlessOptions {
+            command: HeadlessCommand::Run,
+            preset: None,
+            devices: Vec::new(),
+            verbose: false,
+        };
+        let mut headless = false;
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--headless" => headless = true,
+                "--list-devices" => {
+                    headless = true;
+                    options.command = HeadlessCommand::ListDevices;
+                }
+                "--list-presets" => {
+                    headless = true;
+                    options.command = HeadlessCommand::ListPresets;
+                }
+                "--help" | "-h" => {
+                    headless = true;
+                    options.command = HeadlessCommand::Help;
+                }
+                "--preset" => {
+                    headless = true;
+                    let name = iter.next().ok_or("--preset 需要指定预设名称")?;
+                    options.preset = Some(name.clone());
+                }
+                "--device" => {
+                    headless = true;
+                    let name = iter.next().ok_or("--device 需要指定设备名称")?;
+                    if options.devices.len() >= MAX_SLOTS {
+                        return Err(format!("最多指定 {MAX_SLOTS} 个设备"));
+                    }
+                    options.devices.push(name.clone());
+                }
+                "--verbose" | "-v" => options.verbose = true,
+                _ => {}
+            }
+        }
+
+        Ok(headless.then_some(options))
+    }
+}
+
+/// 无界面模式的日志输出到标准输出
+fn init_logger(verbose: bool) {
+    let level = if verbose {
+        log::LevelFilter::Debug
+    } else {
+        log::LevelFilter::Info
+    };
+    let result = fern::Dispatch::new()
+        .format(|out, message, record| {
+            out.finish(format_args!("[{}][{}] {}", record.level(), record.target(), message))
+        })
+        .level(level)
+        .chain(std::io::stdout())
+        .apply();
+    if let Err(e) = result {
+        eprintln!("初始化日志失败: {e}");
+    }
+}
+
+/// Windows release 版本没有控制台，附加到启动它的终端以便输出
+#[cfg(target_os = "windows")]
+fn attach_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+fn list_devices() {
+    setting::initialize();
+    controller::gilrs_listen();
+    if !controller::wait_gilrs_ready(GILRS_READY_TIMEOUT) {
+        log::warn!("gilrs 初始化超时，设备列表可能不完整");
+    }
+
+    let devices = controller::query_devices();
+    if devices.is_empty() {
+        println!("未检测到手柄");
+        return;
+    }
+    for device in devices {
+        println!(
+            "{}\t{}:{}\t{:?}",
+            device.name,
+            device.vendor_id,
+            device.product_id.as_deref().unwrap_or("----"),
+            device.controller_type
+        );
+    }
+}
+
+fn list_presets() {
+    setting::initialize();
+    let current = setting::get_setting().previous_preset;
+    for name in preset::check_presets_list() {
+        let marker = if name == current { "*" } else { " " };
+        println!("{marker} {name}");
+    }
+}
+
+/// 依次连接指定设备，未插入的设备持续等待
+fn connect_devices(devices: &[String]) {
+    let mut pending: Vec<(usize, &String)> = devices.iter().enumerate().collect();
+    let mut waiting_logged = false;
+
+    while !pending.is_empty() {
+        let available = controller::query_devices();
+        pending.retain(|(slot, name)| {
+            if !available.iter().any(|d| &d.name == *name) {
+                return true;
+            }
+            if controller::use_device((*name).clone(), Some(*slot)) {
+                log::info!("✅ [槽位 {slot}] 已连接: {name}");
+                false
+            } else {
+                true
+            }
+        });
+
+        if !pending.is_empty() {
+            if !waiting_logged {
+                let names: Vec<&str> = pending.iter().map(|(_, name)| name.as_str()).collect();
+                log::info!("等待设备插入: {}", names.join(", "));
+                waiting_logged = true;
+            }
+            thread::sleep(DEVICE_RETRY_INTERVAL);
+        }
+    }
+}
+
+/// 无界面模式入口
+pub fn run(options: HeadlessOptions) {
+    #[cfg(target_os = "windows")]
+    attach_console();
+
+    match options.command {
+        HeadlessCommand::Help => {
+            println!("{USAGE}");
+            return;
+        }
+        HeadlessCommand::ListPresets => {
+            list_presets();
+            return;
+        }
+        HeadlessCommand::ListDevices => {
+            init_logger(options.verbose);
+            list_devices();
+            return;
+        }
+        HeadlessCommand::Run => init_logger(options.verbose),
+    }
+
+    log::info!("🚀 以无界面模式启动");
+    setup::initialize(None, Arc::new(AppState::new()));
+
+    if let Some(name) = &options.preset {
+        match preset::switch_to_preset(name) {
+            Ok(_) => log::info!("使用预设: {name}"),
+            Err(e) => {
+                log::error!("切换到预设 {name} 失败: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if !controller::wait_gilrs_ready(GILRS_READY_TIMEOUT) {
+        log::warn!("gilrs 初始化超时");
+    }
+    if options.devices.is_empty() {
+        controller::try_auto_connect_last_device();
+    } else {
+        connect_devices(&options.devices);
+    }
+
+    log::info!("无界面模式运行中，按 Ctrl+C 退出");
+    loop {
+        thread::park();
+    }
+}
@@ -0,0 +1,438 @@
+//! 本地控制接口（Unix socket 上的 JSON-RPC 2.0）
+//!
+//! 外部脚本、Stream Deck、游戏启动器等可以通过它驱动 XialloControl，提供与前端相同的操作：
+//! 预设切换、设备选择、手柄数据、映射增删改查与设置。
+//!
+//! - 套接字：`$XIALLOCONTROL_SOCKET`，否则 `$XDG_RUNTIME_DIR/xiallocontrol.sock`，
+//!   否则配置目录下的 `xiallocontrol.sock`；权限 0600，仅当前用户可访问
+//! - 帧格式：每行一个 JSON 对象（换行分隔）
+//! - 事件推送：调用 `subscribe` 后，所有发给前端的事件（见 `controller::emit_event`）
+//!   以通知 `{"jsonrpc":"2.0","method":"event","params":{"event":..,"payload":..}}` 推送
+//!
+//! ```text
+//! → {"jsonrpc":"2.0","id":1,"method":"switch_to_preset","params":{"name":"FPS"}}
+//! ← {"jsonrpc":"2.0","id":1,"result":{...}}
+//! → {"jsonrpc":"2.0","id":2,"method":"subscribe","params":{"events":["preset_changed"]}}
+//! ```
+//!
+//! 推送队列满（客户端读取过慢）时丢弃事件，不会阻塞手柄监听线程。
+
+use crate::controller::slots;
+use crate::mapping::{Mapping, MappingUpdateConfig};
+use crate::setting::AppSettings;
+use crate::{controller, mapping, preset, setting};
+use once_cell::sync::Lazy;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{SyncSender, TrySendError};
+use std::sync::Mutex;
+
+/// 套接字文件名
+const SOCKET_NAME: &str = "xiallocontrol.sock";
+/// 每个客户端待发送消息的队列长度
+const CLIENT_QUEUE_SIZE: usize = 256;
+
+// JSON-RPC 错误码
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// 命令执行失败（应用自定义错误码）
+const COMMAND_FAILED: i64 = -32000;
+
+#[derive(Debug, Serialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+impl From<String> for RpcError {
+    fn from(message: String) -> Self {
+        Self::new(COMMAND_FAILED, message)
+    }
+}
+
+/// 已连接的客户端
+struct Client {
+    id: u64,
+    tx: SyncSender<String>,
+    /// None 表示未订阅；空集合表示订阅全部事件
+    subscription: Option<HashSet<String>>,
+}
+
+impl Client {
+    fn wants(&self, event: &str) -> bool {
+        self.subscription
+            .as_ref()
+            .is_some_and(|events| events.is_empty() || events.contains(event))
+    }
+}
+
+static CLIENTS: Lazy<Mutex<Vec<Client>>> = Lazy::new(|| Mutex::new(Vec::new()));
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+/// 是否有客户端订阅了事件（避免无订阅时序列化高频的手柄数据事件）
+static HAS_SUBSCRIBERS: AtomicBool = AtomicBool::new(false);
+
+fn update_has_subscribers(clients: &[Client]) {
+    HAS_SUBSCRIBERS.store(
+        clients.iter().any(|c| c.subscription.is_some()),
+        Ordering::Relaxed,
+    );
+}
+
+/// 向订阅了该事件的客户端推送通知
+pub fn broadcast<S: Serialize>(event: &str, payload: &S) {
+    if !HAS_SUBSCRIBERS.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut clients = CLIENTS.lock().unwrap();
+    if !clients.iter().any(|c| c.wants(event)) {
+        return;
+    }
+    let message = json!({
+        "jsonrpc": "2.0",
+        "method": "event",
+        "params": { "event": event, "payload": payload },
+    })
+    .to_string();
+
+    clients.retain(|client| {
+        if !client.wants(event) {
+            return true;
+        }
+        match client.tx.try_send(message.clone()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                static DROP_CNT: AtomicUsize = AtomicUsize::new(0);
+                let cnt = DROP_CNT.fetch_add(1, Ordering::Relaxed);
+                if cnt % 125 == 0 {
+                    log::warn!("本地控制客户端 {} 读取过慢，丢弃事件 {event}", client.id);
+                }
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    });
+    update_has_subscribers(&clients);
+}
+
+fn set_subscription(client_id: u64, subscription: Option<HashSet<String>>) {
+    let mut clients = CLIENTS.lock().unwrap();
+    if let Some(client) = clients.iter_mut().find(|c| c.id == client_id) {
+        client.subscription = subscription;
+    }
+    update_has_subscribers(&clients);
+}
+
+fn register_client(tx: SyncSender<String>) -> u64 {
+    let id = NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed);
+    CLIENTS.lock().unwrap().push(Client { id, tx, subscription: None });
+    id
+}
+
+fn unregister_client(client_id: u64) {
+    let mut clients = CLIENTS.lock().unwrap();
+    clients.retain(|c| c.id != client_id);
+    update_has_subscribers(&clients);
+}
+
+// --- 方法分发 ---
+
+/// 解析参数，缺省参数按空对象处理
+fn parse<P: DeserializeOwned>(params: Value) -> Result<P, RpcError> {
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, format!("参数错误: {e}")))
+}
+
+fn to_value<T: Serialize>(value: T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|e| RpcError::new(COMMAND_FAILED, e.to_string()))
+}
+
+#[derive(Deserialize)]
+struct SlotParams {
+    #[serde(default)]
+    slot: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct SubscribeParams {
+    #[serde(default)]
+    events: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct PresetParams {
+    name: String,
+    #[serde(default)]
+    slot: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct DeviceParams {
+    device_name: String,
+    #[serde(default)]
+    slot: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct MappingIdParams {
+    id: u64,
+    #[serde(default)]
+    slot: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct MappingConfigParams {
+    config: MappingUpdateConfig,
+    #[serde(default)]
+    slot: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct MappingsParams {
+    mappings: Vec<Mapping>,
+    #[serde(default)]
+    slot: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct SettingsParams {
+    new_settings: AppSettings,
+}
+
+/// 在指定槽位（缺省为主槽位）上执行
+fn in_slot<R>(slot: Option<usize>, f: impl FnOnce() -> R) -> Result<R, RpcError> {
+    let slot = slots::resolve_slot(slot)?;
+    Ok(slots::with_slot(slot, f))
+}
+
+fn dispatch(client_id: u64, method: &str, params: Value) -> Result<Value, RpcError> {
+    match method {
+        "subscribe" => {
+            let p: SubscribeParams = parse(params)?;
+            set_subscription(client_id, Some(p.events.into_iter().collect()));
+            to_value(true)
+        }
+        "unsubscribe" => {
+            set_subscription(client_id, None);
+            to_value(true)
+        }
+
+        // 设备
+        "query_devices" => to_value(controller::query_devices()),
+        "list_device_slots" => to_value(controller::list_device_slots()),
+        "use_device" => {
+            let p: DeviceParams = parse(params)?;
+            to_value(controller::use_device(p.device_name, p.slot))
+        }
+        "disconnect_device" => {
+            let p: SlotParams = parse(params)?;
+            to_value(controller::disconnect_device(p.slot))
+        }
+        "get_controller_data" => {
+            let p: SlotParams = parse(params)?;
+            to_value(controller::get_controller_data(p.slot))
+        }
+
+        // 预设
+        "check_presets_list" => to_value(preset::check_presets_list()),
+        "get_current_preset" => {
+            let p: SlotParams = parse(params)?;
+            to_value(in_slot(p.slot, preset::get_current_preset)?)
+        }
+        "switch_to_preset" => {
+            let p: PresetParams = parse(params)?;
+            to_value(in_slot(p.slot, || preset::switch_to_preset(&p.name))??)
+        }
+
+        // 映射
+        "get_mappings" => {
+            let p: SlotParams = parse(params)?;
+            to_value(in_slot(p.slot, mapping::get_mappings)?)
+        }
+        "get_mapping_by_id" => {
+            let p: MappingIdParams = parse(params)?;
+            to_value(in_slot(p.slot, || mapping::get_mapping_by_id(p.id))?)
+        }
+        "add_mapping" => {
+            let p: MappingConfigParams = parse(params)?;
+            to_value(in_slot(p.slot, || mapping::add_mapping(p.config))?)
+        }
+        "update_mapping" => {
+            let p: MappingConfigParams = parse(params)?;
+            to_value(in_slot(p.slot, || mapping::update_mapping(p.config))?)
+        }
+        "delete_mapping" => {
+            let p: MappingIdParams = parse(params)?;
+            let deleted = in_slot(p.slot, || {
+                tauri::async_runtime::block_on(mapping::delete_mapping(p.id))
+            })?;
+            to_value(deleted)
+        }
+        "update_mappings_order" => {
+            let p: MappingsParams = parse(params)?;
+            to_value(in_slot(p.slot, || mapping::update_mappings_order(p.mappings))?)
+        }
+
+        // 设置
+        "get_current_settings" => to_value(setting::get_setting()),
+        "update_settings" => {
+            let p: SettingsParams = parse(params)?;
+            tauri::async_runtime::block_on(setting::apply_settings(
+                controller::try_get_app_handle(),
+                p.new_settings,
+            ))?;
+            to_value(true)
+        }
+
+        _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("未知方法: {method}"))),
+    }
+}
+
+/// 处理一行请求，返回应答（通知请求没有应答）
+fn handle_line(client_id: u64, line: &str) -> Option<String> {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(v) => v,
+        Err(e) => {
+            return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, e.to_string())));
+        }
+    };
+    let id = request.get("id").cloned();
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return Some(error_response(
+            id.unwrap_or(Value::Null),
+            RpcError::new(INVALID_REQUEST, "缺少 method"),
+        ));
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    log::debug!("本地控制请求: {method}");
+    let result = dispatch(client_id, method, params);
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }).to_string(),
+        Err(e) => error_response(id, e),
+    })
+}
+
+fn error_response(id: Value, error: RpcError) -> String {
+    json!({ "jsonrpc": "2.0", "id": id, "error": error }).to_string()
+}
+
+/// 套接字路径
+pub fn socket_path() -> PathBuf {
+    if let Some(path) = std::env::var_os("XIALLOCONTROL_SOCKET") {
+        return PathBuf::from(path);
+    }
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_dir())
+        .map(|dir| dir.join(SOCKET_NAME))
+        .unwrap_or_else(|| crate::xeno_utils::get_config_path(SOCKET_NAME))
+}
+
+#[cfg(unix)]
+mod server {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::sync::mpsc::{sync_channel, Receiver};
+    use std::thread;
+
+    fn writer_loop(mut stream: UnixStream, rx: Receiver<String>) {
+        for message in rx {
+            if writeln!(stream, "{message}").is_err() {
+                break;
+            }
+        }
+    }
+
+    fn handle_connection(stream: UnixStream) {
+        let writer = match stream.try_clone() {
+            Ok(s) => s,
+            Err(e) => {
+                log::error!("本地控制连接初始化失败: {e}");
+                return;
+            }
+        };
+        let (tx, rx) = sync_channel::<String>(CLIENT_QUEUE_SIZE);
+        thread::spawn(move || writer_loop(writer, rx));
+
+        let client_id = register_client(tx.clone());
+        log::info!("本地控制客户端 {client_id} 已连接");
+
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = handle_line(client_id, &line) {
+                if tx.send(response).is_err() {
+                    break;
+                }
+            }
+        }
+
+        unregister_client(client_id);
+        log::info!("本地控制客户端 {client_id} 已断开");
+    }
+
+    pub fn start() {
+        let path = socket_path();
+        if path.exists() {
+            // 套接字仍可连接说明已有实例在运行（如界面与无界面模式同时启动）
+            if UnixStream::connect(&path).is_ok() {
+                log::warn!("本地控制接口已被其他实例占用: {path:?}");
+                return;
+            }
+            if let Err(e) = std::fs::remove_file(&path) {
+                log::error!("清理残留的套接字失败 ({path:?}): {e}");
+                return;
+            }
+        }
+
+        let listener = match UnixListener::bind(&path) {
+            Ok(l) => l,
+            Err(e) => {
+                log::error!("创建本地控制接口失败 ({path:?}): {e}");
+                return;
+            }
+        };
+        if let Err(e) = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)) {
+            log::warn!("设置套接字权限失败: {e}");
+        }
+        log::info!("🔌 本地控制接口已启动: {path:?}");
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        thread::spawn(move || handle_connection(stream));
+                    }
+                    Err(e) => log::error!("接受本地控制连接失败: {e}"),
+                }
+            }
+        });
+    }
+}
+
+/// 启动本地控制接口
+pub fn initialize() {
+    #[cfg(unix)]
+    server::start();
+    #[cfg(not(unix))]
+    log::info!("当前平台不支持 Unix socket，本地控制接口未启动");
+}
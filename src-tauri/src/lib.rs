@@ -28,7 +28,10 @@ use url::Url;
 mod adaptive_sampler;
 mod app_state;
 mod controller;
+mod headless;
+mod ipc;
 mod mapping;
+mod overlay;
 mod preset;
 mod setting;
 mod setup;
@@ -282,6 +285,18 @@ async fn perform_update(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// 程序入口：命令行带有无界面参数时以无界面模式运行（见 [`headless`]），否则启动图形界面
+pub fn run_with_args(args: Vec<String>) {
+    match headless::HeadlessOptions::parse(&args) {
+        Ok(Some(options)) => headless::run(options),
+        Ok(None) => run(),
+        Err(e) => {
+            eprintln!("{e}\n\n{}", headless::USAGE);
+            std::process::exit(2);
+        }
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -332,9 +347,21 @@ pub fn run() {
             controller::use_device,
             controller::disconnect_device,
             controller::physical_disconnect_device,
+            controller::list_device_slots,
             controller::set_frequency,
             controller::get_controller_data,
             controller::try_auto_connect_last_device,
+            controller::need_rumble,
+            controller::button_learning::start_button_learning,
+            controller::button_learning::learn_button,
+            controller::button_learning::learn_axis,
+            controller::button_learning::stop_button_learning,
+            controller::power::get_controller_power,
+            controller::recorder::start_recording,
+            controller::recorder::stop_recording,
+            controller::recorder::list_recordings,
+            controller::recorder::start_replay,
+            controller::recorder::stop_replay,
             controller::calibrate::get_calibration_state,
             controller::calibrate::start_stick_calibration,
             controller::calibrate::next_stick_calibration_step,
@@ -352,6 +379,7 @@ pub fn run() {
             mapping::delete_mapping,
             mapping::refresh_mappings,
             mapping::update_mappings_order,
+            mapping::output::take_recorded_output,
             preset::load_preset,
             preset::update_deadzone,
             preset::check_presets_list,
@@ -362,7 +390,15 @@ pub fn run() {
             preset::update_preset_items,
             preset::update_stick_as_mouse,
             preset::update_stick_rotation_threshold,
+            preset::update_stick_direction,
+            preset::update_stick_scroll,
+            preset::update_stick_curve,
+            preset::update_deadzone_shaping,
             preset::update_mouse_move_speed,
+            preset::update_gyro_mouse,
+            preset::update_preset_led,
+            preset::update_output_sink,
+            preset::switch_slot_preset,
             preset::create_example_presets,
         ])
         .setup(|app| {
@@ -370,7 +406,7 @@ pub fn run() {
             let app_state = Arc::new(AppState::new());
 
             // 在创建窗口前，先初始化设置
-            setup::initialize(app_handle.clone(), app_state.clone());
+            setup::initialize(Some(app_handle.clone()), app_state.clone());
 
             app.manage(AppStateWrapper { app_state });
 
@@ -3,7 +3,9 @@
 
 pub mod adaptive_sampler;
 pub mod controller;
+pub mod ipc;
 pub mod mapping;
+pub mod overlay;
 pub mod preset;
 pub mod setting;
 pub mod setup;
@@ -56,7 +58,7 @@ pub mod app_state;
 fn main() {
     // simple_logger::init_with_level(log::Level::Debug).unwrap();
     xeno_utils::initialize();
-    xiallocontrol_lib::run();
+    xiallocontrol_lib::run_with_args(std::env::args().skip(1).collect());
 
     // show_virtual_keyboard();
 
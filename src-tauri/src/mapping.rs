@@ -1,18 +1,27 @@
 #![allow(dead_code)]
 
+pub mod analog;
+pub mod output;
+pub mod stick_direction;
+pub mod stick_scroll;
+pub mod virtual_pad;
+
 // --- 依赖项和常量 ฅ^•ﻌ•^ฅ ---
 use crate::controller::datas::{ControllerButtons, ControllerDatas, JoystickRotation};
-use crate::controller::{CURRENT_DEVICE, ControllerType};
+use crate::controller::{rumble, CURRENT_DEVICE, ControllerType};
+use crate::controller::slots::{self, PerSlot};
 use crate::{mapping, preset};
 use crate::xeno_utils;
-use enigo::{Enigo, Keyboard, Mouse};
+use crate::mapping::analog::AnalogTrigger;
+use crate::mapping::output::OutputSink;
+use crate::mapping::virtual_pad::{PadAxis, PadButton, PadTarget};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::process::Command;
 use std::sync::Mutex;
-use std::sync::mpsc::{Receiver, Sender, channel};
+use std::sync::mpsc::{Receiver, SendError, Sender, channel};
 use std::sync::{Arc, RwLock};
 use std::thread;
 use std::time::{Instant, SystemTime, UNIX_EPOCH};
@@ -196,6 +205,10 @@ pub struct Mapping {
 
     #[serde(default)]
     mapping_tag: Option<MappingTag>,
+
+    /// 扳机模拟量模式（仅 LT / RT 映射有效），None 时按触发阈值当作普通按键
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    analog: Option<AnalogTrigger>,
 }
 
 impl Mapping {
@@ -211,6 +224,7 @@ impl Mapping {
             action: Action::default(),
             trigger_state: TriggerState::default(),
             mapping_tag: None,
+            analog: None,
         }
     }
 
@@ -238,11 +252,36 @@ struct MappingFile {
 
 // --- 操作指令相关定义 (๑>؂<๑)۶ ---
 
-// 定义可执行trait
+// 定义可执行trait，键鼠输出写入 `OutputSink`
 pub trait Executable {
-    fn execute(&self, enigo: &mut Enigo);
-    fn execute_press(&self, enigo: &mut Enigo);
-    fn execute_release(&self, enigo: &mut Enigo);
+    fn execute(&self, sink: &mut dyn OutputSink);
+    fn execute_press(&self, sink: &mut dyn OutputSink);
+    fn execute_release(&self, sink: &mut dyn OutputSink);
+}
+
+/// 输出失败只记录日志，不中断映射执行
+fn log_output_error(result: Result<(), String>, what: &str) {
+    if let Err(e) = result {
+        log::error!("{what}失败: {e}");
+    }
+}
+
+/// 两次处理之间的最大时间间隔（秒），避免暂停后一次性滚动过多
+const MAX_FRAME_SECONDS: f32 = 0.1;
+
+/// 记录本次处理时刻，返回距上次处理经过的秒数（首次处理为 0，最多 [`MAX_FRAME_SECONDS`]）
+fn frame_seconds(last_update: &mut Option<Instant>, now: Instant) -> f32 {
+    last_update
+        .replace(now)
+        .map(|last| now.duration_since(last).as_secs_f32().min(MAX_FRAME_SECONDS))
+        .unwrap_or(0.0)
+}
+
+/// 与 [`frame_seconds`] 相同，但首帧或间隔达到 [`MAX_FRAME_SECONDS`]（长时间未轮询）时返回 None，
+/// 供按位移积分的陀螺仪、触摸板跳过这一帧，避免跳变
+fn continuous_frame_seconds(last_update: &mut Option<Instant>, now: Instant) -> Option<f32> {
+    let dt = frame_seconds(last_update, now);
+    (dt > 0.0 && dt < MAX_FRAME_SECONDS).then_some(dt)
 }
 
 /// 主要操作类型，代表一个具体的键盘按键、鼠标点击或滚轮事件。
@@ -256,8 +295,18 @@ pub enum PrimaryAction {
     },
     /// 点击一个鼠标按钮。
     MouseClick { button: enigo::Button },
-    /// 滚动鼠标滚轮。
-    MouseWheel { amount: i32 },
+    /// 滚动鼠标滚轮，`horizontal` 为真时横向滚动（正值向右）。
+    MouseWheel {
+        amount: i32,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        horizontal: bool,
+    },
+    /// 按下虚拟手柄按键（Linux uinput）。
+    VirtualPadButton { pad_button: PadButton },
+    /// 将虚拟手柄的摇杆/扳机推到指定值，释放后恢复透传。
+    VirtualPadAxis { pad_axis: PadAxis, pad_value: f32 },
+    /// 让手柄震动（强度 0.0-1.0），用于宏触发、开关切换等的触感确认。
+    Rumble { strength: f32, duration_ms: u32 },
     /// 打开虚拟键盘。
     VirtualKeyboard {
         /// 标记字段，用于序列化和反序列化
@@ -273,22 +322,27 @@ pub enum PrimaryAction {
 }
 
 impl Executable for PrimaryAction {
-    fn execute(&self, enigo: &mut Enigo) {
+    fn execute(&self, sink: &mut dyn OutputSink) {
         match self {
             PrimaryAction::KeyPress { key } => {
-                enigo
-                    .key(*key, enigo::Direction::Click)
-                    .expect("Failed to press key"); // 按下并释放
+                log_output_error(sink.key(*key, enigo::Direction::Click), "按下按键"); // 按下并释放
             }
             PrimaryAction::MouseClick { button } => {
-                enigo
-                    .button(*button, enigo::Direction::Click)
-                    .expect("Failed to clicked mouse button");
+                log_output_error(sink.button(*button, enigo::Direction::Click), "点击鼠标按键");
+            }
+            PrimaryAction::MouseWheel { amount, horizontal } => {
+                log_output_error(sink.scroll(*amount, wheel_axis(*horizontal)), "滚动鼠标滚轮");
+            }
+            PrimaryAction::VirtualPadButton { pad_button } => {
+                virtual_pad::set_button(*pad_button, true);
+                virtual_pad::set_button(*pad_button, false);
             }
-            PrimaryAction::MouseWheel { amount } => {
-                enigo
-                    .scroll(*amount, enigo::Axis::Vertical)
-                    .expect("Failed to scroll mouse weight");
+            PrimaryAction::VirtualPadAxis { pad_axis, pad_value } => {
+                virtual_pad::set_axis_override(*pad_axis, *pad_value, true);
+                virtual_pad::set_axis_override(*pad_axis, *pad_value, false);
+            }
+            PrimaryAction::Rumble { strength, duration_ms } => {
+                rumble::rumble(*strength, *strength, *duration_ms);
             }
             PrimaryAction::VirtualKeyboard { .. } => {
                 // 切换虚拟键盘状态（打开/关闭）
@@ -300,23 +354,27 @@ impl Executable for PrimaryAction {
         }
     }
 
-    fn execute_press(&self, enigo: &mut Enigo) {
+    fn execute_press(&self, sink: &mut dyn OutputSink) {
         match self {
             PrimaryAction::KeyPress { key } => {
-                enigo
-                    .key(*key, enigo::Direction::Press)
-                    .expect("Failed to press key");
+                log_output_error(sink.key(*key, enigo::Direction::Press), "按下按键");
             }
             PrimaryAction::MouseClick { button } => {
-                enigo
-                    .button(*button, enigo::Direction::Press)
-                    .expect("Failed to press mouse button");
+                log_output_error(sink.button(*button, enigo::Direction::Press), "按下鼠标按键");
             }
-            PrimaryAction::MouseWheel { amount } => {
+            PrimaryAction::MouseWheel { amount, horizontal } => {
                 // 滚轮没有按下和释放的概念，直接执行滚动
-                enigo
-                    .scroll(*amount, enigo::Axis::Vertical)
-                    .expect("Failed to scroll mouse weight");
+                log_output_error(sink.scroll(*amount, wheel_axis(*horizontal)), "滚动鼠标滚轮");
+            }
+            PrimaryAction::VirtualPadButton { pad_button } => {
+                virtual_pad::set_button(*pad_button, true);
+            }
+            PrimaryAction::VirtualPadAxis { pad_axis, pad_value } => {
+                virtual_pad::set_axis_override(*pad_axis, *pad_value, true);
+            }
+            PrimaryAction::Rumble { strength, duration_ms } => {
+                // 震动按时长自动停止，按下时触发即可
+                rumble::rumble(*strength, *strength, *duration_ms);
             }
             PrimaryAction::VirtualKeyboard { .. } => {
                 // 虚拟键盘没有按下和释放的概念，直接执行切换操作
@@ -328,21 +386,26 @@ impl Executable for PrimaryAction {
         }
     }
 
-    fn execute_release(&self, enigo: &mut Enigo) {
+    fn execute_release(&self, sink: &mut dyn OutputSink) {
         match self {
             PrimaryAction::KeyPress { key } => {
-                enigo
-                    .key(*key, enigo::Direction::Release)
-                    .expect("Failed to release key");
+                log_output_error(sink.key(*key, enigo::Direction::Release), "释放按键");
             }
             PrimaryAction::MouseClick { button } => {
-                enigo
-                    .button(*button, enigo::Direction::Release)
-                    .expect("Failed to release mouse button");
+                log_output_error(sink.button(*button, enigo::Direction::Release), "释放鼠标按键");
             }
-            PrimaryAction::MouseWheel { amount: _amount } => {
+            PrimaryAction::MouseWheel { .. } => {
                 // 滚轮没有按下和释放的概念，不做任何操作
             }
+            PrimaryAction::VirtualPadButton { pad_button } => {
+                virtual_pad::set_button(*pad_button, false);
+            }
+            PrimaryAction::VirtualPadAxis { pad_axis, pad_value } => {
+                virtual_pad::set_axis_override(*pad_axis, *pad_value, false);
+            }
+            PrimaryAction::Rumble { .. } => {
+                // 震动按时长自动停止，释放时不做任何操作
+            }
             PrimaryAction::VirtualKeyboard { .. } => {
                 // 虚拟键盘没有按下和释放的概念，不做任何操作
             }
@@ -353,6 +416,14 @@ impl Executable for PrimaryAction {
     }
 }
 
+fn wheel_axis(horizontal: bool) -> enigo::Axis {
+    if horizontal {
+        enigo::Axis::Horizontal
+    } else {
+        enigo::Axis::Vertical
+    }
+}
+
 /// 完整的操作指令，包含修饰键和主要操作。
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub struct Action {
@@ -376,46 +447,38 @@ impl Default for Action {
 }
 
 impl Executable for Action {
-    fn execute(&self, enigo: &mut Enigo) {
+    fn execute(&self, sink: &mut dyn OutputSink) {
         // 1. 按下所有修饰键
         for modifier in &self.modifiers {
-            enigo
-                .key(*modifier, enigo::Direction::Press)
-                .expect("Failed to press modifier key");
+            log_output_error(sink.key(*modifier, enigo::Direction::Press), "按下修饰键");
         }
 
         // 2. 执行主操作
-        self.primary.execute(enigo);
+        self.primary.execute(sink);
 
         // 3. 释放所有修饰键 (以相反顺序)
         for modifier in self.modifiers.iter().rev() {
-            enigo
-                .key(*modifier, enigo::Direction::Release)
-                .expect("Failed to release modifier key");
+            log_output_error(sink.key(*modifier, enigo::Direction::Release), "释放修饰键");
         }
     }
 
-    fn execute_press(&self, enigo: &mut Enigo) {
+    fn execute_press(&self, sink: &mut dyn OutputSink) {
         // 1. 按下所有修饰键
         for modifier in &self.modifiers {
-            enigo
-                .key(*modifier, enigo::Direction::Press)
-                .expect("Failed to press modifier key");
+            log_output_error(sink.key(*modifier, enigo::Direction::Press), "按下修饰键");
         }
 
         // 2. 执行主操作的按下
-        self.primary.execute_press(enigo);
+        self.primary.execute_press(sink);
     }
 
-    fn execute_release(&self, enigo: &mut Enigo) {
+    fn execute_release(&self, sink: &mut dyn OutputSink) {
         // 1. 执行主操作的释放
-        self.primary.execute_release(enigo);
+        self.primary.execute_release(sink);
 
         // 2. 释放所有修饰键 (以相反顺序)
         for modifier in self.modifiers.iter().rev() {
-            enigo
-                .key(*modifier, enigo::Direction::Release)
-                .expect("Failed to release modifier key");
+            log_output_error(sink.key(*modifier, enigo::Direction::Release), "释放修饰键");
         }
     }
 }
@@ -456,6 +519,9 @@ pub struct MappingUpdateConfig {
     pub check_mode: Option<CheckMode>,
     pub check_mode_param: Option<u64>,
     pub mapping_tag: Option<MappingTag>,
+    /// 扳机模拟量配置，更新映射时整体替换（None 即关闭模拟量模式）
+    #[serde(default)]
+    pub analog: Option<AnalogTrigger>,
 }
 
 impl MappingUpdateConfig {
@@ -508,6 +574,12 @@ impl MappingUpdateConfig {
         self.check_mode_param = Some(param);
         self
     }
+
+    /// 设置扳机模拟量模式
+    pub fn with_analog(mut self, analog: AnalogTrigger) -> Self {
+        self.analog = Some(analog);
+        self
+    }
 }
 
 /// Enigo 命令类型，用于工作线程间通信。
@@ -523,19 +595,19 @@ pub enum EnigoCommand {
 
 // --- 全局静态变量 (づ￣ 3￣)づ ---
 
-/// 全局映射配置缓存，使用 `RwLock` 确保线程安全。
-pub static GLOBAL_MAPPING_CACHE: Lazy<RwLock<Vec<Mapping>>> = Lazy::new(|| {
-    let mappings = vec![];
-    RwLock::new(mappings)
+/// 映射配置缓存（每个槽位独立），使用 `RwLock` 确保线程安全。
+pub static GLOBAL_MAPPING_CACHE: Lazy<PerSlot<RwLock<Vec<Mapping>>>> = Lazy::new(|| {
+    PerSlot::new(|| RwLock::new(vec![]))
 });
 
 /// 副预设映射配置缓存
-pub static SUB_MAPPING_CACHE: Lazy<RwLock<Vec<Mapping>>> = Lazy::new(|| RwLock::new(vec![]));
+pub static SUB_MAPPING_CACHE: Lazy<PerSlot<RwLock<Vec<Mapping>>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(vec![])));
 
 /// TOML 配置文件名。
 const DEFAULT_MAPPINGS_FILE: &str = "mappings.toml";
-pub static MAPPING_FILE_PATH: Lazy<RwLock<PathBuf>> =
-    Lazy::new(|| RwLock::from(PathBuf::from(DEFAULT_MAPPINGS_FILE)));
+pub static MAPPING_FILE_PATH: Lazy<PerSlot<RwLock<PathBuf>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::from(PathBuf::from(DEFAULT_MAPPINGS_FILE))));
 
 /// 全局手柄按键布局映射，例如将 "Y" 映射到 `ControllerButtons::North`。
 /// 存储不同类型手柄的布局。
@@ -545,27 +617,37 @@ pub static CONTROLLER_LAYOUT_MAP: Lazy<RwLock<ControllerLayouts>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
 
 /// 动态触发状态，存储每个映射的触发状态。
-pub static DYNAMIC_TRIGGER_STATES: Lazy<RwLock<HashMap<u64, TriggerState>>> =
-    Lazy::new(|| RwLock::new(HashMap::new()));
+pub static DYNAMIC_TRIGGER_STATES: Lazy<PerSlot<RwLock<HashMap<u64, TriggerState>>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(HashMap::new())));
 
 /// 动态摇杆映射状态, 存储每个摇杆映射的实时数据.
-pub static JOYSTICK_MAPPING_STATES: Lazy<RwLock<HashMap<u64, JoystickMappingState>>> =
-    Lazy::new(|| RwLock::new(HashMap::new()));
+pub static JOYSTICK_MAPPING_STATES: Lazy<PerSlot<RwLock<HashMap<u64, JoystickMappingState>>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(HashMap::new())));
 
 /// 全局变量来跟踪虚拟键盘进程
 static VIRTUAL_KEYBOARD_PROCESS: Lazy<Mutex<Option<std::process::Child>>> =
     Lazy::new(|| Mutex::new(None));
 
 /// 动态按键检测状态，存储每个映射的按键检测状态。
-pub static BUTTON_CHECK_STATES: Lazy<RwLock<HashMap<u64, ButtonCheckState>>> =
-    Lazy::new(|| RwLock::new(HashMap::new()));
+pub static BUTTON_CHECK_STATES: Lazy<PerSlot<RwLock<HashMap<u64, ButtonCheckState>>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(HashMap::new())));
+
+/// Enigo 工作线程的发送器，发送时自动附带当前槽位，
+/// 工作线程在同一槽位上下文中执行（例如操作该槽位的虚拟手柄）。
+pub struct EnigoSender(Sender<(usize, EnigoCommand)>);
+
+impl EnigoSender {
+    pub fn send(&self, command: EnigoCommand) -> Result<(), SendError<(usize, EnigoCommand)>> {
+        self.0.send((slots::active_slot(), command))
+    }
+}
 
 /// Enigo 工作线程的发送器，用于向其发送执行命令。
-pub static ENIGO_SENDER: Lazy<Sender<EnigoCommand>> = Lazy::new(|| {
-    let (tx, rx): (Sender<EnigoCommand>, Receiver<EnigoCommand>) = channel();
+pub static ENIGO_SENDER: Lazy<EnigoSender> = Lazy::new(|| {
+    let (tx, rx): (Sender<(usize, EnigoCommand)>, Receiver<(usize, EnigoCommand)>) = channel();
     // 启动工作线程来处理 Enigo 命令
     thread::spawn(move || enigo_worker(rx));
-    tx
+    EnigoSender(tx)
 });
 
 
@@ -910,10 +992,10 @@ pub fn create_mapping_from_config(config: MappingUpdateConfig) -> Result<Mapping
         match parse_composed_key_to_action(composed_shortcut_key) {
             Ok(mut action) => {
                 // 如果是滚轮动作且自定义了 amount, 则覆盖
-                if let (PrimaryAction::MouseWheel { .. }, Some(new_amount)) =
+                if let (PrimaryAction::MouseWheel { amount, .. }, Some(new_amount)) =
                     (&mut action.primary, config.amount)
                 {
-                    action.primary = PrimaryAction::MouseWheel { amount: new_amount };
+                    *amount = new_amount;
                 }
 
                 let id = SystemTime::now()
@@ -932,6 +1014,7 @@ pub fn create_mapping_from_config(config: MappingUpdateConfig) -> Result<Mapping
                     action,
                     trigger_state: trigger_state.clone(),
                     mapping_tag: None,
+                    analog: config.analog.clone(),
                 };
 
                 Ok(new_mapping)
@@ -988,10 +1071,10 @@ pub fn update_mapping(config: MappingUpdateConfig) -> bool {
             match parse_composed_key_to_action(composed_shortcut_key) {
                 Ok(mut action) => {
                     // 如果是滚轮动作且自定义了 amount, 则覆盖
-                    if let (PrimaryAction::MouseWheel { .. }, Some(new_amount)) =
+                    if let (PrimaryAction::MouseWheel { amount, .. }, Some(new_amount)) =
                         (&mut action.primary, config.amount)
                     {
-                        action.primary = PrimaryAction::MouseWheel { amount: new_amount };
+                        *amount = new_amount;
                     }
 
                     // 更新映射的字段，只更新提供的字段
@@ -1011,6 +1094,7 @@ pub fn update_mapping(config: MappingUpdateConfig) -> bool {
                     if let Some(tag) = &config.mapping_tag {
                         mapping.mapping_tag = Some(tag.clone());
                     }
+                    mapping.analog = config.analog.clone();
                     mapping.action = action;
 
                     // 同步更新 DYNAMIC_TRIGGER_STATES 中的触发状态
@@ -1089,10 +1173,10 @@ pub fn add_mapping(config: MappingUpdateConfig) -> bool {
         match parse_composed_key_to_action(composed_shortcut_key) {
             Ok(mut action) => {
                 // 如果是滚轮动作且自定义了 amount, 则覆盖
-                if let (PrimaryAction::MouseWheel { .. }, Some(new_amount)) =
+                if let (PrimaryAction::MouseWheel { amount, .. }, Some(new_amount)) =
                     (&mut action.primary, config.amount)
                 {
-                    action.primary = PrimaryAction::MouseWheel { amount: new_amount };
+                    *amount = new_amount;
                 }
 
                 let mut cache = GLOBAL_MAPPING_CACHE.write().unwrap();
@@ -1112,6 +1196,7 @@ pub fn add_mapping(config: MappingUpdateConfig) -> bool {
                     action,
                     trigger_state: trigger_state.clone(),
                     mapping_tag: config.mapping_tag.clone(),
+                    analog: config.analog.clone(),
                 };
 
                 cache.push(new_mapping);
@@ -1229,6 +1314,11 @@ fn create_xbox_layout_map() -> HashMap<&'static str, ControllerButtons> {
     xbox_map.insert("DPadDown", ControllerButtons::Down);
     xbox_map.insert("DPadLeft", ControllerButtons::Left);
     xbox_map.insert("DPadRight", ControllerButtons::Right);
+    xbox_map.insert("Share", ControllerButtons::Capture); // Xbox Series 分享键
+    xbox_map.insert("P1", ControllerButtons::P1); // Elite 背键
+    xbox_map.insert("P2", ControllerButtons::P2);
+    xbox_map.insert("P3", ControllerButtons::P3);
+    xbox_map.insert("P4", ControllerButtons::P4);
     xbox_map
 }
 
@@ -1252,6 +1342,10 @@ fn create_playstation_layout_map() -> HashMap<&'static str, ControllerButtons> {
     ps_map.insert("DPadDown", ControllerButtons::Down);
     ps_map.insert("DPadLeft", ControllerButtons::Left);
     ps_map.insert("DPadRight", ControllerButtons::Right);
+    ps_map.insert("Touchpad", ControllerButtons::Touchpad); // 触摸板按下
+    ps_map.insert("Mute", ControllerButtons::Mute); // DualSense 麦克风静音键
+    ps_map.insert("TouchpadLeft", ControllerButtons::TouchpadLeft); // 触摸板左半区按下
+    ps_map.insert("TouchpadRight", ControllerButtons::TouchpadRight); // 触摸板右半区按下
     ps_map
 }
 
@@ -1273,6 +1367,11 @@ fn create_other_layout_map() -> HashMap<&'static str, ControllerButtons> {
     other_map.insert("DPadDown", ControllerButtons::Down);
     other_map.insert("DPadLeft", ControllerButtons::Left);
     other_map.insert("DPadRight", ControllerButtons::Right);
+    other_map.insert("Share", ControllerButtons::Capture);
+    other_map.insert("P1", ControllerButtons::P1);
+    other_map.insert("P2", ControllerButtons::P2);
+    other_map.insert("P3", ControllerButtons::P3);
+    other_map.insert("P4", ControllerButtons::P4);
     other_map
 }
 
@@ -1296,6 +1395,7 @@ fn create_switch_layout_map() -> HashMap<&'static str, ControllerButtons> {
     switch_map.insert("DPadDown", ControllerButtons::Down);
     switch_map.insert("DPadLeft", ControllerButtons::Left);
     switch_map.insert("DPadRight", ControllerButtons::Right);
+    switch_map.insert("Capture", ControllerButtons::Capture); // Switch 截图键
     switch_map
 }
 
@@ -1369,6 +1469,10 @@ fn get_function_key(num: u8) -> enigo::Key {
     }
 }
 
+/// `Rumble` 未指定参数时的默认强度与时长
+const DEFAULT_RUMBLE_STRENGTH: f32 = 0.5;
+const DEFAULT_RUMBLE_DURATION_MS: u32 = 150;
+
 /// 解析按键组合字符串，生成结构化的 `Action`。
 /// 例如 "Ctrl+Alt+A" 会被解析成一个带有 `[Control, Alt]` 修饰键和 `KeyPress { key: 'A' }` 主操作的 `Action`。
 fn parse_composed_key_to_action(composed: &str) -> Result<Action, ParseError> {
@@ -1458,11 +1562,20 @@ fn parse_composed_key_to_action(composed: &str) -> Result<Action, ParseError> {
             // 主操作 - 鼠标滚轮
             "mousewheelup" => set_primary(
                 &mut primary_action,
-                PrimaryAction::MouseWheel { amount: -1 },
+                PrimaryAction::MouseWheel { amount: -1, horizontal: false },
+            )?,
+            "mousewheeldown" => set_primary(
+                &mut primary_action,
+                PrimaryAction::MouseWheel { amount: 1, horizontal: false },
+            )?,
+            "mousewheelleft" => set_primary(
+                &mut primary_action,
+                PrimaryAction::MouseWheel { amount: -1, horizontal: true },
+            )?,
+            "mousewheelright" => set_primary(
+                &mut primary_action,
+                PrimaryAction::MouseWheel { amount: 1, horizontal: true },
             )?,
-            "mousewheeldown" => {
-                set_primary(&mut primary_action, PrimaryAction::MouseWheel { amount: 1 })?
-            }
 
             "virtualkeyboard" => {
                 set_primary(
@@ -1473,6 +1586,40 @@ fn parse_composed_key_to_action(composed: &str) -> Result<Action, ParseError> {
                 )?;
             }
 
+            // 主操作 - 虚拟手柄，例如 "Pad:A"、"Pad:LT"、"Pad:LeftStickUp"
+            s if s.starts_with("pad:") => {
+                let action = match virtual_pad::parse_pad_token(&s[4..]) {
+                    Some(PadTarget::Button(pad_button)) => {
+                        PrimaryAction::VirtualPadButton { pad_button }
+                    }
+                    Some(PadTarget::Axis(pad_axis, pad_value)) => {
+                        PrimaryAction::VirtualPadAxis { pad_axis, pad_value }
+                    }
+                    None => return Err(ParseError::UnknownKey(part.clone())),
+                };
+                set_primary(&mut primary_action, action)?;
+            }
+
+            // 主操作 - 手柄震动，例如 "Rumble"、"Rumble:0.6"、"Rumble:0.6:300"
+            s if s == "rumble" || s.starts_with("rumble:") => {
+                let mut params = s.split(':').skip(1);
+                let strength = match params.next() {
+                    Some(v) => v.parse::<f32>().map_err(|_| ParseError::UnknownKey(part.clone()))?,
+                    None => DEFAULT_RUMBLE_STRENGTH,
+                };
+                let duration_ms = match params.next() {
+                    Some(v) => v.parse::<u32>().map_err(|_| ParseError::UnknownKey(part.clone()))?,
+                    None => DEFAULT_RUMBLE_DURATION_MS,
+                };
+                set_primary(
+                    &mut primary_action,
+                    PrimaryAction::Rumble {
+                        strength: strength.clamp(0.0, 1.0),
+                        duration_ms,
+                    },
+                )?;
+            }
+
             // 主操作 - 其他键盘按键
             key_str => {
                 let key = match key_str {
@@ -1504,26 +1651,28 @@ fn parse_composed_key_to_action(composed: &str) -> Result<Action, ParseError> {
 // --- 工作线程和初始化 (ﾉ´▽｀)ﾉ♪ ---
 
 /// Enigo 工作线程，接收命令并执行实际的键盘/鼠标操作。
-/// 所有 Enigo 的操作都在这个线程中完成，以避免与主线程的阻塞和冲突。
-fn enigo_worker(rx: Receiver<EnigoCommand>) {
-    let enigo = Enigo::new(&enigo::Settings::default()).unwrap();
-    *GLOBAL_ENIGO.write().unwrap() = Some(enigo);
-
-    while let Ok(command) = rx.recv() {
-        if let Some(enigo_instance) = GLOBAL_ENIGO.write().unwrap().as_mut() {
-            match command {
+/// 所有映射动作都在这个线程中完成，以避免与主线程的阻塞和冲突；
+/// 动作写入命令所属槽位当前预设选择的输出后端（见 [`output`]）。
+fn enigo_worker(rx: Receiver<(usize, EnigoCommand)>) {
+    // 提前创建默认输出后端，避免首次按键时才初始化
+    output::with_sink(output::OutputSinkKind::Enigo, |_| ());
+
+    while let Ok((slot, command)) = rx.recv() {
+        let executed = slots::with_slot(slot, || {
+            output::with_active_sink(|sink| match command {
                 EnigoCommand::Execute(action) => {
-                    action.execute(enigo_instance);
+                    action.execute(sink);
                 }
                 EnigoCommand::ExecutePress(action) => {
-                    action.execute_press(enigo_instance);
+                    action.execute_press(sink);
                 }
                 EnigoCommand::ExecuteRelease(action) => {
-                    action.execute_release(enigo_instance);
+                    action.execute_release(sink);
                 }
-            }
-        } else {
-            log::error!("Enigo 实例未初始化，无法执行操作");
+            })
+        });
+        if executed.is_none() {
+            log::error!("输出后端不可用，无法执行操作");
         }
     }
 }
@@ -1546,6 +1695,20 @@ pub fn init_enigo_sender() {
     log::debug!("ENIGO_SENDER 初始化完成");
 }
 
+/// 扳机模拟量映射的配置与当前扳机值，非 LT / RT 或未启用模拟量时返回 None
+fn analog_trigger_input<'a>(
+    controller_datas: &ControllerDatas,
+    mapping: &'a Mapping,
+) -> Option<(&'a AnalogTrigger, f32)> {
+    let analog = mapping.analog.as_ref()?;
+    let value = match mapping.composed_button.as_str() {
+        "LT" => controller_datas.left_trigger.value,
+        "RT" => controller_datas.right_trigger.value,
+        _ => return None,
+    };
+    Some((analog, value))
+}
+
 fn handle_trigger_data(controller_datas: &mut ControllerDatas, mapping: &Mapping) {
     let trigger_some = match mapping.composed_button.as_str() {
         "LT" => Some(controller_datas.left_trigger),
@@ -1611,12 +1774,20 @@ pub fn map(controller_datas: &mut ControllerDatas, use_sub_preset: bool) {
     let mut trigger_states = DYNAMIC_TRIGGER_STATES.write().unwrap();
     let mut button_check_states = BUTTON_CHECK_STATES.write().unwrap();
 
+    let stick_directions = {
+        let preset = preset::CURRENT_PRESET.read().unwrap();
+        stick_direction::update(
+            controller_datas,
+            &preset.items.stick_direction_left,
+            &preset.items.stick_direction_right,
+        )
+    };
 
     for mapping in mappings.iter() {
         let composed_button = mapping.get_composed_button();
 
-        // 检查是否为摇杆旋转映射
-        let rotation_match = match composed_button {
+        // 检查是否为摇杆旋转 / 摇杆方向映射
+        let stick_match = match composed_button {
             "LeftStickCW" => {
                 Some(controller_datas.left_stick_rotation == JoystickRotation::Clockwise)
             }
@@ -1629,16 +1800,16 @@ pub fn map(controller_datas: &mut ControllerDatas, use_sub_preset: bool) {
             "RightStickCCW" => {
                 Some(controller_datas.right_stick_rotation == JoystickRotation::CounterClockwise)
             }
-            _ => None,
+            _ => stick_directions.is_pressed(composed_button),
         };
 
-        if let Some(is_rotating) = rotation_match {
-            // --- 处理摇杆旋转映射 (虚拟按键) ---
+        if let Some(is_active) = stick_match {
+            // --- 处理摇杆旋转 / 方向映射 (虚拟按键) ---
             let trigger_state = trigger_states
                 .entry(mapping.get_id())
                 .or_insert_with(|| mapping.trigger_state.clone());
 
-            if trigger_state.should_trigger(is_rotating) {
+            if trigger_state.should_trigger(is_active) {
                 if trigger_state.continually_trigger {
                     // 连续触发模式：使用原有的 Execute 命令
                     ENIGO_SENDER
@@ -1656,10 +1827,19 @@ pub fn map(controller_datas: &mut ControllerDatas, use_sub_preset: bool) {
                             .unwrap();
                     }
                 }
-            } else if !is_rotating && trigger_state.continually_trigger {
-                // 连续触发模式下，如果摇杆停止旋转，重置触发状态
+            } else if !is_active && trigger_state.continually_trigger {
+                // 连续触发模式下，如果摇杆停止旋转或离开该方向，重置触发状态
                 trigger_state.reset();
             }
+        } else if let Some((analog, value)) = analog_trigger_input(controller_datas, mapping) {
+            // --- 扳机模拟量映射：按深度连续输出，不经过按键检测 ---
+            analog::handle(
+                mapping.get_id(),
+                analog,
+                value,
+                mapping.trigger_theshold,
+                &mapping.action,
+            );
         } else if let Some(button) = layout_map.get(composed_button) {
             handle_trigger_data(controller_datas, mapping);
 
@@ -1744,10 +1924,8 @@ impl Default for MouseMovementState {
     }
 }
 
-static MOUSE_MOVEMENT_STATE: Lazy<RwLock<MouseMovementState>> =
-    Lazy::new(|| RwLock::new(MouseMovementState::default()));
-
-static GLOBAL_ENIGO: Lazy<RwLock<Option<Enigo>>> = Lazy::new(|| RwLock::new(None));
+static MOUSE_MOVEMENT_STATE: Lazy<PerSlot<RwLock<MouseMovementState>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(MouseMovementState::default())));
 
 pub fn handle_mouse_movement(controller_datas: &ControllerDatas) {
     let (move_speed, stick_as_mouse_simulation) = {
@@ -1768,7 +1946,10 @@ pub fn handle_mouse_movement(controller_datas: &ControllerDatas) {
                 controller_datas.right_stick.x,
                 controller_datas.right_stick.y,
             ),
-            "touchpad" => (0.0, 0.0), // TODO: 触摸板模拟
+            "touchpad" => {
+                handle_touchpad_mouse(controller_datas, move_speed);
+                return;
+            }
             _ => {
                 return;
             }
@@ -1784,8 +1965,10 @@ pub fn handle_mouse_movement(controller_datas: &ControllerDatas) {
         let mut state = MOUSE_MOVEMENT_STATE.write().unwrap();
 
         // 直接在状态上进行累积计算
-        state.move_x_remainder += stick_x * (move_speed as f32);
-        state.move_y_remainder += -stick_y * (move_speed as f32); // Y轴反转，以匹配屏幕坐标
+        // 扳机模拟量映射可按深度调节速度
+        let speed = move_speed as f32 * analog::mouse_speed_scale();
+        state.move_x_remainder += stick_x * speed;
+        state.move_y_remainder += -stick_y * speed; // Y轴反转，以匹配屏幕坐标
 
         // 取出整数部分进行移动
         let move_x = state.move_x_remainder.trunc();
@@ -1799,13 +1982,303 @@ pub fn handle_mouse_movement(controller_datas: &ControllerDatas) {
     };
 
     if move_x != 0.0 || move_y != 0.0 {
-        let mut enigo = GLOBAL_ENIGO.write().unwrap();
-        if let Some(enigo_instance) = enigo.as_mut() {
-            enigo_instance
-                .move_mouse(move_x as i32, move_y as i32, enigo::Coordinate::Rel)
-                .expect("error");
-        } else {
-            log::error!("enigo error")
+        output::with_active_sink(|sink| {
+            log_output_error(sink.move_mouse(move_x as i32, move_y as i32), "摇杆鼠标移动");
+        });
+    }
+}
+
+// --- 触摸板模拟鼠标 ---
+
+/// move_speed 为 1 时，手指划过整个触摸板宽度对应的像素数
+const TOUCHPAD_PIXELS_PER_SPEED: f32 = 40.0;
+/// 加速度系数：手指速度（触摸板宽度/秒）每增加 1，移动倍率增加的量
+const TOUCHPAD_ACCELERATION: f32 = 0.5;
+/// 加速时参考的最大手指速度，防止倍率失控
+const TOUCHPAD_MAX_VELOCITY: f32 = 4.0;
+/// 双指划过整个触摸板高度对应的滚动格数
+const TOUCHPAD_SCROLL_PER_HEIGHT: f32 = 15.0;
+/// 轻触判定：最长接触时间与最大位移（归一化）
+const TOUCHPAD_TAP_MAX_DURATION_MS: u128 = 200;
+const TOUCHPAD_TAP_MAX_DISTANCE: f32 = 0.03;
+
+#[derive(Default)]
+pub struct TouchpadMouseState {
+    move_x_remainder: f32,
+    move_y_remainder: f32,
+    scroll_x_remainder: f32,
+    scroll_y_remainder: f32,
+    /// 上一帧的 (手指 ID, x, y) 与接触指数
+    last_point: Option<(u8, f32, f32)>,
+    last_finger_count: usize,
+    last_update: Option<Instant>,
+    /// 本次接触的开始时间、累计位移、最多同时接触的手指数、是否按下过触摸板
+    touch_start: Option<Instant>,
+    travelled: f32,
+    max_fingers: usize,
+    clicked: bool,
+}
+
+impl TouchpadMouseState {
+    /// 手指全部抬起时判断是否为轻触，返回应点击的鼠标键
+    fn finish_touch(&mut self) -> Option<enigo::Button> {
+        let start = self.touch_start.take()?;
+        let is_tap = !self.clicked
+            && self.travelled < TOUCHPAD_TAP_MAX_DISTANCE
+            && start.elapsed().as_millis() <= TOUCHPAD_TAP_MAX_DURATION_MS;
+        let button = match self.max_fingers {
+            1 => enigo::Button::Left,
+            _ => enigo::Button::Right,
+        };
+        self.travelled = 0.0;
+        self.max_fingers = 0;
+        self.clicked = false;
+        is_tap.then_some(button)
+    }
+}
+
+static TOUCHPAD_MOUSE_STATE: Lazy<PerSlot<RwLock<TouchpadMouseState>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(TouchpadMouseState::default())));
+
+/// 触摸板模拟鼠标：单指移动光标（带加速），双指滚动，轻触点击（双指轻触为右键）
+fn handle_touchpad_mouse(controller_datas: &ControllerDatas, move_speed: u8) {
+    let touchpad = &controller_datas.touchpad;
+    if !touchpad.has_touchpad {
+        return;
+    }
+
+    let mut state = TOUCHPAD_MOUSE_STATE.write().unwrap();
+    let now = Instant::now();
+    let dt = continuous_frame_seconds(&mut state.last_update, now);
+
+    let finger_count = touchpad.active_count();
+    let current = touchpad.primary().map(|p| (p.id, p.x, p.y));
+    let mut click = None;
+
+    if finger_count == 0 {
+        click = state.finish_touch();
+    } else {
+        if state.touch_start.is_none() {
+            state.touch_start = Some(now);
         }
+        state.max_fingers = state.max_fingers.max(finger_count);
+        state.clicked |= controller_datas.get_button(ControllerButtons::Touchpad);
+    }
+
+    // 手指数变化、换了手指或长时间未轮询时只记录位置，不产生位移，避免跳变
+    let continuous = dt.is_some() && finger_count == state.last_finger_count;
+    let delta = match (state.last_point, current) {
+        (Some((last_id, lx, ly)), Some((id, x, y))) if continuous && last_id == id =>
+        {
+            Some((x - lx, y - ly))
+        }
+        _ => None,
+    };
+    state.last_point = current;
+    state.last_finger_count = finger_count;
+
+    let (mut move_x, mut move_y, mut scroll_x, mut scroll_y) = (0.0, 0.0, 0.0, 0.0);
+    if let Some((dx, dy)) = delta {
+        state.travelled += (dx * dx + dy * dy).sqrt();
+
+        if finger_count >= 2 {
+            // 自然滚动：手指向下划动内容向下移动（即向上滚动）
+            state.scroll_x_remainder += -dx * TOUCHPAD_SCROLL_PER_HEIGHT;
+            state.scroll_y_remainder += -dy * TOUCHPAD_SCROLL_PER_HEIGHT;
+            scroll_x = state.scroll_x_remainder.trunc();
+            scroll_y = state.scroll_y_remainder.trunc();
+            state.scroll_x_remainder -= scroll_x;
+            state.scroll_y_remainder -= scroll_y;
+        } else if !state.clicked {
+            // 触摸板原点在左上角，与屏幕坐标方向一致，无需反转
+            let velocity = dt.map_or(0.0, |dt| ((dx * dx + dy * dy).sqrt() / dt).min(TOUCHPAD_MAX_VELOCITY));
+            let gain = move_speed as f32 * TOUCHPAD_PIXELS_PER_SPEED * (1.0 + TOUCHPAD_ACCELERATION * velocity);
+            state.move_x_remainder += dx * gain;
+            state.move_y_remainder += dy * gain;
+            move_x = state.move_x_remainder.trunc();
+            move_y = state.move_y_remainder.trunc();
+            state.move_x_remainder -= move_x;
+            state.move_y_remainder -= move_y;
+        }
+    }
+    drop(state);
+
+    if move_x == 0.0 && move_y == 0.0 && scroll_x == 0.0 && scroll_y == 0.0 && click.is_none() {
+        return;
+    }
+
+    output::with_active_sink(|sink| {
+        if move_x != 0.0 || move_y != 0.0 {
+            log_output_error(sink.move_mouse(move_x as i32, move_y as i32), "触摸板鼠标移动");
+        }
+        if scroll_y != 0.0 {
+            log_output_error(sink.scroll(scroll_y as i32, enigo::Axis::Vertical), "触摸板滚动");
+        }
+        if scroll_x != 0.0 {
+            log_output_error(sink.scroll(scroll_x as i32, enigo::Axis::Horizontal), "触摸板滚动");
+        }
+        if let Some(button) = click {
+            log_output_error(sink.button(button, enigo::Direction::Click), "触摸板轻触点击");
+        }
+    });
+}
+
+// --- 陀螺仪模拟鼠标 ---
+
+/// 小幅度平滑的历史采样数
+const GYRO_SMOOTHING_WINDOW: usize = 8;
+/// 重力方向低通滤波系数（越小越平滑）
+const GRAVITY_FILTER_ALPHA: f32 = 0.05;
+/// 玩家空间偏航的放宽系数，允许手柄在倾斜时仍能获得完整的水平转向
+const PLAYER_SPACE_YAW_RELAX: f32 = 1.41;
+
+pub struct GyroMouseState {
+    move_x_remainder: f32,
+    move_y_remainder: f32,
+    /// 最近的 (偏航, 俯仰) 角速度，用于小幅度平滑
+    history: [(f32, f32); GYRO_SMOOTHING_WINDOW],
+    history_index: usize,
+    /// 低通滤波后的重力方向（手柄坐标系）
+    gravity: [f32; 3],
+    /// Toggle 模式下的启用状态
+    toggle_active: bool,
+    last_button_state: bool,
+    last_update: Option<Instant>,
+}
+
+impl Default for GyroMouseState {
+    fn default() -> Self {
+        Self {
+            move_x_remainder: 0.0,
+            move_y_remainder: 0.0,
+            history: [(0.0, 0.0); GYRO_SMOOTHING_WINDOW],
+            history_index: 0,
+            gravity: [0.0, 1.0, 0.0],
+            toggle_active: false,
+            last_button_state: false,
+            last_update: None,
+        }
+    }
+}
+
+impl GyroMouseState {
+    /// 软分层平滑：角速度低于阈值时取历史平均值抑制手抖，高于阈值时直接使用原始值
+    fn smooth(&mut self, yaw: f32, pitch: f32, threshold: f32) -> (f32, f32) {
+        self.history[self.history_index] = (yaw, pitch);
+        self.history_index = (self.history_index + 1) % GYRO_SMOOTHING_WINDOW;
+        if threshold <= 0.0 {
+            return (yaw, pitch);
+        }
+
+        let (sum_yaw, sum_pitch) = self
+            .history
+            .iter()
+            .fold((0.0, 0.0), |(sy, sp), (y, p)| (sy + y, sp + p));
+        let avg_yaw = sum_yaw / GYRO_SMOOTHING_WINDOW as f32;
+        let avg_pitch = sum_pitch / GYRO_SMOOTHING_WINDOW as f32;
+
+        // 在 [threshold/2, threshold] 区间内从平滑值过渡到原始值
+        let magnitude = (yaw * yaw + pitch * pitch).sqrt();
+        let half = threshold / 2.0;
+        let direct_weight = ((magnitude - half) / half).clamp(0.0, 1.0);
+        (
+            yaw * direct_weight + avg_yaw * (1.0 - direct_weight),
+            pitch * direct_weight + avg_pitch * (1.0 - direct_weight),
+        )
+    }
+
+    /// 玩家空间：绕重力方向的旋转作为水平转向，与手柄握持角度无关
+    fn player_space_yaw(&mut self, gyro: [f32; 3], accel: [f32; 3]) -> f32 {
+        for (g, a) in self.gravity.iter_mut().zip(accel) {
+            *g += (a - *g) * GRAVITY_FILTER_ALPHA;
+        }
+        let len = self.gravity.iter().map(|g| g * g).sum::<f32>().sqrt();
+        if len < f32::EPSILON {
+            return gyro[1];
+        }
+        let (grav_yaw, grav_roll) = (self.gravity[1] / len, self.gravity[2] / len);
+
+        let [_, yaw, roll] = gyro;
+        let world_yaw = yaw * grav_yaw + roll * grav_roll;
+        world_yaw.signum() * (world_yaw.abs() * PLAYER_SPACE_YAW_RELAX).min((yaw * yaw + roll * roll).sqrt())
+    }
+
+    /// 根据启用键配置判断当前是否输出鼠标移动
+    fn is_enabled(&mut self, button_pressed: Option<bool>, mode: Option<&str>) -> bool {
+        let Some(pressed) = button_pressed else {
+            return true;
+        };
+        let rising = pressed && !self.last_button_state;
+        self.last_button_state = pressed;
+
+        match mode.unwrap_or("Hold") {
+            // 按住期间暂停，松开恢复（棘轮式复位，类似抬起鼠标）
+            "Ratchet" => !pressed,
+            "Toggle" => {
+                if rising {
+                    self.toggle_active = !self.toggle_active;
+                }
+                self.toggle_active
+            }
+            _ => pressed,
+        }
+    }
+}
+
+static GYRO_MOUSE_STATE: Lazy<PerSlot<RwLock<GyroMouseState>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(GyroMouseState::default())));
+
+/// 陀螺仪模拟鼠标
+///
+/// 陀螺仪单位为 deg/s，`gyro_sensitivity` 为手柄每转动 1° 鼠标移动的像素数。
+/// 偏航（左右转动）控制水平移动，俯仰（上下翻转）控制垂直移动。
+pub fn handle_gyro_mouse(controller_datas: &ControllerDatas) {
+    let items = preset::get_current_preset().items;
+    let mut state = GYRO_MOUSE_STATE.write().unwrap();
+    let dt = continuous_frame_seconds(&mut state.last_update, Instant::now());
+
+    if !items.use_gyro_as_mouse || !controller_datas.imu.has_imu {
+        return;
+    }
+
+    let button_pressed = items.gyro_enable_button.as_deref().and_then(|name| {
+        get_current_controller_layout_map()
+            .get(name)
+            .map(|button| controller_datas.get_button(*button))
+    });
+    let enabled = state.is_enabled(button_pressed, items.gyro_enable_mode.as_deref());
+
+    let gyro = controller_datas.imu.gyro;
+    let yaw = match items.gyro_space.as_deref() {
+        Some("player") => state.player_space_yaw(gyro, controller_datas.imu.accel),
+        _ => gyro[1],
+    };
+    let (yaw, pitch) = state.smooth(yaw, gyro[0], items.gyro_smoothing_threshold);
+
+    if !enabled {
+        state.move_x_remainder = 0.0;
+        state.move_y_remainder = 0.0;
+        return;
+    }
+
+    // 首帧或长时间未轮询时不积分，避免跳变
+    let Some(dt) = dt else {
+        return;
+    };
+
+    // 偏航逆时针为正 → 鼠标左移；俯仰抬头为正 → 鼠标上移
+    state.move_x_remainder += -yaw * dt * items.gyro_sensitivity;
+    state.move_y_remainder += -pitch * dt * items.gyro_sensitivity;
+
+    let move_x = state.move_x_remainder.trunc();
+    let move_y = state.move_y_remainder.trunc();
+    state.move_x_remainder -= move_x;
+    state.move_y_remainder -= move_y;
+    drop(state);
+
+    if move_x != 0.0 || move_y != 0.0 {
+        output::with_active_sink(|sink| {
+            log_output_error(sink.move_mouse(move_x as i32, move_y as i32), "陀螺仪鼠标移动");
+        });
     }
 }
@@ -0,0 +1,283 @@
+//! 扳机模拟量映射
+//!
+//! 普通映射通过 `trigger_theshold` 把扳机折叠成按键；设置了 `analog` 的 LT / RT 映射
+//! 则按扳机深度连续输出：
+//! - `scroll`：滚动速度与深度成正比，方向取自映射动作（MouseWheelUp / Down / Left / Right）
+//! - `mouse_speed`：按深度调节摇杆模拟鼠标的速度倍率，在本周期鼠标移动之前由
+//!   [`update_mouse_speed_scale`] 计算，当帧生效
+//! - `key_pwm`：按深度调节映射动作在每个周期内按住的时长（占空比），
+//!   让只支持键盘的赛车游戏也能半油门
+//!
+//! 深度从触发阈值算起：阈值处为 0，按到底为 1，再经过映射自己的响应曲线。
+
+use crate::controller::curve::ResponseCurve;
+use crate::controller::datas::ControllerDatas;
+use crate::controller::slots::PerSlot;
+use crate::mapping::{
+    analog_trigger_input, frame_seconds, Action, EnigoCommand, Mapping, PrimaryAction, ENIGO_SENDER,
+    GLOBAL_MAPPING_CACHE, SUB_MAPPING_CACHE,
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// 模拟量输出方式
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum AnalogMode {
+    /// 滚轮，`max_speed` 为扳机按到底时每秒滚动的格数
+    Scroll { max_speed: f32 },
+    /// 鼠标速度倍率，`max_scale` 为扳机按到底时的倍率（小于 1 即为精确瞄准的减速键）
+    MouseSpeed { max_scale: f32 },
+    /// 按键脉宽调制，`period_ms` 为一个按下 / 松开周期的时长
+    KeyPwm { period_ms: u64 },
+}
+
+/// 扳机模拟量映射配置
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct AnalogTrigger {
+    #[serde(flatten)]
+    pub mode: AnalogMode,
+    /// 扳机深度的响应曲线
+    #[serde(default)]
+    pub curve: ResponseCurve,
+}
+
+#[derive(Default)]
+struct AnalogState {
+    last_update: Option<Instant>,
+    scroll_remainder: f32,
+    pwm_cycle_start: Option<Instant>,
+    key_down: bool,
+}
+
+/// 每个模拟量映射的运行状态（按映射 ID）
+static ANALOG_STATES: Lazy<PerSlot<RwLock<HashMap<u64, AnalogState>>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(HashMap::new())));
+
+/// 摇杆模拟鼠标的速度倍率，由 [`update_mouse_speed_scale`] 每帧写入
+static MOUSE_SPEED_SCALE: Lazy<PerSlot<RwLock<f32>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(1.0)));
+
+/// 当前槽位的鼠标速度倍率
+pub fn mouse_speed_scale() -> f32 {
+    *MOUSE_SPEED_SCALE.read().unwrap()
+}
+
+/// 本帧所有 `mouse_speed` 映射的倍率乘积
+fn mouse_speed_scale_for(mappings: &[Mapping], controller_datas: &ControllerDatas) -> f32 {
+    mappings
+        .iter()
+        .filter_map(|mapping| {
+            let (analog, value) = analog_trigger_input(controller_datas, mapping)?;
+            let AnalogMode::MouseSpeed { max_scale } = analog.mode else {
+                return None;
+            };
+            let depth = analog.curve.apply(trigger_depth(value, mapping.trigger_theshold));
+            Some(1.0 + (max_scale - 1.0) * depth)
+        })
+        .product()
+}
+
+/// 计算并写入当前槽位本帧的鼠标速度倍率
+///
+/// 需在 `handle_mouse_movement` 之前调用，否则扳机调节的速度要到下一帧才生效
+pub fn update_mouse_speed_scale(controller_datas: &ControllerDatas, use_sub_preset: bool) {
+    let cache = if use_sub_preset { &SUB_MAPPING_CACHE } else { &GLOBAL_MAPPING_CACHE };
+    let scale = mouse_speed_scale_for(&cache.read().unwrap(), controller_datas);
+    *MOUSE_SPEED_SCALE.write().unwrap() = scale;
+}
+
+/// 从触发阈值算起的扳机深度，0..1
+fn trigger_depth(value: f32, threshold: f32) -> f32 {
+    if value <= threshold {
+        return 0.0;
+    }
+    ((value - threshold) / (1.0 - threshold).max(f32::EPSILON)).clamp(0.0, 1.0)
+}
+
+fn send(command: EnigoCommand) {
+    if let Err(e) = ENIGO_SENDER.send(command) {
+        log::error!("发送扳机模拟量动作失败: {e}");
+    }
+}
+
+/// 处理一个扳机模拟量映射（`mouse_speed` 模式已由 [`update_mouse_speed_scale`] 处理）
+pub fn handle(id: u64, analog: &AnalogTrigger, value: f32, threshold: f32, action: &Action) {
+    let depth = analog.curve.apply(trigger_depth(value, threshold));
+    let now = Instant::now();
+
+    let mut states = ANALOG_STATES.write().unwrap();
+    let state = states.entry(id).or_default();
+    let dt = frame_seconds(&mut state.last_update, now);
+
+    match analog.mode {
+        AnalogMode::Scroll { max_speed } => {
+            if depth <= 0.0 {
+                state.scroll_remainder = 0.0;
+                return;
+            }
+            state.scroll_remainder += depth * max_speed * dt;
+            let notches = state.scroll_remainder.trunc();
+            if notches >= 1.0 {
+                state.scroll_remainder -= notches;
+                let (direction, horizontal) = match action.primary {
+                    PrimaryAction::MouseWheel { amount, horizontal } => {
+                        (if amount < 0 { -1 } else { 1 }, horizontal)
+                    }
+                    _ => (1, false),
+                };
+                send(EnigoCommand::Execute(Action {
+                    modifiers: action.modifiers.clone(),
+                    primary: PrimaryAction::MouseWheel {
+                        amount: direction * notches as i32,
+                        horizontal,
+                    },
+                }));
+            }
+        }
+        AnalogMode::MouseSpeed { .. } => {}
+        AnalogMode::KeyPwm { period_ms } => {
+            let should_press = if depth <= 0.0 {
+                state.pwm_cycle_start = None;
+                false
+            } else if depth >= 1.0 {
+                true
+            } else {
+                let period = Duration::from_millis(period_ms.max(1));
+                let start = *state.pwm_cycle_start.get_or_insert(now);
+                let mut elapsed = now.duration_since(start);
+                if elapsed >= period {
+                    state.pwm_cycle_start = Some(now);
+                    elapsed = Duration::ZERO;
+                }
+                elapsed.as_secs_f32() < period.as_secs_f32() * depth
+            };
+
+            if should_press != state.key_down {
+                state.key_down = should_press;
+                send(if should_press {
+                    EnigoCommand::ExecutePress(action.clone())
+                } else {
+                    EnigoCommand::ExecuteRelease(action.clone())
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::controller::{disconnect_device, listen_tick, use_device, ControllerType};
+    use crate::controller::scripted;
+    use crate::controller::slots::{self, MAX_SLOTS};
+    use crate::mapping::output::{self, OutputEvent, OutputSinkKind};
+    use crate::mapping::{create_mapping_from_config, MappingUpdateConfig};
+    use crate::preset;
+
+    const TEST_SLOT: usize = MAX_SLOTS - 1;
+
+    fn trigger_mapping(id: u64, button: &str, mode: AnalogMode, curve: ResponseCurve) -> Mapping {
+        let mut mapping = create_mapping_from_config(
+            MappingUpdateConfig::new(id)
+                .with_composed_button(button.to_string())
+                .with_composed_shortcut_key("K".to_string())
+                .with_trigger_threshold(0.2)
+                .with_analog(AnalogTrigger { mode, curve }),
+        )
+        .unwrap();
+        // 同一毫秒内创建的映射 ID 相同，测试中手动指定
+        mapping.id = id;
+        mapping
+    }
+
+    fn triggers(left: f32, right: f32) -> ControllerDatas {
+        let mut datas = ControllerDatas::new();
+        datas.left_trigger.value = left;
+        datas.right_trigger.value = right;
+        datas
+    }
+
+    #[test]
+    fn trigger_depth_starts_at_threshold() {
+        assert_eq!(trigger_depth(0.1, 0.2), 0.0);
+        assert_eq!(trigger_depth(0.2, 0.2), 0.0);
+        assert!((trigger_depth(0.6, 0.2) - 0.5).abs() < 1e-6);
+        assert_eq!(trigger_depth(1.0, 0.2), 1.0);
+    }
+
+    #[test]
+    fn mouse_speed_scale_follows_trigger_depth() {
+        let mappings = vec![
+            trigger_mapping(1, "LT", AnalogMode::MouseSpeed { max_scale: 0.25 }, ResponseCurve::Linear),
+            trigger_mapping(2, "RT", AnalogMode::MouseSpeed { max_scale: 3.0 }, ResponseCurve::Power { exponent: 2.0 }),
+            // 其他模式不影响倍率
+            trigger_mapping(3, "RT", AnalogMode::Scroll { max_speed: 10.0 }, ResponseCurve::Linear),
+        ];
+
+        assert_eq!(mouse_speed_scale_for(&mappings, &triggers(0.0, 0.0)), 1.0);
+        // LT 半程：1 + (0.25 - 1) * 0.5
+        assert!((mouse_speed_scale_for(&mappings, &triggers(0.6, 0.0)) - 0.625).abs() < 1e-6);
+        // RT 半程经过平方曲线：1 + (3 - 1) * 0.25
+        assert!((mouse_speed_scale_for(&mappings, &triggers(0.0, 0.6)) - 1.5).abs() < 1e-6);
+        // 同时按到底时倍率相乘
+        assert!((mouse_speed_scale_for(&mappings, &triggers(1.0, 1.0)) - 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn mouse_speed_scale_applies_in_the_same_tick() {
+        let _lock = output::RECORDING_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        output::take_recorded_events();
+
+        let device = scripted::add_device("脚本测试手柄 analog", ControllerType::Xbox);
+        let path = device.device_path.clone().unwrap();
+        assert!(use_device(device.name.clone(), Some(TEST_SLOT)));
+
+        let original = slots::with_slot(TEST_SLOT, || {
+            let mut preset = preset::CURRENT_PRESET.write().unwrap();
+            let original = preset.clone();
+            preset.items.output_sink = OutputSinkKind::Recording;
+            preset.items.use_stick_as_mouse = true;
+            preset.items.stick_as_mouse_simulation = Some("right".to_string());
+            preset.items.move_speed = 20;
+            *GLOBAL_MAPPING_CACHE.write().unwrap() =
+                vec![trigger_mapping(1, "LT", AnalogMode::MouseSpeed { max_scale: 0.25 }, ResponseCurve::Linear)];
+            original
+        });
+
+        let mut frame = triggers(1.0, 0.0);
+        frame.right_stick.x = 1.0;
+        let mut released = frame;
+        released.left_trigger.value = 0.0;
+        assert!(scripted::push_frames(&path, [frame, released]));
+        let mut last_devices = Default::default();
+
+        // 按下扳机的同一周期内鼠标即按 0.25 倍速移动
+        let mouse_moves = || -> Vec<(i32, i32)> {
+            output::wait_for_recorded_events(1)
+                .into_iter()
+                .filter_map(|event| match event {
+                    OutputEvent::MoveMouse { dx, dy } => Some((dx, dy)),
+                    _ => None,
+                })
+                .collect()
+        };
+        listen_tick(&mut last_devices, Instant::now());
+        assert_eq!(mouse_moves(), vec![(5, 0)]);
+
+        // 松开扳机的同一周期内恢复原速
+        listen_tick(&mut last_devices, Instant::now());
+        assert_eq!(mouse_moves(), vec![(20, 0)]);
+
+        assert!(disconnect_device(Some(TEST_SLOT)));
+        assert!(scripted::remove_device(&path));
+        slots::with_slot(TEST_SLOT, || {
+            GLOBAL_MAPPING_CACHE.write().unwrap().clear();
+            *preset::CURRENT_PRESET.write().unwrap() = original;
+        });
+        output::take_recorded_events();
+    }
+}
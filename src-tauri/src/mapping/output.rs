@@ -0,0 +1,523 @@
+//! 键鼠输出后端
+//!
+//! 映射动作与鼠标模拟不再直接调用 enigo，而是写入 [`OutputSink`]：
+//! - [`EnigoSink`]：通过 enigo 调用系统输入接口（默认）
+//! - [`UinputSink`]：Linux 下通过 uinput 创建虚拟键鼠，适用于 enigo 无法注入的 Wayland 等环境
+//! - [`RecordingSink`]：不产生真实输入，只记录事件，用于试运行映射或校验映射输出
+//!
+//! 每个预设可以选择输出后端（`PresetItems::output_sink`）。各后端实例全局共享，
+//! 首次使用时创建，创建失败时回退到 enigo。
+
+use crate::preset;
+use enigo::{Enigo, Keyboard, Mouse};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+
+#[cfg(target_os = "linux")]
+use evdev::uinput::VirtualDevice;
+#[cfg(target_os = "linux")]
+use evdev::{
+    AttributeSet, BusType, InputEvent, InputId, KeyCode, KeyEvent, RelativeAxisCode,
+    RelativeAxisEvent,
+};
+
+/// 记录输出后端最多保留的事件数
+const MAX_RECORDED_EVENTS: usize = 4096;
+
+/// 输出后端类型
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputSinkKind {
+    #[default]
+    Enigo,
+    Uinput,
+    Recording,
+}
+
+/// 一次键鼠输出
+#[derive(Clone, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum OutputEvent {
+    Key { key: enigo::Key, direction: enigo::Direction },
+    Button { button: enigo::Button, direction: enigo::Direction },
+    Scroll { amount: i32, axis: enigo::Axis },
+    MoveMouse { dx: i32, dy: i32 },
+}
+
+/// 键鼠输出后端
+pub trait OutputSink: Send {
+    /// 后端名称（日志用）
+    fn name(&self) -> &'static str;
+
+    fn key(&mut self, key: enigo::Key, direction: enigo::Direction) -> Result<(), String>;
+
+    fn button(&mut self, button: enigo::Button, direction: enigo::Direction) -> Result<(), String>;
+
+    /// 滚动滚轮，正值向下/向右（与 enigo 一致）
+    fn scroll(&mut self, amount: i32, axis: enigo::Axis) -> Result<(), String>;
+
+    /// 相对移动鼠标
+    fn move_mouse(&mut self, dx: i32, dy: i32) -> Result<(), String>;
+}
+
+// --- enigo ---
+
+pub struct EnigoSink(Enigo);
+
+impl EnigoSink {
+    pub fn new() -> Result<Self, String> {
+        Enigo::new(&enigo::Settings::default())
+            .map(Self)
+            .map_err(|e| format!("初始化 enigo 失败: {e:?}"))
+    }
+}
+
+impl OutputSink for EnigoSink {
+    fn name(&self) -> &'static str {
+        "enigo"
+    }
+
+    fn key(&mut self, key: enigo::Key, direction: enigo::Direction) -> Result<(), String> {
+        self.0.key(key, direction).map_err(|e| format!("{e:?}"))
+    }
+
+    fn button(&mut self, button: enigo::Button, direction: enigo::Direction) -> Result<(), String> {
+        self.0.button(button, direction).map_err(|e| format!("{e:?}"))
+    }
+
+    fn scroll(&mut self, amount: i32, axis: enigo::Axis) -> Result<(), String> {
+        self.0.scroll(amount, axis).map_err(|e| format!("{e:?}"))
+    }
+
+    fn move_mouse(&mut self, dx: i32, dy: i32) -> Result<(), String> {
+        self.0
+            .move_mouse(dx, dy, enigo::Coordinate::Rel)
+            .map_err(|e| format!("{e:?}"))
+    }
+}
+
+// --- uinput (Linux) ---
+
+/// 虚拟键鼠设备名称
+pub const UINPUT_SINK_NAME: &str = "XialloControl Virtual Keyboard/Mouse";
+
+/// 可通过 uinput 输出的字符键
+#[cfg(target_os = "linux")]
+const CHAR_KEYS: [(char, KeyCode); 47] = [
+    ('a', KeyCode::KEY_A), ('b', KeyCode::KEY_B), ('c', KeyCode::KEY_C), ('d', KeyCode::KEY_D),
+    ('e', KeyCode::KEY_E), ('f', KeyCode::KEY_F), ('g', KeyCode::KEY_G), ('h', KeyCode::KEY_H),
+    ('i', KeyCode::KEY_I), ('j', KeyCode::KEY_J), ('k', KeyCode::KEY_K), ('l', KeyCode::KEY_L),
+    ('m', KeyCode::KEY_M), ('n', KeyCode::KEY_N), ('o', KeyCode::KEY_O), ('p', KeyCode::KEY_P),
+    ('q', KeyCode::KEY_Q), ('r', KeyCode::KEY_R), ('s', KeyCode::KEY_S), ('t', KeyCode::KEY_T),
+    ('u', KeyCode::KEY_U), ('v', KeyCode::KEY_V), ('w', KeyCode::KEY_W), ('x', KeyCode::KEY_X),
+    ('y', KeyCode::KEY_Y), ('z', KeyCode::KEY_Z),
+    ('1', KeyCode::KEY_1), ('2', KeyCode::KEY_2), ('3', KeyCode::KEY_3), ('4', KeyCode::KEY_4),
+    ('5', KeyCode::KEY_5), ('6', KeyCode::KEY_6), ('7', KeyCode::KEY_7), ('8', KeyCode::KEY_8),
+    ('9', KeyCode::KEY_9), ('0', KeyCode::KEY_0),
+    ('-', KeyCode::KEY_MINUS), ('=', KeyCode::KEY_EQUAL), ('[', KeyCode::KEY_LEFTBRACE),
+    (']', KeyCode::KEY_RIGHTBRACE), (';', KeyCode::KEY_SEMICOLON), ('\'', KeyCode::KEY_APOSTROPHE),
+    ('`', KeyCode::KEY_GRAVE), ('\\', KeyCode::KEY_BACKSLASH), (',', KeyCode::KEY_COMMA),
+    ('.', KeyCode::KEY_DOT), ('/', KeyCode::KEY_SLASH),
+];
+
+/// 可通过 uinput 输出的功能键
+#[cfg(target_os = "linux")]
+const NAMED_KEYS: [(enigo::Key, KeyCode); 32] = [
+    (enigo::Key::Alt, KeyCode::KEY_LEFTALT),
+    (enigo::Key::Backspace, KeyCode::KEY_BACKSPACE),
+    (enigo::Key::CapsLock, KeyCode::KEY_CAPSLOCK),
+    (enigo::Key::Control, KeyCode::KEY_LEFTCTRL),
+    (enigo::Key::Delete, KeyCode::KEY_DELETE),
+    (enigo::Key::DownArrow, KeyCode::KEY_DOWN),
+    (enigo::Key::End, KeyCode::KEY_END),
+    (enigo::Key::Escape, KeyCode::KEY_ESC),
+    (enigo::Key::Home, KeyCode::KEY_HOME),
+    (enigo::Key::LeftArrow, KeyCode::KEY_LEFT),
+    (enigo::Key::Meta, KeyCode::KEY_LEFTMETA),
+    (enigo::Key::PageDown, KeyCode::KEY_PAGEDOWN),
+    (enigo::Key::PageUp, KeyCode::KEY_PAGEUP),
+    (enigo::Key::Return, KeyCode::KEY_ENTER),
+    (enigo::Key::RightArrow, KeyCode::KEY_RIGHT),
+    (enigo::Key::Shift, KeyCode::KEY_LEFTSHIFT),
+    (enigo::Key::Space, KeyCode::KEY_SPACE),
+    (enigo::Key::Tab, KeyCode::KEY_TAB),
+    (enigo::Key::UpArrow, KeyCode::KEY_UP),
+    (enigo::Key::F1, KeyCode::KEY_F1),
+    (enigo::Key::F2, KeyCode::KEY_F2),
+    (enigo::Key::F3, KeyCode::KEY_F3),
+    (enigo::Key::F4, KeyCode::KEY_F4),
+    (enigo::Key::F5, KeyCode::KEY_F5),
+    (enigo::Key::F6, KeyCode::KEY_F6),
+    (enigo::Key::F7, KeyCode::KEY_F7),
+    (enigo::Key::F8, KeyCode::KEY_F8),
+    (enigo::Key::F9, KeyCode::KEY_F9),
+    (enigo::Key::F10, KeyCode::KEY_F10),
+    (enigo::Key::F11, KeyCode::KEY_F11),
+    (enigo::Key::F12, KeyCode::KEY_F12),
+    (enigo::Key::Insert, KeyCode::KEY_INSERT),
+];
+
+/// uinput 虚拟键鼠
+#[cfg(target_os = "linux")]
+pub struct UinputSink {
+    device: VirtualDevice,
+}
+
+#[cfg(target_os = "linux")]
+impl UinputSink {
+    pub fn new() -> Result<Self, String> {
+        let mut keys: AttributeSet<KeyCode> = CHAR_KEYS.iter().map(|(_, code)| *code).collect();
+        for (_, code) in NAMED_KEYS {
+            keys.insert(code);
+        }
+        for code in [
+            KeyCode::BTN_LEFT,
+            KeyCode::BTN_RIGHT,
+            KeyCode::BTN_MIDDLE,
+            KeyCode::BTN_SIDE,
+            KeyCode::BTN_EXTRA,
+        ] {
+            keys.insert(code);
+        }
+        let axes: AttributeSet<RelativeAxisCode> = [
+            RelativeAxisCode::REL_X,
+            RelativeAxisCode::REL_Y,
+            RelativeAxisCode::REL_WHEEL,
+            RelativeAxisCode::REL_HWHEEL,
+        ]
+        .into_iter()
+        .collect();
+
+        let build = || -> std::io::Result<VirtualDevice> {
+            VirtualDevice::builder()?
+                .name(UINPUT_SINK_NAME)
+                .input_id(InputId::new(BusType::BUS_VIRTUAL, 0, 0, 1))
+                .with_keys(&keys)?
+                .with_relative_axes(&axes)?
+                .build()
+        };
+        build()
+            .map(|device| Self { device })
+            .map_err(|e| format!("创建 uinput 虚拟键鼠失败（请检查 /dev/uinput 权限）: {e}"))
+    }
+
+    fn key_code(key: enigo::Key) -> Option<(KeyCode, bool)> {
+        if let enigo::Key::Unicode(c) = key {
+            let lower = c.to_ascii_lowercase();
+            return CHAR_KEYS
+                .iter()
+                .find(|(ch, _)| *ch == lower)
+                .map(|(_, code)| (*code, c.is_ascii_uppercase()));
+        }
+        NAMED_KEYS
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, code)| (*code, false))
+    }
+
+    /// 写入一批事件（evdev 自动追加 SYN_REPORT）
+    fn emit(&mut self, events: &[InputEvent]) -> Result<(), String> {
+        self.device.emit(events).map_err(|e| e.to_string())
+    }
+
+    fn press_code(&mut self, code: KeyCode, direction: enigo::Direction) -> Result<(), String> {
+        match direction {
+            enigo::Direction::Press => self.emit(&[*KeyEvent::new(code, 1)]),
+            enigo::Direction::Release => self.emit(&[*KeyEvent::new(code, 0)]),
+            enigo::Direction::Click => {
+                self.emit(&[*KeyEvent::new(code, 1)])?;
+                self.emit(&[*KeyEvent::new(code, 0)])
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl OutputSink for UinputSink {
+    fn name(&self) -> &'static str {
+        "uinput"
+    }
+
+    fn key(&mut self, key: enigo::Key, direction: enigo::Direction) -> Result<(), String> {
+        let Some((code, shifted)) = Self::key_code(key) else {
+            return Err(format!("uinput 不支持按键 {key:?}"));
+        };
+        // 大写字母需要临时按住 Shift
+        if shifted && direction != enigo::Direction::Release {
+            self.press_code(KeyCode::KEY_LEFTSHIFT, enigo::Direction::Press)?;
+        }
+        let result = self.press_code(code, direction);
+        if shifted && direction != enigo::Direction::Press {
+            self.press_code(KeyCode::KEY_LEFTSHIFT, enigo::Direction::Release)?;
+        }
+        result
+    }
+
+    fn button(&mut self, button: enigo::Button, direction: enigo::Direction) -> Result<(), String> {
+        let code = match button {
+            enigo::Button::Left => KeyCode::BTN_LEFT,
+            enigo::Button::Right => KeyCode::BTN_RIGHT,
+            enigo::Button::Middle => KeyCode::BTN_MIDDLE,
+            enigo::Button::Back => KeyCode::BTN_SIDE,
+            enigo::Button::Forward => KeyCode::BTN_EXTRA,
+            // 滚轮按键只在按下时滚动一格
+            enigo::Button::ScrollUp | enigo::Button::ScrollDown
+            | enigo::Button::ScrollLeft | enigo::Button::ScrollRight => {
+                if direction == enigo::Direction::Release {
+                    return Ok(());
+                }
+                return match button {
+                    enigo::Button::ScrollUp => self.scroll(-1, enigo::Axis::Vertical),
+                    enigo::Button::ScrollDown => self.scroll(1, enigo::Axis::Vertical),
+                    enigo::Button::ScrollLeft => self.scroll(-1, enigo::Axis::Horizontal),
+                    _ => self.scroll(1, enigo::Axis::Horizontal),
+                };
+            }
+        };
+        self.press_code(code, direction)
+    }
+
+    fn scroll(&mut self, amount: i32, axis: enigo::Axis) -> Result<(), String> {
+        // evdev 滚轮向上为正，与 enigo 相反
+        let event = match axis {
+            enigo::Axis::Vertical => RelativeAxisEvent::new(RelativeAxisCode::REL_WHEEL, -amount),
+            enigo::Axis::Horizontal => RelativeAxisEvent::new(RelativeAxisCode::REL_HWHEEL, amount),
+        };
+        self.emit(&[*event])
+    }
+
+    fn move_mouse(&mut self, dx: i32, dy: i32) -> Result<(), String> {
+        self.emit(&[
+            *RelativeAxisEvent::new(RelativeAxisCode::REL_X, dx),
+            *RelativeAxisEvent::new(RelativeAxisCode::REL_Y, dy),
+        ])
+    }
+}
+
+// --- 记录 ---
+
+/// 记录输出后端收到的事件（最近 [`MAX_RECORDED_EVENTS`] 条）
+static RECORDED_EVENTS: Lazy<Mutex<VecDeque<OutputEvent>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+/// 只记录事件、不产生真实输入的输出后端
+pub struct RecordingSink;
+
+impl RecordingSink {
+    fn record(&self, event: OutputEvent) -> Result<(), String> {
+        log::debug!("记录输出: {event:?}");
+        let mut events = RECORDED_EVENTS.lock().unwrap();
+        if events.len() >= MAX_RECORDED_EVENTS {
+            events.pop_front();
+        }
+        events.push_back(event);
+        Ok(())
+    }
+}
+
+impl OutputSink for RecordingSink {
+    fn name(&self) -> &'static str {
+        "recording"
+    }
+
+    fn key(&mut self, key: enigo::Key, direction: enigo::Direction) -> Result<(), String> {
+        self.record(OutputEvent::Key { key, direction })
+    }
+
+    fn button(&mut self, button: enigo::Button, direction: enigo::Direction) -> Result<(), String> {
+        self.record(OutputEvent::Button { button, direction })
+    }
+
+    fn scroll(&mut self, amount: i32, axis: enigo::Axis) -> Result<(), String> {
+        self.record(OutputEvent::Scroll { amount, axis })
+    }
+
+    fn move_mouse(&mut self, dx: i32, dy: i32) -> Result<(), String> {
+        self.record(OutputEvent::MoveMouse { dx, dy })
+    }
+}
+
+/// 取出记录输出后端已记录的事件
+pub fn take_recorded_events() -> Vec<OutputEvent> {
+    RECORDED_EVENTS.lock().unwrap().drain(..).collect()
+}
+
+/// 记录的事件为全局共享，使用记录输出后端的测试需持有该锁串行执行
+#[cfg(test)]
+pub(crate) static RECORDING_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+/// 等待映射工作线程输出至少 `count` 个事件后取出，超时返回已记录的事件
+#[cfg(test)]
+pub(crate) fn wait_for_recorded_events(count: usize) -> Vec<OutputEvent> {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(2);
+    while RECORDED_EVENTS.lock().unwrap().len() < count && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    take_recorded_events()
+}
+
+// --- 后端管理 ---
+
+/// 已创建的输出后端
+static SINKS: Lazy<Mutex<HashMap<OutputSinkKind, Box<dyn OutputSink>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 创建失败的后端，不再重试，直接回退 enigo
+static FAILED_SINKS: Lazy<Mutex<HashSet<OutputSinkKind>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+fn create_sink(kind: OutputSinkKind) -> Result<Box<dyn OutputSink>, String> {
+    match kind {
+        OutputSinkKind::Enigo => Ok(Box::new(EnigoSink::new()?)),
+        #[cfg(target_os = "linux")]
+        OutputSinkKind::Uinput => Ok(Box::new(UinputSink::new()?)),
+        #[cfg(not(target_os = "linux"))]
+        OutputSinkKind::Uinput => Err("uinput 输出仅支持 Linux".to_string()),
+        OutputSinkKind::Recording => Ok(Box::new(RecordingSink)),
+    }
+}
+
+/// 在指定输出后端上执行操作，后端不可用时回退到 enigo
+pub fn with_sink<R>(kind: OutputSinkKind, f: impl FnOnce(&mut dyn OutputSink) -> R) -> Option<R> {
+    let mut sinks = SINKS.lock().unwrap();
+    let mut kind = kind;
+    if FAILED_SINKS.lock().unwrap().contains(&kind) {
+        if kind == OutputSinkKind::Enigo {
+            return None;
+        }
+        kind = OutputSinkKind::Enigo;
+    }
+    if !sinks.contains_key(&kind) {
+        match create_sink(kind) {
+            Ok(sink) => {
+                log::info!("✅ 已创建输出后端: {}", sink.name());
+                sinks.insert(kind, sink);
+            }
+            Err(e) => {
+                log::error!("❌ {e}");
+                FAILED_SINKS.lock().unwrap().insert(kind);
+                if kind == OutputSinkKind::Enigo {
+                    return None;
+                }
+                drop(sinks);
+                log::warn!("输出后端 {kind:?} 不可用，回退到 enigo");
+                return with_sink(OutputSinkKind::Enigo, f);
+            }
+        }
+    }
+    sinks.get_mut(&kind).map(|sink| f(sink.as_mut()))
+}
+
+/// 在当前槽位预设选择的输出后端上执行操作
+pub fn with_active_sink<R>(f: impl FnOnce(&mut dyn OutputSink) -> R) -> Option<R> {
+    let kind = preset::get_current_preset().items.output_sink;
+    with_sink(kind, f)
+}
+
+/// 取出记录输出后端已记录的事件（前端试运行映射时查看）
+#[tauri::command]
+pub fn take_recorded_output() -> Vec<OutputEvent> {
+    take_recorded_events()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::datas::{ControllerButtons, ControllerDatas};
+    use crate::controller::slots;
+    use crate::mapping::{
+        map, parse_composed_key_to_action, Mapping, BUTTON_CHECK_STATES, DYNAMIC_TRIGGER_STATES,
+        GLOBAL_MAPPING_CACHE,
+    };
+    use enigo::{Axis, Direction, Key};
+
+    fn mapping(id: u64, composed_button: &str, composed_shortcut_key: &str) -> Mapping {
+        let mut mapping = Mapping::new(id, composed_button.to_string(), composed_shortcut_key.to_string());
+        mapping.action = parse_composed_key_to_action(composed_shortcut_key).unwrap();
+        mapping
+    }
+
+    fn pressed(buttons: &[ControllerButtons]) -> ControllerDatas {
+        let mut datas = ControllerDatas::new();
+        for button in buttons {
+            datas.set_button(*button, true);
+        }
+        datas
+    }
+
+    /// 在指定槽位上以记录输出后端运行映射，每帧调用一次 `map` 并取出该帧的输出
+    fn run_frames(slot: usize, mappings: Vec<Mapping>, frames: &[(ControllerDatas, usize)]) -> Vec<Vec<OutputEvent>> {
+        slots::with_slot(slot, || {
+            preset::CURRENT_PRESET.write().unwrap().items.output_sink = OutputSinkKind::Recording;
+            *GLOBAL_MAPPING_CACHE.write().unwrap() = mappings;
+            DYNAMIC_TRIGGER_STATES.write().unwrap().clear();
+            BUTTON_CHECK_STATES.write().unwrap().clear();
+
+            let outputs = frames
+                .iter()
+                .map(|&(mut datas, expected)| {
+                    map(&mut datas, false);
+                    wait_for_recorded_events(expected)
+                })
+                .collect();
+
+            GLOBAL_MAPPING_CACHE.write().unwrap().clear();
+            outputs
+        })
+    }
+
+    #[test]
+    fn modifiers_press_in_order_and_release_in_reverse() {
+        let _lock = RECORDING_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        take_recorded_events();
+
+        let outputs = run_frames(
+            1,
+            vec![mapping(1, "A", "Ctrl+Shift+K")],
+            &[(pressed(&[ControllerButtons::South]), 3), (pressed(&[]), 3)],
+        );
+
+        let key = |key, direction| OutputEvent::Key { key, direction };
+        assert_eq!(
+            outputs[0],
+            vec![
+                key(Key::Control, Direction::Press),
+                key(Key::Shift, Direction::Press),
+                key(Key::Unicode('k'), Direction::Press),
+            ]
+        );
+        assert_eq!(
+            outputs[1],
+            vec![
+                key(Key::Unicode('k'), Direction::Release),
+                key(Key::Shift, Direction::Release),
+                key(Key::Control, Direction::Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn horizontal_wheel_scrolls_on_horizontal_axis() {
+        let _lock = RECORDING_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        take_recorded_events();
+
+        let outputs = run_frames(
+            2,
+            vec![
+                mapping(1, "B", "MouseWheelRight"),
+                mapping(2, "X", "MouseWheelLeft"),
+                mapping(3, "Y", "MouseWheelDown"),
+            ],
+            &[(pressed(&[ControllerButtons::East, ControllerButtons::West, ControllerButtons::North]), 3)],
+        );
+
+        assert_eq!(
+            outputs[0],
+            vec![
+                OutputEvent::Scroll { amount: 1, axis: Axis::Horizontal },
+                OutputEvent::Scroll { amount: -1, axis: Axis::Horizontal },
+                OutputEvent::Scroll { amount: 1, axis: Axis::Vertical },
+            ]
+        );
+    }
+}
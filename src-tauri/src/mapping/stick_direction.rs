@@ -0,0 +1,181 @@
+//! 摇杆方向虚拟按键
+//!
+//! 把摇杆的推动方向折叠成 `LeftStickUp`、`LeftStickUpRight` 等虚拟按键，
+//! 供 `composed_button` 使用，从而让摇杆驱动 WASD / 方向键。
+//!
+//! 判定方式：
+//! - 摇杆幅度超过 `activation_radius` 才开始判定方向，已有方向时降到 `release_radius` 以下才松开
+//! - 方向按扇区划分（四向 / 八向），相邻扇区可通过 `overlap_angle` 互相重叠，
+//!   重叠区内两个方向同时按下
+//! - 已激活的扇区边界向外推移 `hysteresis_angle`，避免在边界处来回抖动
+//!
+//! 每个虚拟按键按方向分量判定：`LeftStickUp` 在上分量激活时按下，
+//! 斜向虚拟按键在其上下、左右两个分量同时激活时按下。因此八向模式下推到右上时
+//! `Up`、`Right`、`UpRight` 同时按下（正好对应 W + D），四向模式下只有在重叠区内斜向按键才会触发。
+
+use crate::controller::datas::{ControllerDatas, ControllerStick};
+use crate::controller::slots::PerSlot;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+/// 方向分量位
+const UP: u8 = 0b0001;
+const RIGHT: u8 = 0b0010;
+const DOWN: u8 = 0b0100;
+const LEFT: u8 = 0b1000;
+
+/// 八个扇区（从正上方开始顺时针，每个 45°）对应的方向分量
+const SECTOR_COMPONENTS: [u8; 8] = [
+    UP,
+    UP | RIGHT,
+    RIGHT,
+    DOWN | RIGHT,
+    DOWN,
+    DOWN | LEFT,
+    LEFT,
+    UP | LEFT,
+];
+
+/// 方向划分模式
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StickDirectionMode {
+    /// 上下左右四向
+    FourWay,
+    /// 含斜向的八向
+    #[default]
+    EightWay,
+}
+
+/// 单个摇杆的方向判定配置
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(default)]
+pub struct StickDirectionConfig {
+    pub mode: StickDirectionMode,
+    /// 开始判定方向的摇杆幅度 (0-1)
+    pub activation_radius: f32,
+    /// 已有方向时，幅度低于该值才松开 (0-1)
+    pub release_radius: f32,
+    /// 相邻扇区的重叠角度（度）
+    pub overlap_angle: f32,
+    /// 已激活扇区额外放宽的角度（度）
+    pub hysteresis_angle: f32,
+}
+
+impl Default for StickDirectionConfig {
+    fn default() -> Self {
+        Self {
+            mode: StickDirectionMode::default(),
+            activation_radius: 0.5,
+            release_radius: 0.4,
+            overlap_angle: 0.0,
+            hysteresis_angle: 5.0,
+        }
+    }
+}
+
+/// 上一帧各摇杆激活的扇区（按位，0..8）
+#[derive(Default)]
+struct StickDirectionState {
+    left_sectors: u8,
+    right_sectors: u8,
+}
+
+static STICK_DIRECTION_STATES: Lazy<PerSlot<RwLock<StickDirectionState>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(StickDirectionState::default())));
+
+/// 本帧两个摇杆的方向分量
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StickDirections {
+    left: u8,
+    right: u8,
+}
+
+impl StickDirections {
+    /// 查询方向虚拟按键是否按下，名称不是方向虚拟按键时返回 None
+    pub fn is_pressed(&self, composed_button: &str) -> Option<bool> {
+        let (components, direction) = if let Some(d) = composed_button.strip_prefix("LeftStick") {
+            (self.left, d)
+        } else if let Some(d) = composed_button.strip_prefix("RightStick") {
+            (self.right, d)
+        } else {
+            return None;
+        };
+
+        let required = match direction {
+            "Up" => UP,
+            "Down" => DOWN,
+            "Left" => LEFT,
+            "Right" => RIGHT,
+            "UpLeft" => UP | LEFT,
+            "UpRight" => UP | RIGHT,
+            "DownLeft" => DOWN | LEFT,
+            "DownRight" => DOWN | RIGHT,
+            _ => return None,
+        };
+        Some(components & required == required)
+    }
+}
+
+/// 计算一个摇杆本帧激活的扇区
+fn update_sectors(stick: &ControllerStick, config: &StickDirectionConfig, previous: u8) -> u8 {
+    let magnitude = (stick.x * stick.x + stick.y * stick.y).sqrt();
+    let radius = if previous != 0 {
+        config.release_radius.min(config.activation_radius)
+    } else {
+        config.activation_radius
+    };
+    if magnitude < radius.max(f32::EPSILON) {
+        return 0;
+    }
+
+    // 以正上方为 0°，顺时针为正
+    let angle = stick.x.atan2(stick.y).to_degrees().rem_euclid(360.0);
+    let (step, indices): (f32, &[usize]) = match config.mode {
+        StickDirectionMode::FourWay => (90.0, &[0, 2, 4, 6]),
+        StickDirectionMode::EightWay => (45.0, &[0, 1, 2, 3, 4, 5, 6, 7]),
+    };
+
+    let mut sectors = 0;
+    for &index in indices {
+        let center = index as f32 * 45.0;
+        // 滞回：已激活的扇区放宽、其余扇区收窄同样的角度，边界整体向新方向推移
+        let mut half_width = step / 2.0 + config.overlap_angle.max(0.0) / 2.0;
+        if previous & (1 << index) != 0 {
+            half_width += config.hysteresis_angle.max(0.0);
+        } else if previous != 0 {
+            half_width -= config.hysteresis_angle.max(0.0);
+        }
+        let diff = (angle - center + 180.0).rem_euclid(360.0) - 180.0;
+        if diff.abs() <= half_width {
+            sectors |= 1 << index;
+        }
+    }
+    sectors
+}
+
+fn sectors_to_components(sectors: u8) -> u8 {
+    SECTOR_COMPONENTS
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| sectors & (1 << index) != 0)
+        .fold(0, |acc, (_, components)| acc | components)
+}
+
+/// 更新当前槽位两个摇杆的方向状态，每帧调用一次
+pub fn update(
+    controller_datas: &ControllerDatas,
+    left_config: &StickDirectionConfig,
+    right_config: &StickDirectionConfig,
+) -> StickDirections {
+    let mut state = STICK_DIRECTION_STATES.write().unwrap();
+    state.left_sectors = update_sectors(&controller_datas.left_stick, left_config, state.left_sectors);
+    state.right_sectors =
+        update_sectors(&controller_datas.right_stick, right_config, state.right_sectors);
+
+    StickDirections {
+        left: sectors_to_components(state.left_sectors),
+        right: sectors_to_components(state.right_sectors),
+    }
+}
@@ -0,0 +1,126 @@
+//! 摇杆模拟滚轮
+//!
+//! 开启后摇杆不再需要映射到滚轮按键：上下推动纵向滚动、左右推动横向滚动，
+//! 滚动速度与摇杆幅度成正比。不足一格的滚动量会累积到下一帧，
+//! 慢推时也能平滑地一格一格滚动。
+
+use crate::controller::datas::{ControllerDatas, ControllerStick};
+use crate::controller::slots::PerSlot;
+use crate::mapping::output;
+use crate::mapping::{frame_seconds, log_output_error};
+use crate::preset;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use std::time::Instant;
+
+/// 单个摇杆的滚轮模拟配置
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(default)]
+pub struct StickScrollConfig {
+    pub enabled: bool,
+    /// 摇杆推到底时每秒滚动的格数
+    pub speed: f32,
+    /// 是否启用左右推动的横向滚动
+    pub horizontal: bool,
+    /// 反转纵向滚动方向
+    pub invert: bool,
+}
+
+impl Default for StickScrollConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            speed: 15.0,
+            horizontal: true,
+            invert: false,
+        }
+    }
+}
+
+#[derive(Default)]
+struct ScrollRemainder {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Default)]
+struct StickScrollState {
+    last_update: Option<Instant>,
+    left: ScrollRemainder,
+    right: ScrollRemainder,
+}
+
+static STICK_SCROLL_STATES: Lazy<PerSlot<RwLock<StickScrollState>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(StickScrollState::default())));
+
+/// 累积一个摇杆的滚动量，返回本帧需要滚动的整数格数 (横向, 纵向)
+fn accumulate(
+    stick: &ControllerStick,
+    config: &StickScrollConfig,
+    remainder: &mut ScrollRemainder,
+    dt: f32,
+) -> (i32, i32) {
+    if !config.enabled {
+        *remainder = ScrollRemainder::default();
+        return (0, 0);
+    }
+
+    // 滚轮正值为向下 / 向右，摇杆 Y 轴向上为正，因此纵向默认取反
+    let y_sign = if config.invert { 1.0 } else { -1.0 };
+    remainder.y += stick.y * y_sign * config.speed * dt;
+    if config.horizontal {
+        remainder.x += stick.x * config.speed * dt;
+    } else {
+        remainder.x = 0.0;
+    }
+
+    // 摇杆回中时丢弃残余量，避免下次推动时突然多滚一格
+    if stick.y == 0.0 {
+        remainder.y = 0.0;
+    }
+    if stick.x == 0.0 {
+        remainder.x = 0.0;
+    }
+
+    let scroll_x = remainder.x.trunc();
+    let scroll_y = remainder.y.trunc();
+    remainder.x -= scroll_x;
+    remainder.y -= scroll_y;
+    (scroll_x as i32, scroll_y as i32)
+}
+
+pub fn handle_stick_scroll(controller_datas: &ControllerDatas) {
+    let (left_config, right_config) = {
+        let preset = preset::CURRENT_PRESET.read().unwrap();
+        if !preset.items.stick_scroll_left.enabled && !preset.items.stick_scroll_right.enabled {
+            return;
+        }
+        (
+            preset.items.stick_scroll_left.clone(),
+            preset.items.stick_scroll_right.clone(),
+        )
+    };
+
+    let (scroll_x, scroll_y) = {
+        let mut state = STICK_SCROLL_STATES.write().unwrap();
+        let dt = frame_seconds(&mut state.last_update, Instant::now());
+
+        let (lx, ly) = accumulate(&controller_datas.left_stick, &left_config, &mut state.left, dt);
+        let (rx, ry) = accumulate(&controller_datas.right_stick, &right_config, &mut state.right, dt);
+        (lx + rx, ly + ry)
+    };
+
+    if scroll_x == 0 && scroll_y == 0 {
+        return;
+    }
+
+    output::with_active_sink(|sink| {
+        if scroll_y != 0 {
+            log_output_error(sink.scroll(scroll_y, enigo::Axis::Vertical), "摇杆滚轮");
+        }
+        if scroll_x != 0 {
+            log_output_error(sink.scroll(scroll_x, enigo::Axis::Horizontal), "摇杆滚轮");
+        }
+    });
+}
@@ -0,0 +1,408 @@
+//! 虚拟 Xbox 360 手柄输出 (Linux uinput)
+//!
+//! 部分游戏只认 XInput 风格的手柄布局，这里通过 uinput 创建一个标准的
+//! Xbox 360 手柄（与 xpad 驱动暴露的按键/轴完全一致），映射引擎可以把
+//! 物理手柄的按键重定向到虚拟手柄的按键、摇杆与扳机上。
+//!
+//! - 按键：由映射动作 `PrimaryAction::VirtualPadButton` 驱动，按下计数，多个映射可同时按住同一个键
+//! - 摇杆/扳机：默认透传物理手柄的模拟量，映射动作 `PrimaryAction::VirtualPadAxis` 按住期间覆盖透传值
+//! - 生命周期：`use_device` 时创建，`disconnect_device` 时销毁；每个设备槽位各自拥有一个虚拟手柄
+//!
+//! 非 Linux 平台下所有接口均为空实现。
+
+use crate::controller::datas::ControllerDatas;
+use crate::controller::slots::PerSlot;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[cfg(target_os = "linux")]
+use evdev::uinput::VirtualDevice;
+#[cfg(target_os = "linux")]
+use evdev::{
+    AbsInfo, AbsoluteAxisCode, AbsoluteAxisEvent, AttributeSet, BusType, InputEvent, InputId,
+    KeyCode, KeyEvent, UinputAbsSetup,
+};
+
+/// 虚拟手柄设备名称，设备枚举时据此过滤掉自己
+pub const VIRTUAL_PAD_NAME: &str = "XialloControl Virtual Pad";
+/// 模拟 Microsoft Xbox 360 有线手柄
+const VIRTUAL_PAD_VID: u16 = 0x045e;
+const VIRTUAL_PAD_PID: u16 = 0x028e;
+const VIRTUAL_PAD_VERSION: u16 = 0x0110;
+
+const STICK_MAX: i32 = 32767;
+const TRIGGER_MAX: i32 = 255;
+
+/// 虚拟手柄按键
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Hash)]
+pub enum PadButton {
+    A,
+    B,
+    X,
+    Y,
+    LB,
+    RB,
+    Back,
+    Start,
+    Guide,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+/// 虚拟手柄模拟轴
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Hash)]
+pub enum PadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl PadAxis {
+    const ALL: [PadAxis; 6] = [
+        PadAxis::LeftStickX,
+        PadAxis::LeftStickY,
+        PadAxis::RightStickX,
+        PadAxis::RightStickY,
+        PadAxis::LeftTrigger,
+        PadAxis::RightTrigger,
+    ];
+
+    /// 扳机轴取值 [0, 1]，摇杆轴取值 [-1, 1]
+    pub fn is_trigger(&self) -> bool {
+        matches!(self, PadAxis::LeftTrigger | PadAxis::RightTrigger)
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// 快捷键字符串中 `Pad:xxx` 解析出的目标
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PadTarget {
+    Button(PadButton),
+    Axis(PadAxis, f32),
+}
+
+/// 解析 `Pad:` 前缀之后的部分（已转小写），例如 "a"、"lt"、"leftstickup"
+pub fn parse_pad_token(token: &str) -> Option<PadTarget> {
+    let target = match token {
+        "a" => PadTarget::Button(PadButton::A),
+        "b" => PadTarget::Button(PadButton::B),
+        "x" => PadTarget::Button(PadButton::X),
+        "y" => PadTarget::Button(PadButton::Y),
+        "lb" => PadTarget::Button(PadButton::LB),
+        "rb" => PadTarget::Button(PadButton::RB),
+        "back" => PadTarget::Button(PadButton::Back),
+        "start" => PadTarget::Button(PadButton::Start),
+        "guide" => PadTarget::Button(PadButton::Guide),
+        "leftstick" | "ls" => PadTarget::Button(PadButton::LeftThumb),
+        "rightstick" | "rs" => PadTarget::Button(PadButton::RightThumb),
+        "dpadup" => PadTarget::Button(PadButton::DPadUp),
+        "dpaddown" => PadTarget::Button(PadButton::DPadDown),
+        "dpadleft" => PadTarget::Button(PadButton::DPadLeft),
+        "dpadright" => PadTarget::Button(PadButton::DPadRight),
+        "lt" => PadTarget::Axis(PadAxis::LeftTrigger, 1.0),
+        "rt" => PadTarget::Axis(PadAxis::RightTrigger, 1.0),
+        "leftstickup" => PadTarget::Axis(PadAxis::LeftStickY, 1.0),
+        "leftstickdown" => PadTarget::Axis(PadAxis::LeftStickY, -1.0),
+        "leftstickleft" => PadTarget::Axis(PadAxis::LeftStickX, -1.0),
+        "leftstickright" => PadTarget::Axis(PadAxis::LeftStickX, 1.0),
+        "rightstickup" => PadTarget::Axis(PadAxis::RightStickY, 1.0),
+        "rightstickdown" => PadTarget::Axis(PadAxis::RightStickY, -1.0),
+        "rightstickleft" => PadTarget::Axis(PadAxis::RightStickX, -1.0),
+        "rightstickright" => PadTarget::Axis(PadAxis::RightStickX, 1.0),
+        _ => return None,
+    };
+    Some(target)
+}
+
+/// 虚拟手柄的逻辑状态（与平台无关）
+#[derive(Debug, Default)]
+struct PadState {
+    /// 每个按键被多少个映射按住
+    button_holds: HashMap<PadButton, u32>,
+    /// 物理手柄透传的模拟量
+    passthrough: [f32; 6],
+    /// 映射动作对模拟轴的覆盖值，按下顺序入栈，取栈顶
+    axis_overrides: HashMap<PadAxis, Vec<f32>>,
+}
+
+impl PadState {
+    fn is_pressed(&self, button: PadButton) -> bool {
+        self.button_holds.get(&button).is_some_and(|n| *n > 0)
+    }
+
+    fn axis_value(&self, axis: PadAxis) -> f32 {
+        self.axis_overrides
+            .get(&axis)
+            .and_then(|stack| stack.last().copied())
+            .unwrap_or(self.passthrough[axis.index()])
+    }
+}
+
+/// 已创建的虚拟手柄
+pub struct VirtualPad {
+    #[cfg(target_os = "linux")]
+    device: VirtualDevice,
+    state: PadState,
+    /// 上一次写入设备的原始值，只发送有变化的事件
+    #[cfg(target_os = "linux")]
+    emitted_keys: HashMap<PadButton, i32>,
+    #[cfg(target_os = "linux")]
+    emitted_abs: HashMap<AbsoluteAxisCode, i32>,
+}
+
+/// 各槽位的虚拟手柄实例（映射线程与监听线程共享）
+static VIRTUAL_PAD: Lazy<PerSlot<Mutex<Option<VirtualPad>>>> =
+    Lazy::new(|| PerSlot::new(|| Mutex::new(None)));
+
+#[cfg(target_os = "linux")]
+impl PadButton {
+    const ALL: [PadButton; 11] = [
+        PadButton::A,
+        PadButton::B,
+        PadButton::X,
+        PadButton::Y,
+        PadButton::LB,
+        PadButton::RB,
+        PadButton::Back,
+        PadButton::Start,
+        PadButton::Guide,
+        PadButton::LeftThumb,
+        PadButton::RightThumb,
+    ];
+
+    /// 与 xpad 驱动一致的按键编号（十字键走 HAT 轴，不在此列）
+    fn key_code(&self) -> Option<KeyCode> {
+        let code = match self {
+            PadButton::A => KeyCode::BTN_SOUTH,
+            PadButton::B => KeyCode::BTN_EAST,
+            PadButton::X => KeyCode::BTN_WEST,
+            PadButton::Y => KeyCode::BTN_NORTH,
+            PadButton::LB => KeyCode::BTN_TL,
+            PadButton::RB => KeyCode::BTN_TR,
+            PadButton::Back => KeyCode::BTN_SELECT,
+            PadButton::Start => KeyCode::BTN_START,
+            PadButton::Guide => KeyCode::BTN_MODE,
+            PadButton::LeftThumb => KeyCode::BTN_THUMBL,
+            PadButton::RightThumb => KeyCode::BTN_THUMBR,
+            _ => return None,
+        };
+        Some(code)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl PadAxis {
+    fn abs_code(&self) -> AbsoluteAxisCode {
+        match self {
+            PadAxis::LeftStickX => AbsoluteAxisCode::ABS_X,
+            PadAxis::LeftStickY => AbsoluteAxisCode::ABS_Y,
+            PadAxis::RightStickX => AbsoluteAxisCode::ABS_RX,
+            PadAxis::RightStickY => AbsoluteAxisCode::ABS_RY,
+            PadAxis::LeftTrigger => AbsoluteAxisCode::ABS_Z,
+            PadAxis::RightTrigger => AbsoluteAxisCode::ABS_RZ,
+        }
+    }
+
+    /// 逻辑值转 evdev 原始值（evdev Y 轴向下为正，与内部约定相反）
+    fn to_raw(&self, value: f32) -> i32 {
+        match self {
+            PadAxis::LeftTrigger | PadAxis::RightTrigger => {
+                (value.clamp(0.0, 1.0) * TRIGGER_MAX as f32).round() as i32
+            }
+            PadAxis::LeftStickY | PadAxis::RightStickY => {
+                (-value.clamp(-1.0, 1.0) * STICK_MAX as f32).round() as i32
+            }
+            PadAxis::LeftStickX | PadAxis::RightStickX => {
+                (value.clamp(-1.0, 1.0) * STICK_MAX as f32).round() as i32
+            }
+        }
+    }
+}
+
+impl VirtualPad {
+    #[cfg(target_os = "linux")]
+    fn new() -> std::io::Result<Self> {
+        let keys: AttributeSet<KeyCode> = PadButton::ALL.iter().filter_map(|b| b.key_code()).collect();
+
+        let stick_info = AbsInfo::new(0, -STICK_MAX - 1, STICK_MAX, 16, 128, 0);
+        let trigger_info = AbsInfo::new(0, 0, TRIGGER_MAX, 0, 0, 0);
+        let hat_info = AbsInfo::new(0, -1, 1, 0, 0, 0);
+
+        let mut builder = VirtualDevice::builder()?
+            .name(VIRTUAL_PAD_NAME)
+            .input_id(InputId::new(
+                BusType::BUS_USB,
+                VIRTUAL_PAD_VID,
+                VIRTUAL_PAD_PID,
+                VIRTUAL_PAD_VERSION,
+            ))
+            .with_keys(&keys)?;
+        for axis in PadAxis::ALL {
+            let info = if axis.is_trigger() { trigger_info } else { stick_info };
+            builder = builder.with_absolute_axis(&UinputAbsSetup::new(axis.abs_code(), info))?;
+        }
+        for hat in [AbsoluteAxisCode::ABS_HAT0X, AbsoluteAxisCode::ABS_HAT0Y] {
+            builder = builder.with_absolute_axis(&UinputAbsSetup::new(hat, hat_info))?;
+        }
+
+        Ok(Self {
+            device: builder.build()?,
+            state: PadState::default(),
+            emitted_keys: HashMap::new(),
+            emitted_abs: HashMap::new(),
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn new() -> std::io::Result<Self> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "虚拟手柄仅支持 Linux (uinput)",
+        ))
+    }
+
+    /// 将当前逻辑状态写入 uinput 设备，只发送有变化的部分
+    #[cfg(target_os = "linux")]
+    fn flush(&mut self) {
+        let mut events: Vec<InputEvent> = Vec::new();
+
+        for button in PadButton::ALL {
+            let value = self.state.is_pressed(button) as i32;
+            if self.emitted_keys.get(&button) != Some(&value) {
+                self.emitted_keys.insert(button, value);
+                if let Some(code) = button.key_code() {
+                    events.push(*KeyEvent::new(code, value));
+                }
+            }
+        }
+
+        let hat_x = self.state.is_pressed(PadButton::DPadRight) as i32
+            - self.state.is_pressed(PadButton::DPadLeft) as i32;
+        let hat_y = self.state.is_pressed(PadButton::DPadDown) as i32
+            - self.state.is_pressed(PadButton::DPadUp) as i32;
+        let mut abs_values = vec![
+            (AbsoluteAxisCode::ABS_HAT0X, hat_x),
+            (AbsoluteAxisCode::ABS_HAT0Y, hat_y),
+        ];
+        for axis in PadAxis::ALL {
+            abs_values.push((axis.abs_code(), axis.to_raw(self.state.axis_value(axis))));
+        }
+        for (code, value) in abs_values {
+            if self.emitted_abs.get(&code) != Some(&value) {
+                self.emitted_abs.insert(code, value);
+                events.push(*AbsoluteAxisEvent::new(code, value));
+            }
+        }
+
+        if events.is_empty() {
+            return;
+        }
+        if let Err(e) = self.device.emit(&events) {
+            log::error!("虚拟手柄写入事件失败: {e}");
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn flush(&mut self) {}
+}
+
+/// 创建虚拟手柄（已存在则直接返回 true）
+pub fn create() -> bool {
+    let mut guard = VIRTUAL_PAD.lock().unwrap();
+    if guard.is_some() {
+        return true;
+    }
+    match VirtualPad::new() {
+        Ok(pad) => {
+            *guard = Some(pad);
+            log::info!("✅ 已创建虚拟手柄: {VIRTUAL_PAD_NAME}");
+            true
+        }
+        Err(e) => {
+            log::error!("❌ 创建虚拟手柄失败（请检查 /dev/uinput 权限）: {e}");
+            false
+        }
+    }
+}
+
+/// 销毁虚拟手柄，uinput 设备随 fd 关闭自动移除
+pub fn destroy() {
+    if VIRTUAL_PAD.lock().unwrap().take().is_some() {
+        log::info!("虚拟手柄已销毁");
+    }
+}
+
+/// 虚拟手柄是否已创建
+pub fn is_active() -> bool {
+    VIRTUAL_PAD.lock().unwrap().is_some()
+}
+
+fn with_pad(f: impl FnOnce(&mut VirtualPad)) {
+    let mut guard = VIRTUAL_PAD.lock().unwrap();
+    match guard.as_mut() {
+        Some(pad) => {
+            f(pad);
+            pad.flush();
+        }
+        None => {
+            static WARN_CNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+            let cnt = WARN_CNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            if cnt % 125 == 0 {
+                log::warn!("虚拟手柄未创建，忽略虚拟手柄动作（请在设置中启用虚拟手柄）");
+            }
+        }
+    }
+}
+
+/// 按下/释放虚拟手柄按键
+pub fn set_button(button: PadButton, pressed: bool) {
+    with_pad(|pad| {
+        let holds = pad.state.button_holds.entry(button).or_insert(0);
+        if pressed {
+            *holds += 1;
+        } else {
+            *holds = holds.saturating_sub(1);
+        }
+    });
+}
+
+/// 压入/弹出模拟轴覆盖值
+pub fn set_axis_override(axis: PadAxis, value: f32, active: bool) {
+    with_pad(|pad| {
+        let stack = pad.state.axis_overrides.entry(axis).or_default();
+        if active {
+            stack.push(value);
+        } else if let Some(pos) = stack.iter().rposition(|v| *v == value) {
+            stack.remove(pos);
+        }
+    });
+}
+
+/// 将物理手柄的摇杆与扳机透传到虚拟手柄（由监听线程每帧调用）
+pub fn sync_analog(controller_datas: &ControllerDatas) {
+    let mut guard = VIRTUAL_PAD.lock().unwrap();
+    let Some(pad) = guard.as_mut() else {
+        return;
+    };
+    pad.state.passthrough = [
+        controller_datas.left_stick.x,
+        controller_datas.left_stick.y,
+        controller_datas.right_stick.x,
+        controller_datas.right_stick.y,
+        controller_datas.left_trigger.value,
+        controller_datas.right_trigger.value,
+    ];
+    pad.flush();
+}
@@ -0,0 +1,269 @@
+//! 叠加层状态推送（本机 WebSocket）
+//!
+//! 把前端使用的紧凑手柄数据流（`update_controller_compact_datas`）以及预设 / 副预设状态
+//! 通过 `ws://127.0.0.1:<端口>` 推送出去，OBS 浏览器源、外部可视化工具无需截取窗口即可显示手柄。
+//!
+//! 只推送主槽位，与界面显示一致。每条消息是一个 JSON 文本帧：
+//! ```text
+//! {"type":"controller","buttons":5,"pressure":{"left_stick_x":0.0,...}}
+//! {"type":"preset","preset":"默认","sub_preset":"绘图","sub_preset_active":false}
+//! ```
+//! `buttons` 的位序与 `ControllerButtons` 相同。新客户端连接后立即收到一份当前状态。
+//! 推送只读，客户端发来的消息会被忽略。
+
+use crate::controller::datas::CompactControllerDatas;
+use crate::controller::slots::PRIMARY_SLOT;
+use crate::setting::AppSettings;
+use crate::{controller, preset, setting};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tungstenite::{Message, WebSocket};
+
+/// 每个客户端待发送消息的队列长度
+const CLIENT_QUEUE_SIZE: usize = 128;
+/// 监听线程检查服务是否被停止的间隔
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(200);
+/// 端口被占用时的重试次数
+const BIND_RETRIES: u32 = 5;
+/// 客户端空闲时检查连接状态的间隔
+const CLIENT_IDLE_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct PresetState {
+    preset: String,
+    sub_preset: Option<String>,
+    sub_preset_active: bool,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum OverlayMessage<'a> {
+    Controller(CompactControllerDatas),
+    Preset(&'a PresetState),
+}
+
+impl OverlayMessage<'_> {
+    fn to_text(&self) -> Option<String> {
+        serde_json::to_string(self)
+            .map_err(|e| log::error!("序列化叠加层消息失败: {e}"))
+            .ok()
+    }
+}
+
+/// 服务代数，每次启动 / 停止加一，旧的监听线程与客户端据此退出
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+static CLIENTS: Lazy<Mutex<Vec<SyncSender<String>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// 是否有客户端连接（无客户端时跳过序列化）
+static HAS_CLIENTS: AtomicBool = AtomicBool::new(false);
+static LAST_PRESET_STATE: Lazy<Mutex<Option<PresetState>>> = Lazy::new(|| Mutex::new(None));
+
+fn broadcast(message: String) {
+    let mut clients = CLIENTS.lock().unwrap();
+    clients.retain(|tx| match tx.try_send(message.clone()) {
+        // 队列满说明客户端读取过慢，丢弃这一帧即可，下一帧会带上最新状态
+        Ok(()) | Err(TrySendError::Full(_)) => true,
+        Err(TrySendError::Disconnected(_)) => false,
+    });
+    HAS_CLIENTS.store(!clients.is_empty(), Ordering::Relaxed);
+}
+
+/// 推送手柄数据（由 `controller::pack_and_send_data` 在数据变化时调用）
+pub fn publish_controller(data: CompactControllerDatas) {
+    if !HAS_CLIENTS.load(Ordering::Relaxed) {
+        return;
+    }
+    if let Some(text) = OverlayMessage::Controller(data).to_text() {
+        broadcast(text);
+    }
+}
+
+/// 读取当前槽位的预设状态
+fn current_preset_state(sub_preset_active: bool) -> PresetState {
+    let sub_preset = preset::CURRENT_SUB_PRESET
+        .read()
+        .unwrap()
+        .as_ref()
+        .map(|p| p.name.clone());
+    PresetState {
+        preset: preset::CURRENT_PRESET.read().unwrap().name.clone(),
+        sub_preset_active: sub_preset_active && sub_preset.is_some(),
+        sub_preset,
+    }
+}
+
+/// 同步预设 / 副预设状态，变化时推送（在主槽位的监听周期中调用）
+pub fn sync_preset_state(use_sub_preset: bool) {
+    if !HAS_CLIENTS.load(Ordering::Relaxed) {
+        return;
+    }
+    let state = current_preset_state(use_sub_preset);
+    let mut last = LAST_PRESET_STATE.lock().unwrap();
+    if last.as_ref() == Some(&state) {
+        return;
+    }
+    if let Some(text) = OverlayMessage::Preset(&state).to_text() {
+        broadcast(text);
+    }
+    *last = Some(state);
+}
+
+/// 新连接的客户端先收到一份完整状态
+fn initial_messages() -> Vec<String> {
+    let data = controller::get_controller_data(Some(PRIMARY_SLOT)).as_compact();
+    let preset_state = LAST_PRESET_STATE
+        .lock()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| current_preset_state(false));
+    [
+        OverlayMessage::Preset(&preset_state).to_text(),
+        OverlayMessage::Controller(data).to_text(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// 丢弃客户端发来的数据，返回连接是否仍然可用
+fn drain_incoming(socket: &mut WebSocket<TcpStream>) -> bool {
+    loop {
+        match socket.read() {
+            Ok(Message::Close(_)) => return false,
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+            {
+                return true;
+            }
+            Err(_) => return false,
+        }
+    }
+}
+
+fn client_loop(mut socket: WebSocket<TcpStream>, rx: Receiver<String>, generation: u64) {
+    while GENERATION.load(Ordering::Relaxed) == generation {
+        match rx.recv_timeout(CLIENT_IDLE_INTERVAL) {
+            Ok(text) => {
+                if socket.send(Message::text(text)).is_err() {
+                    break;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                // 空闲时处理 Ping / Close，及时发现已断开的客户端
+                if !drain_incoming(&mut socket) {
+                    break;
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    let _ = socket.close(None);
+    let _ = socket.flush();
+}
+
+fn handle_connection(stream: TcpStream, generation: u64) {
+    let peer = stream.peer_addr().ok();
+    let _ = stream.set_nonblocking(false);
+    let mut socket = match tungstenite::accept(stream) {
+        Ok(s) => s,
+        Err(e) => {
+            log::warn!("叠加层客户端握手失败 ({peer:?}): {e}");
+            return;
+        }
+    };
+    // 读取只用于处理控制帧，不能阻塞推送
+    if let Err(e) = socket.get_ref().set_read_timeout(Some(Duration::from_millis(1))) {
+        log::warn!("设置叠加层连接超时失败: {e}");
+    }
+
+    let (tx, rx) = sync_channel::<String>(CLIENT_QUEUE_SIZE);
+    for text in initial_messages() {
+        let _ = tx.try_send(text);
+    }
+    {
+        let mut clients = CLIENTS.lock().unwrap();
+        clients.push(tx);
+        HAS_CLIENTS.store(true, Ordering::Relaxed);
+    }
+    log::info!("叠加层客户端已连接: {peer:?}");
+
+    client_loop(socket, rx, generation);
+    log::info!("叠加层客户端已断开: {peer:?}");
+}
+
+/// 绑定端口；重启时旧的监听线程最多需要一个检查间隔才会释放端口，因此稍作重试
+fn bind(port: u16) -> std::io::Result<TcpListener> {
+    let mut attempts = 0;
+    loop {
+        match TcpListener::bind((Ipv4Addr::LOCALHOST, port)) {
+            Err(e) if e.kind() == ErrorKind::AddrInUse && attempts < BIND_RETRIES => {
+                attempts += 1;
+                thread::sleep(ACCEPT_INTERVAL);
+            }
+            result => return result,
+        }
+    }
+}
+
+fn start(port: u16) {
+    let listener = match bind(port) {
+        Ok(l) => l,
+        Err(e) => {
+            log::error!("启动叠加层推送服务失败 (端口 {port}): {e}");
+            return;
+        }
+    };
+    if let Err(e) = listener.set_nonblocking(true) {
+        log::error!("设置叠加层监听为非阻塞失败: {e}");
+        return;
+    }
+
+    let generation = GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+    log::info!("📺 叠加层推送服务已启动: ws://127.0.0.1:{port}");
+
+    thread::spawn(move || {
+        while GENERATION.load(Ordering::Relaxed) == generation {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    thread::spawn(move || handle_connection(stream, generation));
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(ACCEPT_INTERVAL),
+                Err(e) => {
+                    log::error!("接受叠加层连接失败: {e}");
+                    thread::sleep(ACCEPT_INTERVAL);
+                }
+            }
+        }
+        log::info!("叠加层推送服务已停止 (端口 {port})");
+    });
+}
+
+fn stop() {
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+    CLIENTS.lock().unwrap().clear();
+    HAS_CLIENTS.store(false, Ordering::Relaxed);
+    *LAST_PRESET_STATE.lock().unwrap() = None;
+}
+
+/// 按设置启动、重启或停止推送服务
+pub fn apply_settings(settings: &AppSettings) {
+    stop();
+    if settings.overlay_server_enabled {
+        let port = settings.overlay_server_port;
+        thread::spawn(move || start(port));
+    }
+}
+
+pub fn initialize() {
+    let settings = setting::get_setting();
+    if settings.overlay_server_enabled {
+        start(settings.overlay_server_port);
+    }
+}
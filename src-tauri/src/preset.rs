@@ -9,9 +9,15 @@ pub mod xbox_example;
 use crate::app_state::AppState;
 use crate::setting::get_setting;
 use crate::xeno_utils::ensure_dir;
-use crate::{mapping, xeno_utils};
+use crate::{controller, mapping, xeno_utils};
 use crate::preset::preset_data::PresetData;
+use crate::controller::curve::ResponseCurve;
+use crate::controller::led;
+use crate::controller::slots::{self, PerSlot};
 use crate::mapping::{MappingUpdateConfig, CheckMode, TriggerState, save_mappings_to_file, create_empty_mapping_file, create_mapping_from_config};
+use crate::mapping::output::OutputSinkKind;
+use crate::mapping::stick_direction::StickDirectionConfig;
+use crate::mapping::stick_scroll::StickScrollConfig;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -23,16 +29,19 @@ const DEFAULT_PRESET_NAME: &str = "default";
 const DEFAULT_MAPPINGS_FILE: &str = "mappings.toml";
 
 const DEFAULT_DEADZONE: u8 = 5; // 10%
+const DEFAULT_GYRO_SENSITIVITY: f32 = 10.0;
+const DEFAULT_GYRO_SMOOTHING_THRESHOLD: f32 = 5.0; // deg/s
 
-// 当前预设
-pub static CURRENT_PRESET: Lazy<RwLock<Preset>> =
-    Lazy::new(|| RwLock::new(Preset::new(DEFAULT_PRESET_NAME.to_string())));
+// 当前预设（每个槽位独立）
+pub static CURRENT_PRESET: Lazy<PerSlot<RwLock<Preset>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(Preset::new(DEFAULT_PRESET_NAME.to_string()))));
 
 // 预设列表
 pub static CURRENT_PRESET_LIST: Lazy<RwLock<Vec<Preset>>> = Lazy::new(|| RwLock::new(vec![]));
 
-// 当前副预设
-pub static CURRENT_SUB_PRESET: Lazy<RwLock<Option<Preset>>> = Lazy::new(|| RwLock::new(None));
+// 当前副预设（每个槽位独立）
+pub static CURRENT_SUB_PRESET: Lazy<PerSlot<RwLock<Option<Preset>>>> =
+    Lazy::new(|| PerSlot::new(|| RwLock::new(None)));
 
 #[derive(Debug)]
 pub struct PresetManager {
@@ -69,12 +78,47 @@ pub struct PresetItems {
     #[serde(default = "default_deadzone")]
     pub deadzone_left: u8,
 
+    /// 右 / 左摇杆外死区 (%)，推到 100% - 外死区即视为推到底
+    #[serde(default)]
+    pub outer_deadzone: u8,
+    #[serde(default)]
+    pub outer_deadzone_left: u8,
+
+    /// 右 / 左摇杆反死区 (%)，离开内死区后输出直接从该值起步
+    #[serde(default)]
+    pub anti_deadzone: u8,
+    #[serde(default)]
+    pub anti_deadzone_left: u8,
+
+    /// 扳机内死区 (%)，用于回不到 0 的扳机
+    #[serde(default)]
+    pub trigger_deadzone_left: u8,
+    #[serde(default)]
+    pub trigger_deadzone_right: u8,
+    /// 扳机外死区 (%)，用于按不到底的扳机
+    #[serde(default)]
+    pub trigger_outer_deadzone_left: u8,
+    #[serde(default)]
+    pub trigger_outer_deadzone_right: u8,
+
+    /// 摇杆响应曲线，作用于死区之外的幅度
+    #[serde(default)]
+    pub stick_curve_left: ResponseCurve,
+    #[serde(default)]
+    pub stick_curve_right: ResponseCurve,
+
     /// 摇杆模拟鼠标相关配置
     #[serde(default)]
     pub use_stick_as_mouse: bool,
     #[serde(default)]
     pub stick_as_mouse_simulation: Option<String>,
 
+    /// 摇杆模拟滚轮相关配置
+    #[serde(default)]
+    pub stick_scroll_left: StickScrollConfig,
+    #[serde(default)]
+    pub stick_scroll_right: StickScrollConfig,
+
     // --- 副预设相关配置 ---
     /// 指定的副预设名称
     #[serde(default)]
@@ -89,9 +133,46 @@ pub struct PresetItems {
     #[serde(default)]
     pub stick_rotate_trigger_threshold: i16,
 
+    /// 摇杆方向虚拟按键（LeftStickUp 等）的判定配置
+    #[serde(default)]
+    pub stick_direction_left: StickDirectionConfig,
+    #[serde(default)]
+    pub stick_direction_right: StickDirectionConfig,
+
     /// 鼠标移动速度 (1-100)
     #[serde(default)]
     pub move_speed: u8,
+
+    // --- 陀螺仪模拟鼠标相关配置 ---
+    #[serde(default)]
+    pub use_gyro_as_mouse: bool,
+    /// 灵敏度：手柄每转动 1° 鼠标移动的像素数
+    #[serde(default = "default_gyro_sensitivity")]
+    pub gyro_sensitivity: f32,
+    /// 坐标空间："local"（手柄自身坐标）或 "player"（以重力方向为准）
+    #[serde(default)]
+    pub gyro_space: Option<String>,
+    /// 陀螺仪启用键，为空时始终启用
+    #[serde(default)]
+    pub gyro_enable_button: Option<String>,
+    /// 启用键模式："Hold" / "Toggle" / "Ratchet"（按住暂停）
+    #[serde(default)]
+    pub gyro_enable_mode: Option<String>,
+    /// 小幅度平滑阈值 (deg/s)，低于该角速度的抖动会被平滑
+    #[serde(default = "default_gyro_smoothing_threshold")]
+    pub gyro_smoothing_threshold: f32,
+
+    // --- 手柄灯光 ---
+    /// 灯条颜色 "#RRGGBB"，为空时使用槽位默认颜色（仅 PlayStation 手柄）
+    #[serde(default)]
+    pub led_color: Option<String>,
+    /// 玩家指示灯编号 (1-4)，为空时使用槽位编号
+    #[serde(default)]
+    pub player_led: Option<u8>,
+
+    /// 键鼠输出后端
+    #[serde(default)]
+    pub output_sink: OutputSinkKind,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -109,13 +190,36 @@ impl Preset {
                 mappings_file_name: DEFAULT_MAPPINGS_FILE.into(),
                 deadzone: DEFAULT_DEADZONE,
                 deadzone_left: DEFAULT_DEADZONE,
+                outer_deadzone: 0,
+                outer_deadzone_left: 0,
+                anti_deadzone: 0,
+                anti_deadzone_left: 0,
+                trigger_deadzone_left: 0,
+                trigger_deadzone_right: 0,
+                trigger_outer_deadzone_left: 0,
+                trigger_outer_deadzone_right: 0,
+                stick_curve_left: ResponseCurve::default(),
+                stick_curve_right: ResponseCurve::default(),
                 use_stick_as_mouse: false,
                 stick_as_mouse_simulation: None,
+                stick_scroll_left: StickScrollConfig::default(),
+                stick_scroll_right: StickScrollConfig::default(),
                 sub_preset_name: None,
                 sub_preset_switch_button: None,
                 sub_preset_switch_mode: None,
                 stick_rotate_trigger_threshold: 15,
+                stick_direction_left: StickDirectionConfig::default(),
+                stick_direction_right: StickDirectionConfig::default(),
                 move_speed: 20,
+                use_gyro_as_mouse: false,
+                gyro_sensitivity: DEFAULT_GYRO_SENSITIVITY,
+                gyro_space: None,
+                gyro_enable_button: None,
+                gyro_enable_mode: None,
+                gyro_smoothing_threshold: DEFAULT_GYRO_SMOOTHING_THRESHOLD,
+                led_color: None,
+                player_led: None,
+                output_sink: OutputSinkKind::default(),
             },
         }
     }
@@ -185,6 +289,14 @@ fn default_deadzone() -> u8 {
     DEFAULT_DEADZONE
 }
 
+fn default_gyro_sensitivity() -> f32 {
+    DEFAULT_GYRO_SENSITIVITY
+}
+
+fn default_gyro_smoothing_threshold() -> f32 {
+    DEFAULT_GYRO_SMOOTHING_THRESHOLD
+}
+
 /// 创建新的预设
 #[tauri::command]
 pub fn create_preset(name: &str) -> Result<Preset, String> {
@@ -345,12 +457,25 @@ pub fn switch_to_preset(name: &str) -> Result<Preset, String> {
             "成功切换到预设: {}",
             mapping::get_mapping_file_path().display()
         );
-        Ok(preset.clone())
+        let loaded = preset.clone();
+        drop(preset);
+        controller::emit_event(
+            "preset_changed",
+            serde_json::json!({ "slot": slots::active_slot(), "name": loaded.name }),
+        );
+        Ok(loaded)
     } else {
         Err("加载预设失败".to_string())
     }
 }
 
+/// 为指定槽位切换预设（每个手柄槽位可使用不同的预设）
+#[tauri::command]
+pub fn switch_slot_preset(slot: usize, name: &str) -> Result<Preset, String> {
+    let slot = slots::resolve_slot(Some(slot))?;
+    slots::with_slot(slot, || switch_to_preset(name))
+}
+
 #[tauri::command]
 pub fn load_preset(name: &str) -> Preset {
     let mut preset = CURRENT_PRESET.write().unwrap();
@@ -422,6 +547,14 @@ pub fn initialize(state: Arc<AppState>) {
 #[tauri::command]
 pub fn update_deadzone(deadzone: u8, deadzone_left: u8) -> Result<(), String> {
     let mut preset = CURRENT_PRESET.write().unwrap();
+    // 与 update_deadzone_shaping 相同：内外死区之和必须留出有效行程
+    let ranges = [
+        (deadzone, preset.items.outer_deadzone),
+        (deadzone_left, preset.items.outer_deadzone_left),
+    ];
+    if ranges.iter().any(|(inner, outer)| *inner as u16 + *outer as u16 >= 100) {
+        return Err("内死区与外死区之和必须小于 100%".to_string());
+    }
     preset.set_deadzone(deadzone);
     preset.set_deadzone_left(deadzone_left);
     if preset.save() {
@@ -431,6 +564,67 @@ pub fn update_deadzone(deadzone: u8, deadzone_left: u8) -> Result<(), String> {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub fn update_deadzone_shaping(
+    outer_deadzone: u8,
+    outer_deadzone_left: u8,
+    anti_deadzone: u8,
+    anti_deadzone_left: u8,
+    trigger_deadzone_left: u8,
+    trigger_deadzone_right: u8,
+    trigger_outer_deadzone_left: u8,
+    trigger_outer_deadzone_right: u8,
+) -> Result<(), String> {
+    if anti_deadzone >= 100 || anti_deadzone_left >= 100 {
+        return Err("反死区必须小于 100%".to_string());
+    }
+
+    let mut preset = CURRENT_PRESET.write().unwrap();
+    let items = &preset.items;
+    // 内外死区之和必须留出有效行程
+    let ranges = [
+        (items.deadzone, outer_deadzone),
+        (items.deadzone_left, outer_deadzone_left),
+        (trigger_deadzone_left, trigger_outer_deadzone_left),
+        (trigger_deadzone_right, trigger_outer_deadzone_right),
+    ];
+    if ranges.iter().any(|(inner, outer)| *inner as u16 + *outer as u16 >= 100) {
+        return Err("内死区与外死区之和必须小于 100%".to_string());
+    }
+
+    preset.items.outer_deadzone = outer_deadzone;
+    preset.items.outer_deadzone_left = outer_deadzone_left;
+    preset.items.anti_deadzone = anti_deadzone;
+    preset.items.anti_deadzone_left = anti_deadzone_left;
+    preset.items.trigger_deadzone_left = trigger_deadzone_left;
+    preset.items.trigger_deadzone_right = trigger_deadzone_right;
+    preset.items.trigger_outer_deadzone_left = trigger_outer_deadzone_left;
+    preset.items.trigger_outer_deadzone_right = trigger_outer_deadzone_right;
+    if preset.save() {
+        Ok(())
+    } else {
+        Err("Failed to save preset".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_stick_curve(stick: String, curve: ResponseCurve) -> Result<(), String> {
+    curve.validate()?;
+
+    let mut preset = CURRENT_PRESET.write().unwrap();
+    match stick.as_str() {
+        "left" => preset.items.stick_curve_left = curve,
+        "right" => preset.items.stick_curve_right = curve,
+        _ => return Err(format!("未知的摇杆: {stick}")),
+    }
+    if preset.save() {
+        Ok(())
+    } else {
+        Err("Failed to save preset".to_string())
+    }
+}
+
 #[tauri::command]
 pub fn update_stick_as_mouse(
     use_stick_as_mouse: bool,
@@ -456,6 +650,53 @@ pub fn update_stick_rotation_threshold(threshold: i16) -> Result<(), String> {
         Err("Failed to save preset".to_string())
     }
 }
+
+#[tauri::command]
+pub fn update_stick_scroll(stick: String, config: StickScrollConfig) -> Result<(), String> {
+    if !(config.speed > 0.0 && config.speed <= 100.0) {
+        return Err("滚动速度必须在 0-100 格/秒范围内".to_string());
+    }
+
+    let mut preset = CURRENT_PRESET.write().unwrap();
+    match stick.as_str() {
+        "left" => preset.items.stick_scroll_left = config,
+        "right" => preset.items.stick_scroll_right = config,
+        _ => return Err(format!("未知的摇杆: {stick}")),
+    }
+    if preset.save() {
+        Ok(())
+    } else {
+        Err("Failed to save preset".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_stick_direction(stick: String, config: StickDirectionConfig) -> Result<(), String> {
+    if !(0.0..=1.0).contains(&config.activation_radius)
+        || !(0.0..=1.0).contains(&config.release_radius)
+    {
+        return Err("方向判定半径必须在 0-1 范围内".to_string());
+    }
+    if config.release_radius > config.activation_radius {
+        return Err("松开半径不能大于激活半径".to_string());
+    }
+    if !(0.0..=45.0).contains(&config.overlap_angle) || !(0.0..=20.0).contains(&config.hysteresis_angle) {
+        return Err("重叠角度必须在 0-45°、滞回角度必须在 0-20° 范围内".to_string());
+    }
+
+    let mut preset = CURRENT_PRESET.write().unwrap();
+    match stick.as_str() {
+        "left" => preset.items.stick_direction_left = config,
+        "right" => preset.items.stick_direction_right = config,
+        _ => return Err(format!("未知的摇杆: {stick}")),
+    }
+    if preset.save() {
+        Ok(())
+    } else {
+        Err("Failed to save preset".to_string())
+    }
+}
+
 #[tauri::command]
 pub fn update_mouse_move_speed(move_speed: u8) -> Result<(), String> {
     let mut preset = CURRENT_PRESET.write().unwrap();
@@ -467,6 +708,73 @@ pub fn update_mouse_move_speed(move_speed: u8) -> Result<(), String> {
     }
 }
 
+#[tauri::command]
+pub fn update_gyro_mouse(
+    use_gyro_as_mouse: bool,
+    gyro_sensitivity: f32,
+    gyro_space: Option<String>,
+    gyro_enable_button: Option<String>,
+    gyro_enable_mode: Option<String>,
+    gyro_smoothing_threshold: f32,
+) -> Result<(), String> {
+    if !(gyro_sensitivity > 0.0 && gyro_sensitivity <= 100.0) {
+        return Err("陀螺仪灵敏度必须在 0-100 范围内".to_string());
+    }
+    if gyro_smoothing_threshold < 0.0 {
+        return Err("平滑阈值不能为负数".to_string());
+    }
+
+    let mut preset = CURRENT_PRESET.write().unwrap();
+    preset.items.use_gyro_as_mouse = use_gyro_as_mouse;
+    preset.items.gyro_sensitivity = gyro_sensitivity;
+    preset.items.gyro_space = gyro_space;
+    preset.items.gyro_enable_button = gyro_enable_button;
+    preset.items.gyro_enable_mode = gyro_enable_mode;
+    preset.items.gyro_smoothing_threshold = gyro_smoothing_threshold;
+    if preset.save() {
+        Ok(())
+    } else {
+        Err("Failed to save preset".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_preset_led(led_color: Option<String>, player_led: Option<u8>) -> Result<(), String> {
+    if let Some(color) = &led_color {
+        if led::parse_color(color).is_none() {
+            return Err(format!("无效的灯条颜色: {color}"));
+        }
+    }
+    if player_led.is_some_and(|p| !(1..=4).contains(&p)) {
+        return Err("玩家指示灯编号必须在 1-4 范围内".to_string());
+    }
+
+    let mut preset = CURRENT_PRESET.write().unwrap();
+    preset.items.led_color = led_color;
+    preset.items.player_led = player_led;
+    if preset.save() {
+        Ok(())
+    } else {
+        Err("Failed to save preset".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_output_sink(output_sink: OutputSinkKind) -> Result<(), String> {
+    #[cfg(not(target_os = "linux"))]
+    if output_sink == OutputSinkKind::Uinput {
+        return Err("uinput 输出仅支持 Linux".to_string());
+    }
+
+    let mut preset = CURRENT_PRESET.write().unwrap();
+    preset.items.output_sink = output_sink;
+    if preset.save() {
+        Ok(())
+    } else {
+        Err("Failed to save preset".to_string())
+    }
+}
+
 /// 检查并创建示例预设（如果不存在）
 fn create_example_presets_if_not_exist() {
     let preset_list = check_presets_list();
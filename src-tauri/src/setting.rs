@@ -9,6 +9,7 @@ use tauri_plugin_autostart::ManagerExt;
 const SETTINGS_FILE: &str = "settings.toml";
 const DEFAULT_POLLING_FREQUENCY: u32 = 125;
 const DEFAULT_DEADZONE: u8 = 10;
+const DEFAULT_OVERLAY_PORT: u16 = 17420;
 
 pub static GLOBAL_SETTINGS: Lazy<RwLock<AppSettings>> =
     Lazy::new(|| RwLock::new(load_settings_internal()));
@@ -39,6 +40,21 @@ pub struct AppSettings {
 
     #[serde(default = "default_language")]
     pub language: String,
+
+    /// 连接手柄时同时创建虚拟 Xbox 360 手柄（仅 Linux）
+    #[serde(default = "bool_false")]
+    pub virtual_pad_enabled: bool,
+
+    /// 虚拟手柄透传物理手柄的摇杆与扳机
+    #[serde(default = "bool_true")]
+    pub virtual_pad_passthrough_analog: bool,
+
+    /// 启用本机 WebSocket 状态推送（供 OBS 浏览器源等叠加层使用）
+    #[serde(default = "bool_false")]
+    pub overlay_server_enabled: bool,
+
+    #[serde(default = "default_overlay_port")]
+    pub overlay_server_port: u16,
 }
 
 impl Default for AppSettings {
@@ -53,6 +69,10 @@ impl Default for AppSettings {
             previous_preset: "default".to_string(),
             calibration_mode: "square".to_string(),
             language: "system".to_string(),
+            virtual_pad_enabled: false,
+            virtual_pad_passthrough_analog: true,
+            overlay_server_enabled: false,
+            overlay_server_port: DEFAULT_OVERLAY_PORT,
         }
     }
 }
@@ -88,6 +108,10 @@ fn default_language() -> String {
     "system".to_string()
 }
 
+fn default_overlay_port() -> u16 {
+    DEFAULT_OVERLAY_PORT
+}
+
 /// 获取当前设置
 pub fn get_setting() -> AppSettings {
     GLOBAL_SETTINGS.read().unwrap().clone()
@@ -136,18 +160,29 @@ pub async fn save_settings() -> Result<()> {
 
 #[tauri::command]
 pub async fn update_settings(app: AppHandle, new_settings: AppSettings) -> Result<(), String> {
+    apply_settings(Some(app), new_settings).await
+}
+
+/// 更新设置，`app` 为 None（无界面模式或本地控制接口）时无法修改依赖应用句柄的开机自启动设置，
+/// 此时若请求修改 `auto_start` 则拒绝整个更新
+pub async fn apply_settings(app: Option<AppHandle>, new_settings: AppSettings) -> Result<(), String> {
     log::debug!("接收到更新设置请求: {new_settings:?}");
 
     // 1. 验证数据
     if !(1..=8000).contains(&new_settings.polling_frequency) {
         return Err("轮询频率必须在1-8000Hz范围内".to_string());
     }
+    if app.is_none() && new_settings.auto_start != GLOBAL_SETTINGS.read().unwrap().auto_start {
+        return Err("没有应用句柄，无法修改开机自启动设置".to_string());
+    }
 
     // 2. 更新全局缓存
     let mut settings_lock = GLOBAL_SETTINGS.write().unwrap();
     let old_auto_start = settings_lock.auto_start;
     let old_remember_last_connection = settings_lock.remember_last_connection; // 获取旧值
     let old_last_connected_device = settings_lock.last_connected_device.clone(); // 获取旧值
+    let old_virtual_pad_enabled = settings_lock.virtual_pad_enabled;
+    let old_overlay = (settings_lock.overlay_server_enabled, settings_lock.overlay_server_port);
     *settings_lock = new_settings.clone();
     drop(settings_lock); // 提前释放锁
 
@@ -160,17 +195,20 @@ pub async fn update_settings(app: AppHandle, new_settings: AppSettings) -> Resul
 
     // 4. 根据新旧数据差异执行其他命令
     if new_settings.auto_start != old_auto_start {
-        let autostart_manager = app.autolaunch();
-        if new_settings.auto_start {
-            if let Err(e) = autostart_manager.enable() {
-                log::error!("启用开机自启动失败: {e:?}");
+        // 没有应用句柄时已在验证阶段拒绝
+        if let Some(app) = app {
+            let autostart_manager = app.autolaunch();
+            if new_settings.auto_start {
+                if let Err(e) = autostart_manager.enable() {
+                    log::error!("启用开机自启动失败: {e:?}");
+                }
+                log::info!("已启用开机自启动");
+            } else {
+                if let Err(e) = autostart_manager.disable() {
+                    log::error!("禁用开机自启动失败: {e:?}");
+                }
+                log::info!("已禁用开机自启动");
             }
-            log::info!("已启用开机自启动");
-        } else {
-            if let Err(e) = autostart_manager.disable() {
-                log::error!("禁用开机自启动失败: {e:?}");
-            }
-            log::info!("已禁用开机自启动");
         }
     }
 
@@ -190,10 +228,50 @@ pub async fn update_settings(app: AppHandle, new_settings: AppSettings) -> Resul
         );
     }
 
+    if new_settings.virtual_pad_enabled != old_virtual_pad_enabled {
+        log::info!("虚拟手柄设置已更改: {old_virtual_pad_enabled} -> {}", new_settings.virtual_pad_enabled);
+        // 每个已连接设备的槽位各自拥有一个虚拟手柄
+        for slot in 0..crate::controller::slots::MAX_SLOTS {
+            let has_device = crate::controller::CURRENT_DEVICE.slot(slot).read().unwrap().device_path.is_some();
+            crate::controller::slots::with_slot(slot, || {
+                if new_settings.virtual_pad_enabled && has_device {
+                    crate::mapping::virtual_pad::create();
+                } else if !new_settings.virtual_pad_enabled {
+                    crate::mapping::virtual_pad::destroy();
+                }
+            });
+        }
+    }
+
+    if (new_settings.overlay_server_enabled, new_settings.overlay_server_port) != old_overlay {
+        crate::overlay::apply_settings(&new_settings);
+    }
+
     log::info!("设置已成功更新");
     Ok(())
 }
 
+/// 更新上次连接的设备并异步保存（不依赖 Tauri 应用句柄，无界面模式下也可使用）
+pub fn update_last_connected_device(device: Option<LastConnectedDevice>) {
+    {
+        let mut settings = GLOBAL_SETTINGS.write().unwrap();
+        if settings.last_connected_device == device {
+            return;
+        }
+        log::info!(
+            "last_connected_device 设置已更改: {:?} -> {:?}",
+            settings.last_connected_device,
+            device
+        );
+        settings.last_connected_device = device;
+    }
+    tauri::async_runtime::spawn(async {
+        if let Err(e) = save_settings().await {
+            log::error!("保存上次连接设备信息失败: {e:?}");
+        }
+    });
+}
+
 #[tauri::command]
 pub async fn get_current_settings() -> AppSettings {
     log::debug!("前端请求当前设置");
@@ -2,9 +2,12 @@ use std::sync::Arc;
 
 use tauri::AppHandle;
 
-use crate::{adaptive_sampler, app_state::AppState, mapping, preset, setting, controller};
+use crate::{adaptive_sampler, app_state::AppState, mapping, preset, setting, controller, ipc, overlay};
 
-pub fn initialize(app_handle: AppHandle, app_state: Arc<AppState>) {
+/// 启动核心引擎（设置、预设、映射、手柄监听）
+///
+/// `app_handle` 为 None 时以无界面模式运行（见 `headless` 模块），界面只是同一套核心之上的前端
+pub fn initialize(app_handle: Option<AppHandle>, app_state: Arc<AppState>) {
     setting::initialize();
     preset::initialize(app_state.clone());
 
@@ -12,5 +15,8 @@ pub fn initialize(app_handle: AppHandle, app_state: Arc<AppState>) {
 
     adaptive_sampler::initialize();
 
-    controller::initialize(app_handle.clone());
+    controller::initialize(app_handle);
+
+    ipc::initialize();
+    overlay::initialize();
 }
@@ -65,6 +65,10 @@ export async function queryGlobalSettings() {
         previous_preset: string;
         calibration_mode: string;
         language: string;
+        virtual_pad_enabled: boolean;
+        virtual_pad_passthrough_analog: boolean;
+        overlay_server_enabled: boolean;
+        overlay_server_port: number;
     }>("get_current_settings");
 
     console.log("queryGlobalSettings", settings);
@@ -78,6 +82,10 @@ export async function queryGlobalSettings() {
     state.previousPreset = settings.previous_preset || "default";
     state.calibration_mode = settings.calibration_mode || "square";
     state.language = settings.language || "system";
+    state.virtualPadEnabled = settings.virtual_pad_enabled || false;
+    state.virtualPadPassthroughAnalog = settings.virtual_pad_passthrough_analog ?? true;
+    state.overlayServerEnabled = settings.overlay_server_enabled || false;
+    state.overlayServerPort = settings.overlay_server_port || 17420;
 
     // Init language
     let targetLocale = state.language;
@@ -67,7 +67,22 @@ export enum ControllerButtons {
     Left,
     Right,
     Up,
-    Down
+    Down,
+
+    // PlayStation touchpad click / DualSense mute
+    Touchpad,
+    Mute,
+    TouchpadLeft,
+    TouchpadRight,
+
+    // Paddles (Xbox Elite)
+    P1,
+    P2,
+    P3,
+    P4,
+
+    // Xbox Share / Switch Capture
+    Capture
 }
 
 const controllerSvgsBtnElements = {
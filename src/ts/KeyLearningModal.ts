@@ -0,0 +1,161 @@
+import { reactive } from 'vue';
+import { appWindow, state } from "@/ts/global_states.ts";
+import { invoke } from "@tauri-apps/api/core";
+import { updateStatusMessage } from "@/ts/LeftPanel.ts";
+
+// 与后端 button_learning::LEARNABLE_BUTTONS 保持一致
+export const learnableButtons = [
+    { value: 'south', label: '下 (A / ✕)' },
+    { value: 'east', label: '右 (B / ○)' },
+    { value: 'west', label: '左 (X / □)' },
+    { value: 'north', label: '上 (Y / △)' },
+    { value: 'left_shoulder', label: 'LB / L1' },
+    { value: 'right_shoulder', label: 'RB / R1' },
+    { value: 'left_trigger', label: 'LT / L2' },
+    { value: 'right_trigger', label: 'RT / R2' },
+    { value: 'back', label: 'Back / Select' },
+    { value: 'start', label: 'Start' },
+    { value: 'guide', label: 'Guide / Home' },
+    { value: 'left_stick', label: '左摇杆按下' },
+    { value: 'right_stick', label: '右摇杆按下' },
+    { value: 'dpad_up', label: '十字键 上' },
+    { value: 'dpad_down', label: '十字键 下' },
+    { value: 'dpad_left', label: '十字键 左' },
+    { value: 'dpad_right', label: '十字键 右' },
+];
+
+// 与后端 button_learning::LEARNABLE_AXES 保持一致，hint 为学习时的操作提示
+export const learnableAxes = [
+    { value: 'left_x', label: '左摇杆 X 轴', hint: '请将左摇杆反复推向右侧' },
+    { value: 'left_y', label: '左摇杆 Y 轴', hint: '请将左摇杆反复推向上方' },
+    { value: 'right_x', label: '右摇杆 X 轴', hint: '请将右摇杆反复推向右侧' },
+    { value: 'right_y', label: '右摇杆 Y 轴', hint: '请将右摇杆反复推向上方' },
+    { value: 'left_z', label: '左扳机 (LT / L2)', hint: '请反复按下左扳机' },
+    { value: 'right_z', label: '右扳机 (RT / R2)', hint: '请反复按下右扳机' },
+];
+
+interface AxisLearnedPayload {
+    axis: string;
+    code: number | null;
+    inverted: boolean;
+    as_button: boolean;
+    replaced: string | null;
+}
+
+interface ButtonLearnedPayload {
+    button: string;
+    code: number;
+    replaced: string | null;
+}
+
+interface ButtonMappingsInvalidPayload {
+    entry: string | null;
+    error: string;
+}
+
+export const learningState = reactive({
+    learned: {} as Record<string, number | null>, // 已学习的按键 -> EvCode（已保存的映射只知道已学习，不带 EvCode）
+    waiting: '',
+    samplingAxis: '',
+    hint: '点击左侧按键后，在手柄上按下对应的物理键',
+});
+
+function labelOf(button: string) {
+    return learnableButtons.find(b => b.value === button)?.label ?? button;
+}
+
+export async function learnButton(button: string) {
+    if (learningState.samplingAxis) return;
+    try {
+        await invoke('learn_button', { button });
+        learningState.waiting = button;
+        learningState.hint = `请在手柄上按下「${labelOf(button)}」`;
+    } catch (e) { updateStatusMessage(`学习失败: ${e}`, true); }
+}
+
+// 学习完一个按键后自动进入下一个未学习的按键
+async function learnNextUnlearned(after: string) {
+    const start = learnableButtons.findIndex(b => b.value === after) + 1;
+    const next = learnableButtons.slice(start).find(b => !(b.value in learningState.learned));
+    if (next) {
+        await learnButton(next.value);
+    } else {
+        learningState.waiting = '';
+        learningState.hint = '学习完成，点击“保存”应用映射';
+    }
+}
+
+appWindow.listen("button_learned", async (event) => {
+    const payload = event.payload as ButtonLearnedPayload;
+    if (payload.replaced) {
+        delete learningState.learned[payload.replaced];
+    }
+    learningState.learned[payload.button] = payload.code;
+    await learnNextUnlearned(payload.button);
+});
+
+export async function learnAxis(axis: string) {
+    if (learningState.samplingAxis) return;
+    try {
+        await invoke('learn_axis', { axis });
+        learningState.waiting = '';
+        learningState.samplingAxis = axis;
+        learningState.hint = `${learnableAxes.find(a => a.value === axis)?.hint ?? ''}（约 2 秒）`;
+    } catch (e) { updateStatusMessage(`学习失败: ${e}`, true); }
+}
+
+appWindow.listen("axis_learned", (event) => {
+    const payload = event.payload as AxisLearnedPayload;
+    learningState.samplingAxis = '';
+    if (payload.code === null) {
+        learningState.hint = '未检测到明显的轴移动，请重试';
+        return;
+    }
+    if (payload.replaced) {
+        delete learningState.learned[payload.replaced];
+    }
+    // 数字扳机被学习为按键
+    const learnedAs = payload.as_button
+        ? (payload.axis === 'left_z' ? 'left_trigger' : 'right_trigger')
+        : payload.axis;
+    learningState.learned[learnedAs] = payload.code;
+    // 模拟扳机会替换之前学习的数字扳机
+    if (!payload.as_button && (payload.axis === 'left_z' || payload.axis === 'right_z')) {
+        delete learningState.learned[payload.axis === 'left_z' ? 'left_trigger' : 'right_trigger'];
+    }
+    learningState.hint = payload.as_button
+        ? '该扳机为数字按键，已学习为按键'
+        : `轴学习完成${payload.inverted ? '（已自动反向）' : ''}`;
+});
+
+// button_mappings.toml 损坏时后端会跳过对应条目，这里提示用户检查文件
+appWindow.listen("button_mappings_invalid", (event) => {
+    const payload = event.payload as ButtonMappingsInvalidPayload;
+    const target = payload.entry ? `条目 ${payload.entry}` : '文件';
+    updateStatusMessage(`按键映射${target}格式错误，已忽略: ${payload.error}`, true);
+});
+
+export async function openKeyLearningModal() {
+    if (!state.deviceSelected) return;
+    try {
+        const learned = await invoke<string[]>('start_button_learning', { device: state.deviceSelected });
+        learningState.learned = Object.fromEntries(learned.map(b => [b, null]));
+        learningState.waiting = '';
+        learningState.samplingAxis = '';
+        learningState.hint = '点击左侧按键后，在手柄上按下对应的物理键';
+        state.showKeyLearningModal = true;
+    } catch (e) { updateStatusMessage(`无法开始按键学习: ${e}`, true); }
+}
+
+export async function saveKeyLearning() {
+    try {
+        await invoke('stop_button_learning', { save: true });
+        updateStatusMessage('按键映射已保存并应用！');
+        state.showKeyLearningModal = false;
+    } catch (e) { updateStatusMessage(`保存失败: ${e}`, true); }
+}
+
+export async function closeKeyLearningModal() {
+    await invoke('stop_button_learning', { save: false });
+    state.showKeyLearningModal = false;
+}
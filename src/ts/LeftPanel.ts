@@ -110,6 +110,19 @@ export interface DeviceInfo {
     controller_type: ControllerType;
 }
 
+// 与后端 power::PowerState 保持一致
+export interface PowerState {
+    level: number | null;
+    charging: boolean;
+    connection: 'wired' | 'bluetooth' | 'wireless' | 'unknown';
+}
+
+interface PowerEventPayload {
+    slot: number;
+    name: string;
+    power: PowerState | null;
+}
+
 
 // 更新设备列表
 // function updateDeviceList(devices: DeviceInfo[]) {
@@ -142,6 +155,11 @@ async function queryDevice() {
     const devices = await invoke<DeviceInfo[]>("query_devices");
     // updateDeviceList(devices);
     console.log(devices);
+    applyDeviceList(devices);
+}
+
+// 用户已选择设备时不替换列表，避免打断当前选择
+function applyDeviceList(devices: DeviceInfo[]) {
     if (devices.length === 0) state.hasUserSelectedDevice = false;
     if (state.hasUserSelectedDevice) {
         state.isConnected = devices.length > 0;
@@ -264,6 +282,24 @@ appWindow.listen("physical_connect_status", async (event) => {
     await queryDevice();
 })
 
+// 设备热插拔后后端会推送最新的设备列表
+appWindow.listen("update_devices", (event) => {
+    applyDeviceList(event.payload as DeviceInfo[]);
+});
+
+// 电量 / 连接方式变化，界面只显示主槽位
+appWindow.listen("update_controller_power", (event) => {
+    const payload = event.payload as PowerEventPayload;
+    if (payload.slot === 0) {
+        state.controllerPower = payload.power;
+    }
+});
+
+appWindow.listen("controller_battery_low", (event) => {
+    const payload = event.payload as PowerEventPayload;
+    updateStatusMessage(`手柄 ${payload.name} 电量低 (${payload.power?.level ?? '?'}%)，请及时充电`, true);
+});
+
 appWindow.listen("auto_connect_success", async (event) => {
     const deviceInfo = event.payload as DeviceInfo;
     state.isConnected = true;
@@ -1,4 +1,4 @@
-import { state } from "@/ts/global_states.ts";
+import { AnalogTrigger, state } from "@/ts/global_states.ts";
 import { updateStatusMessage } from "@/ts/LeftPanel.ts";
 import { queryMappings } from "@/App.ts";
 import { startKeyDetection, stopKeyDetection } from "@/ts/RightPanel.ts";
@@ -22,6 +22,39 @@ export interface MappingUpdateConfig {
     amount?: number | null;
     check_mode?: string;
     check_mode_param?: number;
+    analog?: AnalogTrigger | null;
+}
+
+/**
+ * 根据模态窗口中的设置构建扳机模拟量配置，未启用或非扳机映射时返回 null
+ */
+function buildAnalogConfig(composed_button: string): AnalogTrigger | null {
+    if (!state.analog.enabled || !['LT', 'RT'].includes(composed_button)) {
+        return null;
+    }
+    const curve: AnalogTrigger['curve'] = state.analog.exponent === 1
+        ? { type: 'linear' }
+        : { type: 'power', exponent: state.analog.exponent };
+    switch (state.analog.mode) {
+        case 'scroll':
+            return { mode: 'scroll', max_speed: state.analog.max_speed, curve };
+        case 'mouse_speed':
+            return { mode: 'mouse_speed', max_scale: state.analog.max_scale, curve };
+        case 'key_pwm':
+            return { mode: 'key_pwm', period_ms: state.analog.period_ms, curve };
+    }
+}
+
+/**
+ * 恢复扳机模拟量设置到模态窗口状态，没有配置时重置为默认值
+ */
+export function restoreAnalogState(analog?: AnalogTrigger | null) {
+    state.analog.enabled = !!analog;
+    state.analog.mode = analog?.mode ?? 'scroll';
+    state.analog.max_speed = analog?.max_speed ?? 20;
+    state.analog.max_scale = analog?.max_scale ?? 0.4;
+    state.analog.period_ms = analog?.period_ms ?? 100;
+    state.analog.exponent = analog?.curve.type === 'power' ? analog.curve.exponent : 1.0;
 }
 
 export async function mappingsConfirm() {
@@ -74,15 +107,16 @@ export async function mappingsConfirm() {
         trigger_theshold: state.triggerTheshold,
         amount: (() => {
             const lowerCaseKey = raw_shortcut_key.toLowerCase();
-            if (lowerCaseKey.includes('mousewheelup')) {
+            if (lowerCaseKey.includes('mousewheelup') || lowerCaseKey.includes('mousewheelleft')) {
                 return -state.mapping_amount;
-            } else if (lowerCaseKey.includes('mousewheeldown')) {
+            } else if (lowerCaseKey.includes('mousewheeldown') || lowerCaseKey.includes('mousewheelright')) {
                 return state.mapping_amount;
             }
             return null;
         })(),
         check_mode: state.checkMode,
         check_mode_param: state.checkModeParam,
+        analog: buildAnalogConfig(composed_button),
     };
 
     console.log("提交的映射配置:", payload);
@@ -1,10 +1,10 @@
-import { Preset, state } from "@/ts/global_states.ts";
+import { appWindow, Preset, ResponseCurve, state } from "@/ts/global_states.ts";
 import { invoke } from "@tauri-apps/api/core";
 import { updateStatusMessage } from "@/ts/LeftPanel.ts";
 import { queryMappings, queryPresetList, refreshMappings } from "@/App.ts";
 import { nextTick } from "vue";
 import { setLanguage, translate } from "@/ts/i18n.ts";
-import { MappingUpdateConfig } from "@/ts/MappingModal.ts";
+import { MappingUpdateConfig, restoreAnalogState } from "@/ts/MappingModal.ts";
 
 
 /**
@@ -33,7 +33,11 @@ export async function updateSettings() {
         theme: state.theme,
         polling_frequency: state.pollingFrequency,
         previous_preset: state.previousPreset,
-        language: state.language
+        language: state.language,
+        virtual_pad_enabled: state.virtualPadEnabled,
+        virtual_pad_passthrough_analog: state.virtualPadPassthroughAnalog,
+        overlay_server_enabled: state.overlayServerEnabled,
+        overlay_server_port: state.overlayServerPort
     };
 
     try {
@@ -141,6 +145,9 @@ export async function editButtonMap(id: number) {
             state.checkModeParam = 300; // 重置为默认值
         }
 
+        // 7. 恢复 扳机模拟量设置
+        restoreAnalogState(mapping.analog);
+
         // 使用转换后的中文值和恢复的状态打开模态窗口
         console.log("编辑按钮映射", id);
         await openButtonMapModal("编辑按键映射", mapping.composed_button, display_key, mapping.id);
@@ -182,6 +189,7 @@ export async function addButtonMap() {
     // 重置 check mode 和 param 为默认值
     state.checkMode = 'single';
     state.checkModeParam = 300;
+    restoreAnalogState(null);
 
     await openButtonMapModal("添加按键映射");
 }
@@ -313,10 +321,14 @@ function handleWheel(e: any) {
     state.currentKeys.shift = e.shiftKey;
     state.currentKeys.alt = e.altKey;
     state.currentKeys.meta = e.metaKey;
-    if (e.deltaY < 0 || e.deltaX < 0) {
+    if (e.deltaY < 0) {
         state.currentKeys.key = 'MouseWheelUp';
-    } else if (e.deltaY > 0 || e.deltaX > 0) {
+    } else if (e.deltaY > 0) {
         state.currentKeys.key = 'MouseWheelDown';
+    } else if (e.deltaX < 0) {
+        state.currentKeys.key = 'MouseWheelLeft';
+    } else if (e.deltaX > 0) {
+        state.currentKeys.key = 'MouseWheelRight';
     }
 
     updateKeyDisplay();
@@ -360,6 +372,11 @@ const buttonTextMapLists = {
         { value: 'DPadDown' },
         { value: 'DPadLeft' },
         { value: 'DPadRight' },
+        { value: 'Share' },
+        { value: 'P1' },
+        { value: 'P2' },
+        { value: 'P3' },
+        { value: 'P4' },
     ],
     ps: [
         { value: 'Cross' },
@@ -377,6 +394,10 @@ const buttonTextMapLists = {
         { value: 'DPadDown' },
         { value: 'DPadLeft' },
         { value: 'DPadRight' },
+        { value: 'Touchpad' },
+        { value: 'TouchpadLeft' },
+        { value: 'TouchpadRight' },
+        { value: 'Mute' },
     ],
     switch: [ // 新增 Switch 布局
         { value: 'A' },
@@ -394,6 +415,7 @@ const buttonTextMapLists = {
         { value: 'DPadDown' },
         { value: 'DPadLeft' },
         { value: 'DPadRight' },
+        { value: 'Capture' },
     ]
 }
 
@@ -501,6 +523,26 @@ export async function saveDeadzoneSettings() {
     }
 }
 
+export async function saveDeadzoneShaping() {
+    const items = state.current_preset.items;
+    try {
+        await invoke("update_deadzone_shaping", {
+            outerDeadzone: items.outer_deadzone ?? 0,
+            outerDeadzoneLeft: items.outer_deadzone_left ?? 0,
+            antiDeadzone: items.anti_deadzone ?? 0,
+            antiDeadzoneLeft: items.anti_deadzone_left ?? 0,
+            triggerDeadzoneLeft: items.trigger_deadzone_left ?? 0,
+            triggerDeadzoneRight: items.trigger_deadzone_right ?? 0,
+            triggerOuterDeadzoneLeft: items.trigger_outer_deadzone_left ?? 0,
+            triggerOuterDeadzoneRight: items.trigger_outer_deadzone_right ?? 0,
+        });
+        updateStatusMessage("死区设置已保存", false);
+    } catch (error) {
+        console.error("保存死区设置失败:", error);
+        updateStatusMessage(`保存死区设置失败: ${error}`, true);
+    }
+}
+
 
 export async function switchPreset() {
     if (!invoke) return;
@@ -659,6 +701,72 @@ export async function updateStickRotationThreshold() {
     }
 }
 
+export async function updateStickCurve(stick: "left" | "right") {
+    try {
+        await invoke("update_stick_curve", {
+            stick,
+            curve: state.current_preset.items[`stick_curve_${stick}`]
+        });
+    } catch (error) {
+        console.error("保存摇杆响应曲线失败:", error);
+        updateStatusMessage(`保存摇杆响应曲线失败: ${error}`, true);
+    }
+}
+
+/**
+ * 切换摇杆响应曲线类型，并填入该类型的默认参数
+ */
+export async function setStickCurveType(stick: "left" | "right", type: ResponseCurve["type"]) {
+    const curves: Record<ResponseCurve["type"], ResponseCurve> = {
+        linear: { type: "linear" },
+        power: { type: "power", exponent: 2.0 },
+        s_curve: { type: "s_curve", strength: 0.5 },
+        piecewise: { type: "piecewise", points: [[0.5, 0.3]] },
+    };
+    state.current_preset.items[`stick_curve_${stick}`] = curves[type];
+    await updateStickCurve(stick);
+}
+
+export async function addStickCurvePoint(stick: "left" | "right") {
+    const curve = state.current_preset.items[`stick_curve_${stick}`];
+    if (curve.type !== "piecewise") return;
+    const lastX = curve.points.length > 0 ? curve.points[curve.points.length - 1][0] : 0;
+    const x = Math.round((lastX + 1) / 2 * 100) / 100;
+    curve.points.push([x, x]);
+    await updateStickCurve(stick);
+}
+
+export async function removeStickCurvePoint(stick: "left" | "right", index: number) {
+    const curve = state.current_preset.items[`stick_curve_${stick}`];
+    if (curve.type !== "piecewise") return;
+    curve.points.splice(index, 1);
+    await updateStickCurve(stick);
+}
+
+export async function updateStickScroll(stick: "left" | "right") {
+    try {
+        await invoke("update_stick_scroll", {
+            stick,
+            config: state.current_preset.items[`stick_scroll_${stick}`]
+        });
+    } catch (error) {
+        console.error("保存摇杆模拟滚轮设置失败:", error);
+        updateStatusMessage(`保存摇杆模拟滚轮设置失败: ${error}`, true);
+    }
+}
+
+export async function updateStickDirection(stick: "left" | "right") {
+    try {
+        await invoke("update_stick_direction", {
+            stick,
+            config: state.current_preset.items[`stick_direction_${stick}`]
+        });
+    } catch (error) {
+        console.error("保存摇杆方向按键设置失败:", error);
+        updateStatusMessage(`保存摇杆方向按键设置失败: ${error}`, true);
+    }
+}
+
 export async function updateMouseMoveSpeed() {
     try {
         await invoke("update_mouse_move_speed", {
@@ -668,4 +776,95 @@ export async function updateMouseMoveSpeed() {
         console.error("保存鼠标移动速度失败:", error);
         updateStatusMessage(`保存鼠标移动速度失败: ${error}`, true);
     }
-}
\ No newline at end of file
+}
+
+export async function updateGyroMouse() {
+    const items = state.current_preset.items;
+    try {
+        await invoke("update_gyro_mouse", {
+            useGyroAsMouse: items.use_gyro_as_mouse,
+            gyroSensitivity: Number(items.gyro_sensitivity),
+            gyroSpace: items.gyro_space,
+            gyroEnableButton: items.gyro_enable_button || null,
+            gyroEnableMode: items.gyro_enable_mode,
+            gyroSmoothingThreshold: Number(items.gyro_smoothing_threshold)
+        });
+    } catch (error) {
+        console.error("保存陀螺仪鼠标设置失败:", error);
+        updateStatusMessage(`保存陀螺仪鼠标设置失败: ${error}`, true);
+    }
+}
+
+export async function updatePresetLed() {
+    const items = state.current_preset.items;
+    try {
+        await invoke("update_preset_led", {
+            ledColor: items.led_color || null,
+            playerLed: items.player_led
+        });
+    } catch (error) {
+        console.error("保存灯光设置失败:", error);
+        updateStatusMessage(`保存灯光设置失败: ${error}`, true);
+    }
+}
+
+export async function updateOutputSink() {
+    try {
+        await invoke("update_output_sink", {
+            outputSink: state.current_preset.items.output_sink ?? "enigo"
+        });
+    } catch (error) {
+        console.error("保存输出后端失败:", error);
+        updateStatusMessage(`保存输出后端失败: ${error}`, true);
+    }
+}
+
+// ---------- 输入录制 / 回放（主槽位） ----------
+export async function refreshRecordings() {
+    try {
+        state.recordings = await invoke<string[]>("list_recordings");
+    } catch (error) {
+        console.error("读取录制列表失败:", error);
+    }
+}
+
+export async function toggleRecording() {
+    try {
+        if (state.isRecording) {
+            const name = await invoke<string | null>("stop_recording", {});
+            state.isRecording = false;
+            updateStatusMessage(`录制已保存: ${name ?? ''}`);
+            await refreshRecordings();
+            state.selectedRecording = name;
+        } else {
+            const name = await invoke<string>("start_recording", {});
+            state.isRecording = true;
+            updateStatusMessage(`开始录制: ${name}`);
+        }
+    } catch (error) {
+        console.error("录制操作失败:", error);
+        updateStatusMessage(`录制操作失败: ${error}`, true);
+    }
+}
+
+export async function toggleReplay() {
+    try {
+        if (state.isReplaying) {
+            await invoke("stop_replay", {});
+        } else if (state.selectedRecording) {
+            await invoke("start_replay", { name: state.selectedRecording, looped: state.replayLoop });
+            state.isReplaying = true;
+            updateStatusMessage(`开始回放: ${state.selectedRecording}`);
+        }
+    } catch (error) {
+        console.error("回放操作失败:", error);
+        updateStatusMessage(`回放操作失败: ${error}`, true);
+    }
+}
+
+appWindow.listen("replay_finished", (event) => {
+    if (event.payload === 0) {
+        state.isReplaying = false;
+        updateStatusMessage("回放结束");
+    }
+});
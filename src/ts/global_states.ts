@@ -2,7 +2,7 @@
 import {nextTick, reactive} from "vue";
 import {getCurrentWindow} from "@tauri-apps/api/window";
 import {invoke} from "@tauri-apps/api/core";
-import {DeviceInfo} from "@/ts/LeftPanel.ts";
+import {DeviceInfo, PowerState} from "@/ts/LeftPanel.ts";
 import { locale } from "@tauri-apps/plugin-os";
 
 let appWindow = getCurrentWindow();
@@ -18,17 +18,80 @@ export interface Preset {
     items: {
         deadzone: number;
         deadzone_left: number;
+        outer_deadzone: number;
+        outer_deadzone_left: number;
+        anti_deadzone: number;
+        anti_deadzone_left: number;
+        trigger_deadzone_left: number;
+        trigger_deadzone_right: number;
+        trigger_outer_deadzone_left: number;
+        trigger_outer_deadzone_right: number;
+        stick_curve_left: ResponseCurve;
+        stick_curve_right: ResponseCurve;
         mappings_file_name: string;
         use_stick_as_mouse: boolean;
         stick_as_mouse_simulation: string | null;
+        stick_scroll_left: StickScrollConfig;
+        stick_scroll_right: StickScrollConfig;
         sub_preset_name: string | null;
         sub_preset_switch_button: string | null;
         sub_preset_switch_mode: string | null;
         stick_rotate_trigger_threshold: 15,
+        stick_direction_left: StickDirectionConfig;
+        stick_direction_right: StickDirectionConfig;
         move_speed: number,
+        use_gyro_as_mouse: boolean;
+        gyro_sensitivity: number;
+        gyro_space: string | null;
+        gyro_enable_button: string | null;
+        gyro_enable_mode: string | null;
+        gyro_smoothing_threshold: number;
+        led_color: string | null;
+        player_led: number | null;
+        output_sink: "enigo" | "uinput" | "recording";
     }
 }
 
+/**
+ * 摇杆模拟滚轮配置，对应后端的 StickScrollConfig
+ */
+export interface StickScrollConfig {
+    enabled: boolean;
+    speed: number;
+    horizontal: boolean;
+    invert: boolean;
+}
+
+/**
+ * 摇杆方向虚拟按键的判定配置，对应后端的 StickDirectionConfig
+ */
+export interface StickDirectionConfig {
+    mode: "four_way" | "eight_way";
+    activation_radius: number;
+    release_radius: number;
+    overlap_angle: number;
+    hysteresis_angle: number;
+}
+
+export type AnalogMode = "scroll" | "mouse_speed" | "key_pwm";
+
+export type ResponseCurve =
+    | { type: "linear" }
+    | { type: "power", exponent: number }
+    | { type: "s_curve", strength: number }
+    | { type: "piecewise", points: [number, number][] };
+
+/**
+ * 扳机模拟量映射配置，对应后端的 AnalogTrigger
+ */
+export interface AnalogTrigger {
+    mode: AnalogMode;
+    max_speed?: number;
+    max_scale?: number;
+    period_ms?: number;
+    curve: ResponseCurve;
+}
+
 export interface UpdateInfo {
     version: string;
     body: string;
@@ -61,6 +124,10 @@ export const state = reactive({
     pollingFrequency: 125,
     previousPreset: "default",
     calibration_mode: "square",
+    virtualPadEnabled: false,
+    virtualPadPassthroughAnalog: true,
+    overlayServerEnabled: false,
+    overlayServerPort: 17420,
 
     connectButtonDisabled: false,
 
@@ -83,6 +150,7 @@ export const state = reactive({
     showUpdateModal: false,
     showCaliModal: false,
     showPresetEditModal: false,
+    showKeyLearningModal: false,
 
     buttonsText: [{value: ''}],
     sticksText: [
@@ -92,6 +160,22 @@ export const state = reactive({
         {value: 'LeftStickCCW'},
         {value: 'RightStickCW'},
         {value: 'RightStickCCW'},
+        {value: 'LeftStickUp'},
+        {value: 'LeftStickDown'},
+        {value: 'LeftStickLeft'},
+        {value: 'LeftStickRight'},
+        {value: 'LeftStickUpLeft'},
+        {value: 'LeftStickUpRight'},
+        {value: 'LeftStickDownLeft'},
+        {value: 'LeftStickDownRight'},
+        {value: 'RightStickUp'},
+        {value: 'RightStickDown'},
+        {value: 'RightStickLeft'},
+        {value: 'RightStickRight'},
+        {value: 'RightStickUpLeft'},
+        {value: 'RightStickUpRight'},
+        {value: 'RightStickDownLeft'},
+        {value: 'RightStickDownRight'},
     ],
 
     // main.js 原有的状态
@@ -102,6 +186,14 @@ export const state = reactive({
     connectIcon: "",
 
     isConnected: false,
+    controllerPower: null as PowerState | null,
+
+    // 输入录制 / 回放
+    recordings: [] as string[],
+    selectedRecording: null as string | null,
+    isRecording: false,
+    isReplaying: false,
+    replayLoop: false,
     deviceType: 'xbox',
     mappings: [] as any[],
     editingMappingId: null as number | null,
@@ -113,6 +205,15 @@ export const state = reactive({
         acceleration: 0.8,
     },
     triggerTheshold: 0.3,
+    // 扳机模拟量映射（仅 LT / RT）
+    analog: {
+        enabled: false,
+        mode: "scroll" as AnalogMode,
+        max_speed: 20,
+        max_scale: 0.4,
+        period_ms: 100,
+        exponent: 1.0,
+    },
     mapping_amount: 1, // For mouse wheel amount in mapping modal
     checkMode: "single",
     checkModeParam: 300,